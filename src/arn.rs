@@ -0,0 +1,297 @@
+//! ARNs of vector buckets and indexes.
+//!
+//! ```
+//! use s3_vectors::{IndexArn, VectorBucketArn};
+//!
+//! let index: IndexArn = "arn:aws:s3vectors:eu-west-1:111122223333:bucket/docs/index/embeddings"
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(index.bucket.bucket_name, "docs");
+//! assert_eq!(index.index_name, "embeddings");
+//! assert_eq!(index.region(), "eu-west-1");
+//!
+//! let bucket = VectorBucketArn::new("eu-west-1", "111122223333", "docs");
+//! assert_eq!(bucket.index("embeddings"), index);
+//! assert!("arn:aws:s3:::docs".parse::<VectorBucketArn>().is_err());
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::region::RegionCatalogue;
+use crate::validation::{validate_bucket_name, validate_index_name};
+
+/// `arn:<partition>:s3vectors:<region>:<account>:bucket/<name>`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VectorBucketArn {
+    pub partition: String,
+    pub region: String,
+    pub account_id: String,
+    pub bucket_name: String,
+}
+
+/// `arn:<partition>:s3vectors:<region>:<account>:bucket/<name>/index/<name>`
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IndexArn {
+    pub bucket: VectorBucketArn,
+    pub index_name: String,
+}
+
+/// Either kind of S3 Vectors ARN
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceArn {
+    Bucket(VectorBucketArn),
+    Index(IndexArn),
+}
+
+/// Why an ARN was rejected
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid ARN '{arn}': {message}")]
+pub struct ArnError {
+    pub arn: String,
+    pub message: String,
+}
+
+impl ArnError {
+    fn new(arn: &str, message: impl Into<String>) -> Self {
+        Self {
+            arn: arn.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl VectorBucketArn {
+    /// ARN of a bucket, in the partition `region` belongs to
+    pub fn new(region: &str, account_id: &str, bucket_name: &str) -> Self {
+        let partition = RegionCatalogue::global()
+            .partition(region)
+            .map_or("aws", |p| p.id.as_str());
+        Self {
+            partition: partition.to_string(),
+            region: region.to_string(),
+            account_id: account_id.to_string(),
+            bucket_name: bucket_name.to_string(),
+        }
+    }
+
+    /// ARN of an index in this bucket
+    pub fn index(&self, index_name: &str) -> IndexArn {
+        IndexArn {
+            bucket: self.clone(),
+            index_name: index_name.to_string(),
+        }
+    }
+}
+
+impl IndexArn {
+    pub fn region(&self) -> &str {
+        &self.bucket.region
+    }
+}
+
+impl ResourceArn {
+    /// The bucket, or the bucket the index is in
+    pub fn bucket(&self) -> &VectorBucketArn {
+        match self {
+            ResourceArn::Bucket(bucket) => bucket,
+            ResourceArn::Index(index) => &index.bucket,
+        }
+    }
+
+    pub fn region(&self) -> &str {
+        &self.bucket().region
+    }
+}
+
+impl FromStr for ResourceArn {
+    type Err = ArnError;
+
+    fn from_str(arn: &str) -> Result<Self, ArnError> {
+        let invalid = || {
+            ArnError::new(
+                arn,
+                "expected arn:aws:s3vectors:<region>:<account>:bucket/<name>[/index/<name>]",
+            )
+        };
+        let parts: Vec<&str> = arn.splitn(6, ':').collect();
+        let [prefix, partition, service, region, account_id, resource] = parts[..] else {
+            return Err(invalid());
+        };
+        if prefix != "arn" || service != "s3vectors" {
+            return Err(invalid());
+        }
+        let Some(known) = RegionCatalogue::global().partition_by_id(partition) else {
+            return Err(ArnError::new(
+                arn,
+                format!("unknown partition '{partition}'"),
+            ));
+        };
+        if !known.matches(region) {
+            return Err(ArnError::new(
+                arn,
+                format!("'{region}' is not a region of partition '{partition}'"),
+            ));
+        }
+        if account_id.len() != 12 || !account_id.chars().all(|c| c.is_ascii_digit()) {
+            return Err(ArnError::new(
+                arn,
+                format!("account '{account_id}' is not a 12-digit account ID"),
+            ));
+        }
+
+        let resource = resource.strip_prefix("bucket/").ok_or_else(invalid)?;
+        let (bucket_name, index_name) = match resource.split_once("/index/") {
+            Some((bucket, index)) => (bucket, Some(index)),
+            None => (resource, None),
+        };
+        validate_bucket_name(bucket_name).map_err(|e| ArnError::new(arn, e.to_string()))?;
+        let bucket = VectorBucketArn {
+            partition: partition.to_string(),
+            region: region.to_string(),
+            account_id: account_id.to_string(),
+            bucket_name: bucket_name.to_string(),
+        };
+        match index_name {
+            Some(index_name) => {
+                validate_index_name(index_name).map_err(|e| ArnError::new(arn, e.to_string()))?;
+                Ok(ResourceArn::Index(bucket.index(index_name)))
+            }
+            None => Ok(ResourceArn::Bucket(bucket)),
+        }
+    }
+}
+
+impl FromStr for VectorBucketArn {
+    type Err = ArnError;
+
+    fn from_str(arn: &str) -> Result<Self, ArnError> {
+        match arn.parse()? {
+            ResourceArn::Bucket(bucket) => Ok(bucket),
+            ResourceArn::Index(_) => Err(ArnError::new(
+                arn,
+                "this is an index ARN, not a vector bucket ARN",
+            )),
+        }
+    }
+}
+
+impl FromStr for IndexArn {
+    type Err = ArnError;
+
+    fn from_str(arn: &str) -> Result<Self, ArnError> {
+        match arn.parse()? {
+            ResourceArn::Index(index) => Ok(index),
+            ResourceArn::Bucket(_) => Err(ArnError::new(
+                arn,
+                "this is a vector bucket ARN, not an index ARN",
+            )),
+        }
+    }
+}
+
+impl fmt::Display for VectorBucketArn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "arn:{}:s3vectors:{}:{}:bucket/{}",
+            self.partition, self.region, self.account_id, self.bucket_name
+        )
+    }
+}
+
+impl fmt::Display for IndexArn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/index/{}", self.bucket, self.index_name)
+    }
+}
+
+impl fmt::Display for ResourceArn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceArn::Bucket(bucket) => bucket.fmt(f),
+            ResourceArn::Index(index) => index.fmt(f),
+        }
+    }
+}
+
+// ARNs are strings on the wire
+macro_rules! string_serde {
+    ($($ty:ty),*) => {$(
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(serde::de::Error::custom)
+            }
+        }
+    )*};
+}
+
+string_serde!(VectorBucketArn, IndexArn, ResourceArn);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUCKET: &str = "arn:aws:s3vectors:us-east-1:111122223333:bucket/docs";
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        let bucket: VectorBucketArn = BUCKET.parse().unwrap();
+        assert_eq!(
+            bucket,
+            VectorBucketArn::new("us-east-1", "111122223333", "docs")
+        );
+        assert_eq!(bucket.to_string(), BUCKET);
+
+        let index: IndexArn = format!("{BUCKET}/index/embeddings").parse().unwrap();
+        assert_eq!(index.bucket, bucket);
+        assert_eq!(index.to_string(), format!("{BUCKET}/index/embeddings"));
+        assert_eq!(
+            serde_json::to_value(&index).unwrap(),
+            serde_json::json!(format!("{BUCKET}/index/embeddings"))
+        );
+
+        let gov: ResourceArn = "arn:aws-us-gov:s3vectors:us-gov-west-1:111122223333:bucket/docs"
+            .parse()
+            .unwrap();
+        assert_eq!(gov.region(), "us-gov-west-1");
+        assert_eq!(
+            VectorBucketArn::new("cn-north-1", "111122223333", "docs").partition,
+            "aws-cn"
+        );
+    }
+
+    #[test]
+    fn test_invalid_arns() {
+        // The wrong kind of ARN for the type
+        assert!(BUCKET.parse::<IndexArn>().is_err());
+        assert!(format!("{BUCKET}/index/embeddings")
+            .parse::<VectorBucketArn>()
+            .unwrap_err()
+            .message
+            .contains("index ARN"));
+
+        for arn in [
+            "docs",
+            "arn:aws:s3:::docs",
+            "arn:aws:s3vectors:us-east-1:111122223333:docs",
+            "arn:aws:s3vectors:us-east-1:1234:bucket/docs",
+            "arn:aws:s3vectors:cn-north-1:111122223333:bucket/docs",
+            "arn:aws-xx:s3vectors:us-east-1:111122223333:bucket/docs",
+            "arn:aws:s3vectors:us-east-1:111122223333:bucket/Docs",
+            "arn:aws:s3vectors:us-east-1:111122223333:bucket/docs/index/",
+        ] {
+            assert!(arn.parse::<ResourceArn>().is_err(), "{arn}");
+        }
+    }
+}
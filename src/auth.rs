@@ -1,16 +1,19 @@
+use std::sync::Arc;
+
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
 
+use crate::credentials::{Credentials, CredentialsProvider};
+
 type HmacSha256 = Hmac<Sha256>;
 
 #[derive(Clone, Debug)]
 pub struct AwsV4Signer {
-    access_key_id: String,
-    secret_access_key: String,
-    session_token: Option<String>,
+    credentials: Arc<dyn CredentialsProvider>,
     region: String,
+    service: String,
 }
 
 impl AwsV4Signer {
@@ -20,14 +23,40 @@ impl AwsV4Signer {
         session_token: Option<String>,
         region: String,
     ) -> Self {
+        Self::with_provider(
+            Arc::new(Credentials::new(
+                access_key_id,
+                secret_access_key,
+                session_token,
+            )),
+            region,
+        )
+    }
+
+    /// Sign with credentials fetched from `provider` for every request, so
+    /// that refreshed session credentials are picked up automatically
+    pub fn with_provider(provider: Arc<dyn CredentialsProvider>, region: String) -> Self {
+        let service = crate::RegionCatalogue::global()
+            .signing_name(&region)
+            .to_string();
         Self {
-            access_key_id,
-            secret_access_key,
-            session_token,
+            credentials: provider,
             region,
+            service,
         }
     }
 
+    /// The same credentials, signing for S3 Vectors in another region
+    pub fn for_region(&self, region: &str) -> Self {
+        Self::with_provider(self.credentials.clone(), region.to_string())
+    }
+
+    /// Sign for another AWS service (e.g. `sts`) instead of S3 Vectors
+    pub fn with_service(mut self, service: &str) -> Self {
+        self.service = service.to_string();
+        self
+    }
+
     pub async fn sign_request(
         &self,
         method: &str,
@@ -35,7 +64,19 @@ impl AwsV4Signer {
         headers: std::collections::HashMap<String, String>,
         payload: &[u8],
     ) -> Result<std::collections::HashMap<String, String>> {
-        let now = Utc::now();
+        let credentials = self.credentials.provide_credentials().await?;
+        self.sign_request_at(method, url, headers, payload, &credentials, Utc::now())
+    }
+
+    fn sign_request_at(
+        &self,
+        method: &str,
+        url: &str,
+        headers: std::collections::HashMap<String, String>,
+        payload: &[u8],
+        credentials: &Credentials,
+        now: DateTime<Utc>,
+    ) -> Result<std::collections::HashMap<String, String>> {
         let date_stamp = now.format("%Y%m%d").to_string();
         let time_stamp = now.format("%Y%m%dT%H%M%SZ").to_string();
 
@@ -60,39 +101,42 @@ impl AwsV4Signer {
         signed_headers.insert("host".to_string(), host);
         signed_headers.insert("x-amz-date".to_string(), time_stamp.clone());
 
-        if let Some(token) = &self.session_token {
-            signed_headers.insert("x-amz-security-token".to_string(), token.clone());
+        if let Some(token) = credentials.session_token() {
+            signed_headers.insert("x-amz-security-token".to_string(), token.to_string());
         }
 
         // Calculate payload hash
         let payload_hash = hex::encode(Sha256::digest(payload));
         signed_headers.insert("x-amz-content-sha256".to_string(), payload_hash.clone());
 
-        // Extract URI from URL
-        let uri = url_parsed.path().to_string();
+        let (uri, query) = canonical_uri_and_query(&url_parsed);
 
         // Create canonical request
         let canonical_headers = self.create_canonical_headers_map(&signed_headers);
         let signed_headers_str = self.get_signed_headers_map(&signed_headers);
 
         let canonical_request = format!(
-            "{method}\n{uri}\n{}\n{canonical_headers}\n{signed_headers_str}\n{payload_hash}",
-            "", // query string
+            "{method}\n{uri}\n{query}\n{canonical_headers}\n{signed_headers_str}\n{payload_hash}",
         );
 
         // Create string to sign
         let request_hash = hex::encode(Sha256::digest(canonical_request.as_bytes()));
-        let credential_scope = format!("{date_stamp}/{}/s3vectors/aws4_request", self.region);
+        let credential_scope =
+            format!("{date_stamp}/{}/{}/aws4_request", self.region, self.service);
         let string_to_sign =
             format!("AWS4-HMAC-SHA256\n{time_stamp}\n{credential_scope}\n{request_hash}");
 
         // Calculate signature
-        let signature = self.calculate_signature(&date_stamp, &string_to_sign)?;
+        let signature = self.calculate_signature(
+            credentials.secret_access_key(),
+            &date_stamp,
+            &string_to_sign,
+        )?;
 
         // Create authorization header
         let auth_header = format!(
             "AWS4-HMAC-SHA256 Credential={}/{credential_scope}, SignedHeaders={signed_headers_str}, Signature={signature}",
-            self.access_key_id
+            credentials.access_key_id()
         );
 
         signed_headers.insert("authorization".to_string(), auth_header);
@@ -125,11 +169,16 @@ impl AwsV4Signer {
         signed.join(";")
     }
 
-    fn calculate_signature(&self, date_stamp: &str, string_to_sign: &str) -> Result<String> {
-        let k_secret = format!("AWS4{}", self.secret_access_key);
+    fn calculate_signature(
+        &self,
+        secret_access_key: &str,
+        date_stamp: &str,
+        string_to_sign: &str,
+    ) -> Result<String> {
+        let k_secret = format!("AWS4{secret_access_key}");
         let k_date = sign(k_secret.as_bytes(), date_stamp.as_bytes())?;
         let k_region = sign(&k_date, self.region.as_bytes())?;
-        let k_service = sign(&k_region, b"s3vectors")?;
+        let k_service = sign(&k_region, self.service.as_bytes())?;
         let k_signing = sign(&k_service, b"aws4_request")?;
         let signature = sign(&k_signing, string_to_sign.as_bytes())?;
 
@@ -137,9 +186,187 @@ impl AwsV4Signer {
     }
 }
 
+/// Percent-encode everything except RFC 3986 unreserved characters, keeping
+/// `/` when encoding a path
+pub(crate) fn uri_encode(input: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// Canonical URI and query string of a SigV4 request. Like every service but
+/// S3, the already-encoded path is encoded again; query parameters are
+/// encoded and sorted.
+fn canonical_uri_and_query(url: &url::Url) -> (String, String) {
+    let uri = uri_encode(url.path(), true);
+    let mut params: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| (uri_encode(&key, false), uri_encode(&value, false)))
+        .collect();
+    params.sort();
+    let query = params
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&");
+    (uri, query)
+}
+
 fn sign(key: &[u8], msg: &[u8]) -> Result<Vec<u8>> {
     let mut mac = HmacSha256::new_from_slice(key)
         .map_err(|e| anyhow::anyhow!("Failed to create HMAC: {e}"))?;
     mac.update(msg);
     Ok(mac.finalize().into_bytes().to_vec())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn test_signer() -> AwsV4Signer {
+        AwsV4Signer::new(
+            "AKIDEXAMPLE".to_string(),
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            None,
+            "us-east-1".to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_sign_request_includes_custom_port_in_host() {
+        let headers = test_signer()
+            .sign_request(
+                "POST",
+                "http://localhost:8080/ListVectorBuckets",
+                HashMap::new(),
+                b"{}",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(headers["host"], "localhost:8080");
+        assert!(headers["authorization"].contains("/us-east-1/s3vectors/aws4_request"));
+        assert!(headers["authorization"].contains("SignedHeaders=host;"));
+    }
+
+    #[tokio::test]
+    async fn test_sign_request_omits_default_port_from_host() {
+        let headers = test_signer()
+            .sign_request(
+                "POST",
+                "https://s3vectors.us-east-1.api.aws:443/ListVectorBuckets",
+                HashMap::new(),
+                b"{}",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(headers["host"], "s3vectors.us-east-1.api.aws");
+    }
+
+    #[test]
+    fn test_sign_request_signs_endpoint_base_path() {
+        let signer = test_signer();
+        let credentials = Credentials::new(
+            "AKIDEXAMPLE",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            None,
+        );
+        let now = Utc::now();
+        let with_path = signer
+            .sign_request_at(
+                "POST",
+                "http://localhost:8080/proxy/ListVectorBuckets",
+                HashMap::new(),
+                b"{}",
+                &credentials,
+                now,
+            )
+            .unwrap();
+        let without_path = signer
+            .sign_request_at(
+                "POST",
+                "http://localhost:8080/ListVectorBuckets",
+                HashMap::new(),
+                b"{}",
+                &credentials,
+                now,
+            )
+            .unwrap();
+
+        // Same timestamp and payload: only the canonical URI differs
+        assert_ne!(with_path["authorization"], without_path["authorization"]);
+    }
+
+    #[test]
+    fn test_canonical_uri_and_query() {
+        let url = url::Url::parse(
+            "https://s3vectors.us-east-1.api.aws/tags/arn%3Aaws%3As3vectors%3Aus-east-1%3A111122223333%3Abucket%2Fdocs?tagKeys=team&tagKeys=env%20name",
+        )
+        .unwrap();
+        let (uri, query) = canonical_uri_and_query(&url);
+
+        assert_eq!(
+            uri,
+            "/tags/arn%253Aaws%253As3vectors%253Aus-east-1%253A111122223333%253Abucket%252Fdocs"
+        );
+        assert_eq!(query, "tagKeys=env%20name&tagKeys=team");
+
+        let plain = url::Url::parse("http://localhost:8080/ListVectorBuckets").unwrap();
+        assert_eq!(
+            canonical_uri_and_query(&plain),
+            ("/ListVectorBuckets".to_string(), String::new())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sign_request_fetches_credentials_per_request() {
+        use crate::credentials::ProvideCredentials;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Rotates to a new session on every call
+        #[derive(Debug, Default)]
+        struct RotatingProvider(AtomicUsize);
+
+        impl CredentialsProvider for RotatingProvider {
+            fn provide_credentials(&self) -> ProvideCredentials<'_> {
+                Box::pin(async move {
+                    let n = self.0.fetch_add(1, Ordering::SeqCst);
+                    Ok(Credentials::new(
+                        format!("ASIA{n}"),
+                        "secret",
+                        Some(format!("token-{n}")),
+                    ))
+                })
+            }
+        }
+
+        let signer = AwsV4Signer::with_provider(
+            Arc::new(RotatingProvider::default()),
+            "us-east-1".to_string(),
+        );
+        let url = "https://s3vectors.us-east-1.api.aws/ListVectorBuckets";
+        let first = signer
+            .sign_request("POST", url, HashMap::new(), b"{}")
+            .await
+            .unwrap();
+        let second = signer
+            .sign_request("POST", url, HashMap::new(), b"{}")
+            .await
+            .unwrap();
+
+        assert!(first["authorization"].contains("Credential=ASIA0/"));
+        assert_eq!(first["x-amz-security-token"], "token-0");
+        assert!(second["authorization"].contains("Credential=ASIA1/"));
+        assert_eq!(second["x-amz-security-token"], "token-1");
+    }
+}
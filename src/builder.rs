@@ -0,0 +1,379 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::credentials::{CachingProvider, Credentials, CredentialsProvider};
+use crate::retry::{RetryConfig, RetryMode, RetryStrategy};
+use crate::{auth, resolve_endpoint, S3VectorsClient, HTTP_CLIENT};
+
+/// Builder for [`S3VectorsClient`] with per-client HTTP settings.
+///
+/// Clients built without any HTTP overrides share the process-wide default
+/// HTTP client; any override creates a dedicated `reqwest::Client`, so two
+/// clients with different timeouts or proxies can coexist.
+#[derive(Debug)]
+pub struct S3VectorsClientBuilder {
+    region: String,
+    endpoint: Option<String>,
+    credentials: Option<Arc<dyn CredentialsProvider>>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    retry: RetryConfig,
+    retry_strategy: Option<Arc<dyn RetryStrategy>>,
+    proxy: Option<String>,
+    ca_bundle: Option<PathBuf>,
+    accept_invalid_certs: bool,
+    user_agent_suffix: Option<String>,
+    http_client: Option<reqwest::Client>,
+}
+
+impl S3VectorsClientBuilder {
+    pub fn new(region: &str) -> Self {
+        Self {
+            region: region.to_string(),
+            endpoint: None,
+            credentials: None,
+            connect_timeout: None,
+            read_timeout: None,
+            timeout: None,
+            retry: RetryConfig::default(),
+            retry_strategy: None,
+            proxy: None,
+            ca_bundle: None,
+            accept_invalid_certs: false,
+            user_agent_suffix: None,
+            http_client: None,
+        }
+    }
+
+    /// Send requests to a custom endpoint instead of the regional default
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.to_string());
+        self
+    }
+
+    /// Sign requests with explicit credentials
+    pub fn credentials(
+        mut self,
+        access_key_id: String,
+        secret_access_key: String,
+        session_token: Option<String>,
+    ) -> Self {
+        self.credentials = Some(Arc::new(Credentials::new(
+            access_key_id,
+            secret_access_key,
+            session_token,
+        )));
+        self
+    }
+
+    /// Sign requests with credentials from a provider such as
+    /// [`DefaultCredentialsChain`](crate::credentials::DefaultCredentialsChain).
+    /// Credentials are cached until shortly before they expire.
+    pub fn credentials_provider(mut self, provider: impl CredentialsProvider + 'static) -> Self {
+        self.credentials = Some(Arc::new(CachingProvider::new(Arc::new(provider))));
+        self
+    }
+
+    /// Maximum time to establish a connection
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum time to wait between reads of the response
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Maximum total time for a single HTTP attempt (default: 30s)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Number of retries after the initial attempt
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.retry.max_retries = max_retries;
+        self
+    }
+
+    /// Initial and maximum delay for exponential backoff between retries
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.retry.initial_backoff = initial;
+        self.retry.max_backoff = max;
+        self
+    }
+
+    /// Standard (jittered backoff) or adaptive (client-side rate limiting)
+    /// retries
+    pub fn retry_mode(mut self, mode: RetryMode) -> Self {
+        self.retry.mode = mode;
+        self
+    }
+
+    /// Stop retrying once this much time has passed since the first attempt
+    pub fn retry_deadline(mut self, deadline: Duration) -> Self {
+        self.retry.deadline = Some(deadline);
+        self
+    }
+
+    pub fn retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Replace the built-in retry strategy. The deadline from the retry
+    /// configuration still applies.
+    pub fn retry_strategy(mut self, strategy: impl RetryStrategy + 'static) -> Self {
+        self.retry_strategy = Some(Arc::new(strategy));
+        self
+    }
+
+    /// Route all requests through a proxy (e.g. `http://proxy.internal:3128`)
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.proxy = Some(url.to_string());
+        self
+    }
+
+    /// Trust the PEM-encoded root certificates in this file in addition to
+    /// the built-in roots
+    pub fn ca_bundle(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_bundle = Some(path.into());
+        self
+    }
+
+    /// Disable TLS certificate verification entirely.
+    ///
+    /// Only intended for debugging behind intercepting proxies; prefer
+    /// [`ca_bundle`](Self::ca_bundle) to trust a proxy's re-signing CA.
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Appended to the default `s3-vectors/<version>` user agent
+    pub fn user_agent_suffix(mut self, suffix: &str) -> Self {
+        self.user_agent_suffix = Some(suffix.to_string());
+        self
+    }
+
+    /// Use a preconfigured HTTP client. Takes precedence over all other HTTP
+    /// settings on this builder.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    pub fn build(self) -> Result<S3VectorsClient> {
+        let endpoint = resolve_endpoint(&self.region, self.endpoint.as_deref())?;
+
+        let http = match self.http_client.clone() {
+            Some(client) => client,
+            None if self.has_http_overrides() => self.build_http_client()?,
+            None => HTTP_CLIENT.clone(),
+        };
+
+        let signer = self
+            .credentials
+            .map(|provider| auth::AwsV4Signer::with_provider(provider, self.region.clone()));
+
+        Ok(S3VectorsClient {
+            endpoint,
+            region: self.region,
+            signer,
+            http,
+            retry_strategy: self.retry_strategy.unwrap_or_else(|| self.retry.strategy()),
+            non_filterable_keys: Default::default(),
+            retry: self.retry,
+        })
+    }
+
+    fn has_http_overrides(&self) -> bool {
+        self.connect_timeout.is_some()
+            || self.read_timeout.is_some()
+            || self.timeout.is_some()
+            || self.proxy.is_some()
+            || self.ca_bundle.is_some()
+            || self.accept_invalid_certs
+            || self.user_agent_suffix.is_some()
+    }
+
+    fn build_http_client(&self) -> Result<reqwest::Client> {
+        let mut user_agent =
+            concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")).to_string();
+        if let Some(suffix) = &self.user_agent_suffix {
+            user_agent.push(' ');
+            user_agent.push_str(suffix);
+        }
+
+        let mut builder = reqwest::Client::builder()
+            .user_agent(user_agent)
+            .timeout(self.timeout.unwrap_or(Duration::from_secs(30)));
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(url) = &self.proxy {
+            let proxy =
+                reqwest::Proxy::all(url).with_context(|| format!("Invalid proxy URL: {url}"))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(path) = &self.ca_bundle {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("Failed to read CA bundle: {}", path.display()))?;
+            let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("Invalid PEM CA bundle: {}", path.display()))?;
+            if certificates.is_empty() {
+                anyhow::bail!("CA bundle contains no certificates: {}", path.display());
+            }
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        if self.accept_invalid_certs {
+            tracing::warn!(
+                "TLS certificate verification is DISABLED; connections can be intercepted"
+            );
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        builder.build().context("Failed to build HTTP client")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_defaults() {
+        let client = S3VectorsClient::builder("us-west-2").build().unwrap();
+        assert_eq!(client.region(), "us-west-2");
+        assert_eq!(client.endpoint(), "https://s3vectors.us-west-2.api.aws");
+        assert_eq!(client.retry, RetryConfig::default());
+        assert!(client.signer.is_none());
+    }
+
+    #[test]
+    fn test_builder_validates_region_without_custom_endpoint() {
+        let client = S3VectorsClient::builder("cn-north-1").build().unwrap();
+        assert_eq!(
+            client.endpoint(),
+            "https://s3vectors.cn-north-1.api.amazonwebservices.com.cn"
+        );
+        assert!(S3VectorsClient::builder("xx-nowhere-1").build().is_err());
+        // Emulators and other custom endpoints accept any region
+        let client = S3VectorsClient::builder("xx-nowhere-1")
+            .endpoint("http://localhost:9000")
+            .build()
+            .unwrap();
+        assert_eq!(client.region(), "xx-nowhere-1");
+    }
+
+    #[test]
+    fn test_clients_with_different_settings_coexist() {
+        let fast = S3VectorsClient::builder("us-east-1")
+            .timeout(Duration::from_secs(2))
+            .max_retries(0)
+            .build()
+            .unwrap();
+        let patient = S3VectorsClient::builder("us-east-1")
+            .connect_timeout(Duration::from_secs(10))
+            .backoff(Duration::from_millis(500), Duration::from_secs(20))
+            .max_retries(8)
+            .user_agent_suffix("batch-loader/1.0")
+            .build()
+            .unwrap();
+
+        assert_eq!(fast.retry.max_retries, 0);
+        assert_eq!(patient.retry.max_retries, 8);
+        assert_eq!(patient.retry.initial_backoff, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_builder_rejects_invalid_settings() {
+        assert!(S3VectorsClient::builder("us-east-1")
+            .proxy("not a url")
+            .build()
+            .is_err());
+        assert!(S3VectorsClient::builder("us-east-1")
+            .ca_bundle("/nonexistent/ca.pem")
+            .build()
+            .is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty.pem");
+        std::fs::write(&empty, "").unwrap();
+        assert!(S3VectorsClient::builder("us-east-1")
+            .ca_bundle(&empty)
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_builder_accepts_ca_bundle_and_insecure_mode() {
+        // Self-signed test CA; only the PEM parsing path is exercised here
+        const TEST_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBkTCCATegAwIBAgIUdlTHNrvHsOnjsMbCIDkQkTKTQAowCgYIKoZIzj0EAwIw
+HTEbMBkGA1UEAwwSczMtdmVjdG9ycy10ZXN0LWNhMCAXDTI2MTAxNjIyMzQ1OVoY
+DzIxMjYwOTIyMjIzNDU5WjAdMRswGQYDVQQDDBJzMy12ZWN0b3JzLXRlc3QtY2Ew
+WTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAAQk/hEyptT9a6b3dDnUFq+wEEY+7Hb1
+Gp2SvjetKmL4hPFc1OHtgqvuXh1fkdEvI5qUPqWMpErE25ex48HA0vBNo1MwUTAd
+BgNVHQ4EFgQUsNQSC5aXvIDu+DRzXgcvJicxSqIwHwYDVR0jBBgwFoAUsNQSC5aX
+vIDu+DRzXgcvJicxSqIwDwYDVR0TAQH/BAUwAwEB/zAKBggqhkjOPQQDAgNIADBF
+AiEA3VaV9BJ5SxIa97K0C10HyC81+NvX8I9hZC+EmEiZ99UCIAe8fdD9bfYnypzi
+LnbGfT1RgewhFdjgGgFY52v3GwU+
+-----END CERTIFICATE-----
+";
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("ca.pem");
+        std::fs::write(&bundle, TEST_CA).unwrap();
+
+        assert!(S3VectorsClient::builder("us-east-1")
+            .ca_bundle(&bundle)
+            .build()
+            .is_ok());
+        assert!(S3VectorsClient::builder("us-east-1")
+            .danger_accept_invalid_certs(true)
+            .build()
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_builder_applies_timeout_per_client() {
+        // Accept connections but never answer, so only the client timeout ends the request
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                held.push(socket);
+            }
+        });
+
+        let client = S3VectorsClient::builder("us-east-1")
+            .endpoint(&format!("http://{addr}"))
+            .credentials("AKIDEXAMPLE".to_string(), "secret".to_string(), None)
+            .timeout(Duration::from_millis(200))
+            .max_retries(0)
+            .build()
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let err = client
+            .list_vector_buckets(None, None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::S3VectorsError::RequestError(ref e) if e.is_timeout()));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
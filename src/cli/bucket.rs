@@ -1,10 +1,14 @@
 use crate::cli::output::{print_output, print_table};
-use crate::cli::OutputFormat;
-use crate::types::BucketStatus;
-use crate::S3VectorsClient;
+use crate::cli::tag::{matches_all, parse_tags, TagFilter};
+use crate::cli::{OutputFormat, WaitArgs};
+use crate::types::{
+    BucketStatus, CreateVectorBucketRequest, EncryptionConfiguration, ListVectorBucketsResponse,
+};
+use crate::{PaginationConfig, S3VectorsClient};
 use anyhow::Result;
 use chrono::{DateTime, NaiveDate, Utc};
 use clap::{Args, Subcommand, ValueEnum};
+use futures_util::TryStreamExt;
 use serde::Serialize;
 use std::str::FromStr;
 use tabled::Tabled;
@@ -25,11 +29,25 @@ pub enum BucketSubcommands {
         #[arg(help = "Name of the vector bucket")]
         name: String,
 
-        #[arg(long, help = "KMS key ID for encryption")]
+        #[arg(
+            long,
+            value_enum,
+            help = "Server-side encryption [default: sse-kms with --kms-key-id, else the service default SSE-S3]"
+        )]
+        sse: Option<SseArg>,
+
+        #[arg(
+            long,
+            visible_alias = "kms-key-arn",
+            help = "KMS key ARN for SSE-KMS encryption"
+        )]
         kms_key_id: Option<String>,
 
         #[arg(long, help = "Tags in key=value format", value_delimiter = ',')]
         tags: Option<Vec<String>>,
+
+        #[command(flatten)]
+        wait: WaitArgs,
     },
 
     #[command(about = "List vector buckets")]
@@ -39,6 +57,9 @@ pub enum BucketSubcommands {
 
         #[arg(long, help = "Prefix to filter bucket names")]
         prefix: Option<String>,
+
+        #[arg(long, help = "Fetch all pages (--max-results sets the page size)")]
+        all: bool,
     },
 
     #[command(about = "Query vector buckets with advanced filtering")]
@@ -73,6 +94,13 @@ pub enum BucketSubcommands {
         #[arg(long, help = "Filter only encrypted buckets")]
         encrypted: bool,
 
+        #[arg(
+            long = "tag",
+            help = "Filter by tag (key=value, or key to match any value); repeatable",
+            value_delimiter = ','
+        )]
+        tags: Vec<TagFilter>,
+
         #[arg(
             long,
             help = "Sort results by field",
@@ -101,9 +129,20 @@ pub enum BucketSubcommands {
 
         #[arg(long, help = "Skip confirmation prompt")]
         force: bool,
+
+        #[command(flatten)]
+        wait: WaitArgs,
     },
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum SseArg {
+    /// S3 managed keys (AES256)
+    SseS3,
+    /// AWS KMS keys (aws:kms), requires --kms-key-id
+    SseKms,
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum BucketSortField {
     Name,
@@ -116,6 +155,33 @@ pub enum SortOrder {
     Desc,
 }
 
+/// Encryption settings for `--sse` and `--kms-key-id`; `None` leaves the
+/// choice to the service
+fn encryption_configuration(
+    sse: Option<SseArg>,
+    kms_key_id: Option<&str>,
+) -> Option<EncryptionConfiguration> {
+    let sse_type = match (sse, kms_key_id) {
+        (None, None) => return None,
+        (Some(SseArg::SseS3), _) => EncryptionConfiguration::SSE_S3,
+        (Some(SseArg::SseKms), _) | (None, Some(_)) => EncryptionConfiguration::SSE_KMS,
+    };
+    // Invalid combinations are reported by the client's validation
+    Some(EncryptionConfiguration {
+        kms_key_arn: kms_key_id.map(str::to_string),
+        sse_type: Some(sse_type.to_string()),
+    })
+}
+
+fn describe_encryption(encryption: &EncryptionConfiguration) -> String {
+    match encryption.sse_type.as_deref() {
+        Some(EncryptionConfiguration::SSE_S3) => "SSE-S3 (AES256)".to_string(),
+        Some(EncryptionConfiguration::SSE_KMS) => "SSE-KMS (aws:kms)".to_string(),
+        Some(other) => other.to_string(),
+        None => "service default".to_string(),
+    }
+}
+
 #[derive(Serialize, Tabled)]
 struct BucketInfo {
     name: String,
@@ -133,6 +199,7 @@ struct BucketQueryParams<'a> {
     created_after: Option<&'a str>,
     created_before: Option<&'a str>,
     encrypted_only: bool,
+    tags: &'a [TagFilter],
     sort_by: BucketSortField,
     sort_order: SortOrder,
     limit: Option<usize>,
@@ -147,23 +214,25 @@ impl BucketCommand {
         match &self.command {
             BucketSubcommands::Create {
                 name,
+                sse,
                 kms_key_id,
                 tags,
+                wait,
             } => {
-                self.create_bucket(
-                    client,
-                    name,
-                    kms_key_id.as_deref(),
-                    tags.as_ref(),
-                    output_format,
-                )
-                .await
+                let request = CreateVectorBucketRequest {
+                    vector_bucket_name: name.clone(),
+                    encryption_configuration: encryption_configuration(*sse, kms_key_id.as_deref()),
+                    tags: parse_tags(tags.as_deref().unwrap_or_default())?,
+                };
+                self.create_bucket(client, request, wait, output_format)
+                    .await
             }
             BucketSubcommands::List {
                 max_results,
                 prefix,
+                all,
             } => {
-                self.list_buckets(client, *max_results, prefix.as_deref(), output_format)
+                self.list_buckets(client, *max_results, prefix.as_deref(), *all, output_format)
                     .await
             }
             BucketSubcommands::Query {
@@ -175,6 +244,7 @@ impl BucketCommand {
                 created_after,
                 created_before,
                 encrypted,
+                tags,
                 sort_by,
                 sort_order,
                 limit,
@@ -188,6 +258,7 @@ impl BucketCommand {
                     created_after: created_after.as_deref(),
                     created_before: created_before.as_deref(),
                     encrypted_only: *encrypted,
+                    tags,
                     sort_by: *sort_by,
                     sort_order: *sort_order,
                     limit: *limit,
@@ -195,8 +266,8 @@ impl BucketCommand {
                 self.query_buckets(client, params, output_format).await
             }
             BucketSubcommands::Get { name } => self.get_bucket(client, name, output_format).await,
-            BucketSubcommands::Delete { name, force } => {
-                self.delete_bucket(client, name, *force, output_format)
+            BucketSubcommands::Delete { name, force, wait } => {
+                self.delete_bucket(client, name, *force, wait, output_format)
                     .await
             }
         }
@@ -205,12 +276,16 @@ impl BucketCommand {
     async fn create_bucket(
         &self,
         client: &S3VectorsClient,
-        name: &str,
-        _kms_key_id: Option<&str>,
-        _tags: Option<&Vec<String>>,
+        request: CreateVectorBucketRequest,
+        wait: &WaitArgs,
         output_format: OutputFormat,
     ) -> Result<()> {
-        let bucket = client.create_vector_bucket(name).await?;
+        let name = request.vector_bucket_name.clone();
+        let tags = request.tags.clone();
+        let mut bucket = client.create_vector_bucket_with(request).await?;
+        if let Some(waiter) = wait.waiter() {
+            bucket = waiter.bucket_active(client, &name).await?;
+        }
 
         match output_format {
             OutputFormat::Table => {
@@ -218,6 +293,13 @@ impl BucketCommand {
                 println!("Name: {}", bucket.vector_bucket_name);
                 println!("Status: {:?}", bucket.status);
                 println!("ARN: {}", bucket.vector_bucket_arn);
+                if let Some(encryption) = &bucket.encryption_configuration {
+                    println!("Encryption: {}", describe_encryption(encryption));
+                }
+                if !tags.is_empty() {
+                    let tags: Vec<String> = tags.iter().map(|(k, v)| format!("{k}={v}")).collect();
+                    println!("Tags: {}", tags.join(", "));
+                }
             }
             _ => print_output(&bucket, output_format)?,
         }
@@ -230,11 +312,26 @@ impl BucketCommand {
         client: &S3VectorsClient,
         max_results: u32,
         prefix: Option<&str>,
+        all: bool,
         output_format: OutputFormat,
     ) -> Result<()> {
-        let response = client
-            .list_vector_buckets(Some(max_results), None, prefix.map(|s| s.to_string()))
-            .await?;
+        let prefix = prefix.map(|s| s.to_string());
+        let response = if all {
+            ListVectorBucketsResponse {
+                buckets: client
+                    .list_vector_buckets_paginated(
+                        prefix,
+                        PaginationConfig::default().page_size(max_results),
+                    )
+                    .try_collect()
+                    .await?,
+                next_token: None,
+            }
+        } else {
+            client
+                .list_vector_buckets(Some(max_results), None, prefix)
+                .await?
+        };
 
         match output_format {
             OutputFormat::Table => {
@@ -279,8 +376,9 @@ impl BucketCommand {
                     println!("  Created: {}", created.format("%Y-%m-%d %H:%M:%S"));
                 }
                 if let Some(encryption) = &bucket.encryption_configuration {
-                    if let Some(sse_type) = &encryption.sse_type {
-                        println!("  Encryption: {sse_type}");
+                    println!("  Encryption: {}", describe_encryption(encryption));
+                    if let Some(kms_key_arn) = &encryption.kms_key_arn {
+                        println!("  KMS key: {kms_key_arn}");
                     }
                 }
             }
@@ -295,6 +393,7 @@ impl BucketCommand {
         client: &S3VectorsClient,
         name: &str,
         force: bool,
+        wait: &WaitArgs,
         output_format: OutputFormat,
     ) -> Result<()> {
         if !force {
@@ -311,6 +410,9 @@ impl BucketCommand {
         }
 
         client.delete_vector_bucket(name).await?;
+        if let Some(waiter) = wait.waiter() {
+            waiter.bucket_deleted(client, name).await?;
+        }
 
         match output_format {
             OutputFormat::Table => {
@@ -355,7 +457,6 @@ impl BucketCommand {
 
         // Fetch all buckets with pagination
         let mut all_buckets = Vec::new();
-        let mut next_token = None;
         let mut page_count = 0;
 
         // Show progress if fetching many buckets
@@ -365,28 +466,22 @@ impl BucketCommand {
             let _ = io::stdout().flush(); // Best effort flush, ignore errors
         }
 
-        loop {
-            let response = client
-                .list_vector_buckets(
-                    Some(MAX_LIST_RESULTS),
-                    next_token.clone(),
-                    api_prefix.map(|s| s.to_string()),
-                )
-                .await?;
-
-            all_buckets.extend(response.buckets);
-            page_count += 1;
-
-            // Show progress for large lists
-            if page_count > 1 && output_format == OutputFormat::Table {
-                print!(".");
-                use std::io::{self, Write};
-                let _ = io::stdout().flush(); // Best effort flush, ignore errors
-            }
-
-            match response.next_token {
-                Some(token) => next_token = Some(token),
-                None => break,
+        let buckets = client.list_vector_buckets_paginated(
+            api_prefix.map(|s| s.to_string()),
+            PaginationConfig::default().page_size(MAX_LIST_RESULTS),
+        );
+        futures_util::pin_mut!(buckets);
+        while let Some(bucket) = buckets.try_next().await? {
+            all_buckets.push(bucket);
+
+            // Show progress for large lists, once per full page
+            if all_buckets.len() % MAX_LIST_RESULTS as usize == 0 {
+                page_count += 1;
+                if output_format == OutputFormat::Table {
+                    print!(".");
+                    use std::io::{self, Write};
+                    let _ = io::stdout().flush(); // Best effort flush, ignore errors
+                }
             }
         }
 
@@ -455,6 +550,20 @@ impl BucketCommand {
             filtered_buckets.retain(|b| b.encryption_configuration.is_some());
         }
 
+        // Tag filtering needs one lookup per remaining bucket, so it runs last
+        if !params.tags.is_empty() {
+            let mut tagged = Vec::with_capacity(filtered_buckets.len());
+            for bucket in filtered_buckets {
+                let tags = client
+                    .list_tags_for_resource(&bucket.vector_bucket_arn)
+                    .await?;
+                if matches_all(params.tags, &tags) {
+                    tagged.push(bucket);
+                }
+            }
+            filtered_buckets = tagged;
+        }
+
         // Sort results
         match params.sort_by {
             BucketSortField::Name => {
@@ -624,12 +733,16 @@ mod tests {
         match cli.command {
             BucketSubcommands::Create {
                 name,
+                sse,
                 kms_key_id,
                 tags,
+                wait,
             } => {
                 assert_eq!(name, "my-bucket");
+                assert!(sse.is_none());
                 assert!(kms_key_id.is_none());
                 assert!(tags.is_none());
+                assert!(wait.waiter().is_none());
             }
             _ => panic!("Expected Create command"),
         }
@@ -653,6 +766,7 @@ mod tests {
                 name,
                 kms_key_id,
                 tags,
+                ..
             } => {
                 assert_eq!(name, "my-bucket");
                 assert_eq!(kms_key_id, Some("key123".to_string()));
@@ -674,9 +788,22 @@ mod tests {
             BucketSubcommands::List {
                 max_results,
                 prefix,
+                all,
             } => {
                 assert_eq!(max_results, 100); // default value
                 assert!(prefix.is_none());
+                assert!(!all);
+            }
+            _ => panic!("Expected List command"),
+        }
+
+        let cli = TestCli::parse_from(["test", "list", "--all", "--max-results", "500"]);
+        match cli.command {
+            BucketSubcommands::List {
+                max_results, all, ..
+            } => {
+                assert_eq!(max_results, 500);
+                assert!(all);
             }
             _ => panic!("Expected List command"),
         }
@@ -695,13 +822,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_create_encryption() {
+        let key = "arn:aws:kms:us-east-1:111122223333:key/abcd";
+        assert_eq!(encryption_configuration(None, None), None);
+        assert_eq!(
+            encryption_configuration(None, Some(key)),
+            Some(EncryptionConfiguration::sse_kms(key))
+        );
+        assert_eq!(
+            encryption_configuration(Some(SseArg::SseS3), None),
+            Some(EncryptionConfiguration::sse_s3())
+        );
+    }
+
     #[test]
     fn test_parse_delete_command() {
         let args = vec!["test", "delete", "my-bucket", "--force"];
         let cli = TestCli::parse_from(args);
 
         match cli.command {
-            BucketSubcommands::Delete { name, force } => {
+            BucketSubcommands::Delete { name, force, .. } => {
                 assert_eq!(name, "my-bucket");
                 assert!(force);
             }
@@ -709,6 +850,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_delete_with_wait() {
+        let args = vec![
+            "test",
+            "delete",
+            "my-bucket",
+            "--force",
+            "--wait",
+            "--wait-timeout",
+            "30",
+        ];
+        let cli = TestCli::parse_from(args);
+
+        match cli.command {
+            BucketSubcommands::Delete { wait, .. } => {
+                let waiter = wait.waiter().unwrap();
+                assert_eq!(waiter.deadline, std::time::Duration::from_secs(30));
+            }
+            _ => panic!("Expected Delete command"),
+        }
+
+        // --wait-timeout alone is rejected
+        let args = vec!["test", "delete", "my-bucket", "--wait-timeout", "30"];
+        assert!(TestCli::try_parse_from(args).is_err());
+    }
+
     #[test]
     fn test_parse_query_simple() {
         let args = vec!["test", "query", "prod"];
@@ -749,6 +916,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_query_with_tag_filters() {
+        let args = vec![
+            "test",
+            "query",
+            "--tag",
+            "env=prod,team",
+            "--tag",
+            "tier=gold",
+        ];
+        let cli = TestCli::parse_from(args);
+
+        match cli.command {
+            BucketSubcommands::Query { tags, .. } => {
+                let keys: Vec<_> = tags.iter().map(|t| t.key.as_str()).collect();
+                assert_eq!(keys, ["env", "team", "tier"]);
+                assert_eq!(tags[0].value.as_deref(), Some("prod"));
+                assert_eq!(tags[1].value, None);
+            }
+            _ => panic!("Expected Query command"),
+        }
+    }
+
     #[test]
     fn test_parse_query_with_date_filter() {
         let args = vec![
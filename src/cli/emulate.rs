@@ -0,0 +1,61 @@
+use crate::emulator::Emulator;
+use anyhow::Result;
+use clap::Args;
+use colored::*;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct EmulateCommand {
+    #[arg(long, help = "Port to listen on", default_value_t = 9090)]
+    pub port: u16,
+
+    #[arg(long, help = "Address to bind", default_value = "127.0.0.1")]
+    pub host: IpAddr,
+
+    #[arg(
+        long,
+        help = "Keep state in this JSON file across restarts (in memory if omitted)"
+    )]
+    pub state_file: Option<PathBuf>,
+}
+
+impl EmulateCommand {
+    pub async fn execute(&self) -> Result<()> {
+        let emulator = match &self.state_file {
+            Some(path) => Emulator::with_state_file(path)?,
+            None => Emulator::new(),
+        };
+        let addr = SocketAddr::new(self.host, self.port);
+
+        println!(
+            "{} S3 Vectors emulator on {}",
+            "Starting".green().bold(),
+            format!("http://{addr}").cyan()
+        );
+        println!(
+            "Point the CLI at it with --endpoint-url http://{addr} \
+             (any credentials are accepted)"
+        );
+        emulator.serve(addr).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{Cli, Commands};
+    use clap::Parser;
+
+    #[test]
+    fn test_parse_emulate_command() {
+        let cli = Cli::try_parse_from(["s3-vectors", "emulate", "--port", "4566"]).unwrap();
+        match cli.command {
+            Some(Commands::Emulate(cmd)) => {
+                assert_eq!(cmd.port, 4566);
+                assert_eq!(cmd.host.to_string(), "127.0.0.1");
+                assert!(cmd.state_file.is_none());
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+}
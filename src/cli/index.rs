@@ -1,8 +1,14 @@
 use crate::cli::output::{print_output, print_table};
-use crate::cli::OutputFormat;
-use crate::{CreateIndexRequest, DistanceMetric, ListIndexesResponse, S3VectorsClient};
+use crate::cli::resource::{BucketTarget, IndexTarget};
+use crate::cli::tag::{matches_all, TagFilter};
+use crate::cli::{OutputFormat, WaitArgs};
+use crate::{
+    CreateIndexRequest, DistanceMetric, ListIndexesResponse, MetadataConfiguration,
+    PaginationConfig, S3VectorsClient,
+};
 use anyhow::Result;
 use clap::{Args, Subcommand};
+use futures_util::TryStreamExt;
 use serde::Serialize;
 use tabled::Tabled;
 
@@ -16,11 +22,11 @@ pub struct IndexCommand {
 pub enum IndexSubcommands {
     #[command(about = "Create a new vector index")]
     Create {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN, or an index ARN")]
         bucket: String,
 
-        #[arg(help = "Name of the index")]
-        name: String,
+        #[arg(help = "Name of the index (omit with an index ARN)")]
+        name: Option<String>,
 
         #[arg(short, long, help = "Number of dimensions (1-4096)")]
         dimensions: u32,
@@ -36,11 +42,22 @@ pub enum IndexSubcommands {
 
         #[arg(long, help = "Metadata fields configuration as JSON")]
         metadata_config: Option<String>,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            conflicts_with = "metadata_config",
+            help = "Comma-separated metadata keys to store but exclude from query filters (max 10)"
+        )]
+        non_filterable_keys: Vec<String>,
+
+        #[command(flatten)]
+        wait: WaitArgs,
     },
 
     #[command(about = "List indexes in a bucket")]
     List {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN")]
         bucket: String,
 
         #[arg(short, long, help = "Maximum number of results", default_value = "100")]
@@ -49,29 +66,42 @@ pub enum IndexSubcommands {
         #[arg(long, help = "Prefix to filter index names")]
         prefix: Option<String>,
 
+        #[arg(long, help = "Fetch all pages (--max-results sets the page size)")]
+        all: bool,
+
         #[arg(short = 'q', long, help = "Natural language query to search indexes")]
         query: Option<String>,
+
+        #[arg(
+            long = "tag",
+            help = "Filter by tag (key=value, or key to match any value); repeatable",
+            value_delimiter = ','
+        )]
+        tags: Vec<TagFilter>,
     },
 
     #[command(about = "Get index details")]
     Get {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN, or an index ARN")]
         bucket: String,
 
-        #[arg(help = "Name of the index")]
-        name: String,
+        #[arg(help = "Name of the index (omit with an index ARN)")]
+        name: Option<String>,
     },
 
     #[command(about = "Delete an index")]
     Delete {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN, or an index ARN")]
         bucket: String,
 
-        #[arg(help = "Name of the index")]
-        name: String,
+        #[arg(help = "Name of the index (omit with an index ARN)")]
+        name: Option<String>,
 
         #[arg(long, help = "Skip confirmation prompt")]
         force: bool,
+
+        #[command(flatten)]
+        wait: WaitArgs,
     },
 }
 
@@ -90,6 +120,12 @@ impl From<DistanceMetricArg> for DistanceMetric {
     }
 }
 
+fn non_filterable_keys(config: Option<&MetadataConfiguration>) -> &[String] {
+    config
+        .and_then(|config| config.non_filterable_metadata_keys.as_deref())
+        .unwrap_or_default()
+}
+
 #[derive(Serialize, Tabled)]
 struct IndexInfo {
     name: String,
@@ -112,14 +148,25 @@ impl IndexCommand {
                 dimensions,
                 metric,
                 metadata_config,
+                non_filterable_keys,
+                wait,
             } => {
+                let metadata_configuration = match metadata_config {
+                    Some(config) => Some(serde_json::from_str(config)?),
+                    None if non_filterable_keys.is_empty() => None,
+                    None => Some(MetadataConfiguration {
+                        non_filterable_metadata_keys: Some(non_filterable_keys.clone()),
+                    }),
+                };
+                let target = IndexTarget::parse(bucket, name.as_deref())?;
                 self.create_index(
-                    client,
-                    bucket,
-                    name,
+                    &target.client(client)?,
+                    &target.bucket,
+                    &target.index,
                     *dimensions,
                     *metric,
-                    metadata_config.as_deref(),
+                    metadata_configuration,
+                    wait,
                     output_format,
                 )
                 .await
@@ -128,28 +175,49 @@ impl IndexCommand {
                 bucket,
                 max_results,
                 prefix,
+                all,
                 query,
+                tags,
             } => {
+                let target = BucketTarget::parse(bucket)?;
                 self.list_indexes(
-                    client,
-                    bucket,
+                    &target.client(client)?,
+                    &target.bucket,
                     *max_results,
                     prefix.as_deref(),
+                    *all,
                     query.as_deref(),
+                    tags,
                     output_format,
                 )
                 .await
             }
             IndexSubcommands::Get { bucket, name } => {
-                self.get_index(client, bucket, name, output_format).await
+                let target = IndexTarget::parse(bucket, name.as_deref())?;
+                self.get_index(
+                    &target.client(client)?,
+                    &target.bucket,
+                    &target.index,
+                    output_format,
+                )
+                .await
             }
             IndexSubcommands::Delete {
                 bucket,
                 name,
                 force,
+                wait,
             } => {
-                self.delete_index(client, bucket, name, *force, output_format)
-                    .await
+                let target = IndexTarget::parse(bucket, name.as_deref())?;
+                self.delete_index(
+                    &target.client(client)?,
+                    &target.bucket,
+                    &target.index,
+                    *force,
+                    wait,
+                    output_format,
+                )
+                .await
             }
         }
     }
@@ -162,23 +230,24 @@ impl IndexCommand {
         name: &str,
         dimensions: u32,
         metric: DistanceMetricArg,
-        metadata_config: Option<&str>,
+        metadata_configuration: Option<MetadataConfiguration>,
+        wait: &WaitArgs,
         output_format: OutputFormat,
     ) -> Result<()> {
-        let mut request = CreateIndexRequest {
+        let non_filterable_keys = non_filterable_keys(metadata_configuration.as_ref()).to_vec();
+        let request = CreateIndexRequest {
             vector_bucket_name: bucket.to_string(),
             index_name: name.to_string(),
             data_type: crate::DataType::Float32,
             dimension: dimensions,
             distance_metric: metric.into(),
-            metadata_configuration: None,
+            metadata_configuration,
         };
 
-        if let Some(config) = metadata_config {
-            request.metadata_configuration = Some(serde_json::from_str(config)?);
-        }
-
         client.create_index(request).await?;
+        if let Some(waiter) = wait.waiter() {
+            waiter.index_active(client, bucket, name).await?;
+        }
 
         match output_format {
             OutputFormat::Table => {
@@ -187,6 +256,9 @@ impl IndexCommand {
                 println!("Index: {name}");
                 println!("Dimensions: {dimensions}");
                 println!("Metric: {metric:?}");
+                if !non_filterable_keys.is_empty() {
+                    println!("Non-filterable keys: {}", non_filterable_keys.join(", "));
+                }
             }
             _ => {
                 let result = serde_json::json!({
@@ -194,7 +266,8 @@ impl IndexCommand {
                     "bucket": bucket,
                     "index": name,
                     "dimensions": dimensions,
-                    "metric": format!("{:?}", metric)
+                    "metric": format!("{:?}", metric),
+                    "nonFilterableMetadataKeys": non_filterable_keys,
                 });
                 print_output(&result, output_format)?;
             }
@@ -203,16 +276,32 @@ impl IndexCommand {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn list_indexes(
         &self,
         client: &S3VectorsClient,
         bucket: &str,
         max_results: u32,
         prefix: Option<&str>,
+        all: bool,
         query: Option<&str>,
+        tags: &[TagFilter],
         output_format: OutputFormat,
     ) -> Result<()> {
-        let response = client.list_indexes(bucket, Some(max_results), None).await?;
+        let response = if all {
+            ListIndexesResponse {
+                indexes: client
+                    .list_indexes_paginated(
+                        bucket,
+                        PaginationConfig::default().page_size(max_results),
+                    )
+                    .try_collect()
+                    .await?,
+                next_token: None,
+            }
+        } else {
+            client.list_indexes(bucket, Some(max_results), None).await?
+        };
 
         // Apply filters
         let mut filtered_indexes = response.indexes;
@@ -222,6 +311,18 @@ impl IndexCommand {
             filtered_indexes.retain(|idx| idx.index_name.starts_with(p));
         }
 
+        // Apply tag filters, one lookup per remaining index
+        if !tags.is_empty() {
+            let mut tagged = Vec::with_capacity(filtered_indexes.len());
+            for idx in filtered_indexes {
+                let index_tags = client.list_tags_for_resource(&idx.index_arn).await?;
+                if matches_all(tags, &index_tags) {
+                    tagged.push(idx);
+                }
+            }
+            filtered_indexes = tagged;
+        }
+
         // Apply natural language query if provided
         if let Some(q) = query {
             println!("Searching indexes for: \"{q}\"");
@@ -290,15 +391,30 @@ impl IndexCommand {
 
         match output_format {
             OutputFormat::Table => {
+                let index = &response.index;
                 println!("Index Details:");
-                println!("  Name: {}", response.index.index_name);
-                println!("  Bucket: {}", response.index.vector_bucket_name);
-                println!("  Status: {:?}", response.index.status);
-                println!("  Dimensions: {}", response.index.dimension);
-                if let Some(count) = response.index.vector_count {
+                println!("  Name: {}", index.index_name);
+                println!("  Bucket: {bucket}");
+                println!("  ARN: {}", index.index_arn);
+                if let Some(status) = &index.status {
+                    println!("  Status: {status:?}");
+                }
+                println!("  Dimensions: {}", index.dimension);
+                println!("  Distance metric: {:?}", index.distance_metric);
+                if let Some(count) = index.vector_count {
                     println!("  Vectors: {count}");
                 }
-                println!("  Created: {}", response.index.created_at);
+                let keys = non_filterable_keys(index.metadata_configuration.as_ref());
+                if keys.is_empty() {
+                    println!("  Non-filterable keys: (none)");
+                } else {
+                    println!("  Non-filterable keys: {}", keys.join(", "));
+                }
+                if let Some(created) =
+                    chrono::DateTime::from_timestamp(index.creation_time as i64, 0)
+                {
+                    println!("  Created: {}", created.format("%Y-%m-%d %H:%M:%S"));
+                }
             }
             _ => print_output(&response.index, output_format)?,
         }
@@ -312,6 +428,7 @@ impl IndexCommand {
         bucket: &str,
         name: &str,
         force: bool,
+        wait: &WaitArgs,
         output_format: OutputFormat,
     ) -> Result<()> {
         if !force {
@@ -330,6 +447,9 @@ impl IndexCommand {
         }
 
         client.delete_index(bucket, name).await?;
+        if let Some(waiter) = wait.waiter() {
+            waiter.index_deleted(client, bucket, name).await?;
+        }
 
         match output_format {
             OutputFormat::Table => {
@@ -380,7 +500,7 @@ mod tests {
                 ..
             } => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(name, "my-index");
+                assert_eq!(name.as_deref(), Some("my-index"));
                 assert_eq!(dimensions, 384);
                 assert_eq!(metric, DistanceMetricArg::Cosine);
             }
@@ -388,6 +508,48 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_create_index_with_non_filterable_keys() {
+        let args = vec![
+            "test",
+            "create",
+            "my-bucket",
+            "my-index",
+            "-d",
+            "384",
+            "--non-filterable-keys",
+            "content,source_path",
+            "--wait",
+        ];
+        let cli = TestCli::parse_from(args);
+
+        match cli.command {
+            IndexSubcommands::Create {
+                non_filterable_keys,
+                wait,
+                ..
+            } => {
+                assert_eq!(non_filterable_keys, vec!["content", "source_path"]);
+                assert_eq!(wait.waiter(), Some(crate::Waiter::default()));
+            }
+            _ => panic!("Expected Create command"),
+        }
+
+        let conflicting = TestCli::try_parse_from(vec![
+            "test",
+            "create",
+            "my-bucket",
+            "my-index",
+            "-d",
+            "384",
+            "--non-filterable-keys",
+            "content",
+            "--metadata-config",
+            "{}",
+        ]);
+        assert!(conflicting.is_err());
+    }
+
     #[test]
     fn test_parse_list_indexes_command() {
         let args = vec!["test", "list", "my-bucket"];
@@ -398,11 +560,15 @@ mod tests {
                 bucket,
                 max_results,
                 prefix,
+                all,
                 query: _,
+                tags,
             } => {
                 assert_eq!(bucket, "my-bucket");
                 assert_eq!(max_results, 100); // default
                 assert!(prefix.is_none());
+                assert!(!all);
+                assert!(tags.is_empty());
             }
             _ => panic!("Expected List command"),
         }
@@ -416,7 +582,7 @@ mod tests {
         match cli.command {
             IndexSubcommands::Get { bucket, name } => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(name, "my-index");
+                assert_eq!(name.as_deref(), Some("my-index"));
             }
             _ => panic!("Expected Get command"),
         }
@@ -432,10 +598,12 @@ mod tests {
                 bucket,
                 name,
                 force,
+                wait,
             } => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(name, "my-index");
+                assert_eq!(name.as_deref(), Some("my-index"));
                 assert!(force);
+                assert!(!wait.wait);
             }
             _ => panic!("Expected Delete command"),
         }
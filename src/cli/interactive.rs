@@ -232,7 +232,25 @@ impl InteractiveMode {
         println!(
             "{} {:<72} {}",
             "║".blue(),
-            "       put, get, delete",
+            "       put, get, delete, generate, simulate, diff, history, rollback",
+            "║".blue()
+        );
+        println!(
+            "{}",
+            "║                                                                              ║"
+                .blue()
+        );
+        println!(
+            "{} {} {:<60} {}",
+            "║".blue(),
+            "tag   ".cyan().bold(),
+            "- Manage bucket and index tags",
+            "║".blue()
+        );
+        println!(
+            "{} {:<72} {}",
+            "║".blue(),
+            "       add, remove, list <arn>",
             "║".blue()
         );
         println!(
@@ -407,7 +425,14 @@ impl InteractiveMode {
                     Commands::Index(cmd) => cmd.execute(&self.client, self.output_format).await?,
                     Commands::Vector(cmd) => cmd.execute(&self.client, self.output_format).await?,
                     Commands::Policy(cmd) => cmd.execute(&self.client, self.output_format).await?,
+                    Commands::Tag(cmd) => cmd.execute(&self.client, self.output_format).await?,
                     Commands::Rag(cmd) => cmd.execute(&self.client, self.output_format).await?,
+                    Commands::Emulate(_) => {
+                        println!(
+                            "{} The emulator runs in the foreground; start it with `s3-vectors emulate`.",
+                            "Note:".yellow()
+                        );
+                    }
                 }
             }
             Err(e) => {
@@ -531,7 +556,7 @@ mod tests {
                     },
             }) => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(name, "my-index");
+                assert_eq!(name.as_deref(), Some("my-index"));
                 assert_eq!(dimensions, 384);
             }
             _ => panic!("Expected index create command"),
@@ -593,7 +618,7 @@ mod tests {
                     },
             }) => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(index, "my-index");
+                assert_eq!(index.as_deref(), Some("my-index"));
                 assert_eq!(vector, "0.1,0.2,0.3");
                 assert_eq!(top_k, 10);
             }
@@ -601,6 +626,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_vector_query_with_where() {
+        let interactive = InteractiveMode::new(
+            S3VectorsClient::from_env().unwrap(),
+            OutputFormat::Table,
+            false,
+        );
+        // Single quotes survive inside a double-quoted REPL argument
+        let args = interactive
+            .parse_command_args(
+                "s3-vectors vector query b i -q 0.1,0.2 --where \"genre = 'scifi' AND year >= 2020\"",
+            )
+            .unwrap();
+
+        #[derive(Parser)]
+        struct TempCli {
+            #[command(subcommand)]
+            command: Commands,
+        }
+
+        match TempCli::try_parse_from(&args).unwrap().command {
+            Commands::Vector(VectorCommand {
+                command: VectorSubcommands::Query { where_clause, .. },
+            }) => {
+                let filter = crate::cli::where_clause::parse_where(&where_clause.unwrap()).unwrap();
+                assert_eq!(
+                    filter.to_json(),
+                    serde_json::json!({"$and": [
+                        {"genre": {"$eq": "scifi"}},
+                        {"year": {"$gte": 2020}},
+                    ]})
+                );
+            }
+            _ => panic!("Expected vector query command"),
+        }
+    }
+
     #[test]
     fn test_invalid_command_handling() {
         // Test that invalid commands are handled gracefully
@@ -638,6 +700,7 @@ mod tests {
                         policy,
                         file,
                     },
+                ..
             }) => {
                 assert_eq!(bucket, "my-bucket");
                 assert!(policy.is_none());
@@ -673,8 +736,8 @@ mod tests {
                     },
             }) => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(index, "my-index");
-                assert_eq!(key, "key1");
+                assert_eq!(index.as_deref(), Some("my-index"));
+                assert_eq!(key.as_deref(), Some("key1"));
                 assert_eq!(data, "0.1,0.2,0.3");
                 assert_eq!(metadata, Some(r#"{"category":"test"}"#.to_string()));
             }
@@ -697,7 +760,7 @@ mod tests {
         let parsed = TempCli::try_parse_from(&args).unwrap();
         match parsed.command {
             Commands::Bucket(BucketCommand {
-                command: BucketSubcommands::Delete { name, force },
+                command: BucketSubcommands::Delete { name, force, .. },
             }) => {
                 assert_eq!(name, "my-bucket");
                 assert!(force);
@@ -1,4 +1,5 @@
 pub mod bucket;
+pub mod emulate;
 pub mod index;
 pub mod init;
 pub mod install_models;
@@ -6,10 +7,21 @@ pub mod interactive;
 pub mod output;
 pub mod policy;
 pub mod rag;
+pub mod resource;
+pub mod tag;
 pub mod vector;
+pub mod where_clause;
 
+use crate::credentials::{
+    profile_region, AssumeRoleProvider, CredentialsProvider, DefaultCredentialsChain,
+    MfaTokenProvider, ProfileProvider,
+};
+use crate::{get_config, S3VectorsClient, Waiter};
 use clap::{Parser, Subcommand};
 use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -27,11 +39,10 @@ pub struct Cli {
         short,
         long,
         global = true,
-        help = "AWS region",
-        env = "AWS_REGION",
-        default_value = "us-east-1"
+        help = "AWS region [default: profile region or us-east-1]",
+        env = "AWS_REGION"
     )]
-    pub region: String,
+    pub region: Option<String>,
 
     #[arg(
         short,
@@ -42,6 +53,37 @@ pub struct Cli {
     )]
     pub profile: Option<String>,
 
+    #[arg(
+        long,
+        global = true,
+        help = "IAM role to assume with the resolved credentials (e.g. a shared ML account)"
+    )]
+    pub role_arn: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        requires = "role_arn",
+        help = "External ID required by the role's trust policy"
+    )]
+    pub external_id: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        requires = "role_arn",
+        help = "MFA device serial number or ARN; prompts for the code"
+    )]
+    pub mfa_serial: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        help = "Override the S3 Vectors endpoint URL (e.g. a local emulator or VPC endpoint)",
+        env = "AWS_ENDPOINT_URL_S3VECTORS"
+    )]
+    pub endpoint_url: Option<String>,
+
     #[arg(
         short,
         long,
@@ -52,6 +94,14 @@ pub struct Cli {
     )]
     pub output: OutputFormat,
 
+    #[arg(
+        long,
+        global = true,
+        help = "PEM file with additional trusted CA certificates",
+        env = "AWS_CA_BUNDLE"
+    )]
+    pub ca_bundle: Option<PathBuf>,
+
     #[arg(long, global = true, help = "Disable SSL certificate verification")]
     pub no_verify_ssl: bool,
 
@@ -59,6 +109,84 @@ pub struct Cli {
     pub verbose: bool,
 }
 
+impl Cli {
+    /// Region from `--region` / `AWS_REGION`, then the profile's `region`
+    /// setting, then `us-east-1`
+    pub fn region(&self) -> String {
+        self.region
+            .clone()
+            .or_else(|| profile_region(self.profile.as_deref().unwrap_or("default")))
+            .unwrap_or_else(|| "us-east-1".to_string())
+    }
+
+    /// Build the client for this invocation.
+    ///
+    /// An explicit profile is used as-is; otherwise credentials come from the
+    /// default provider chain. The endpoint and TLS settings come from the
+    /// global flags.
+    pub fn build_client(&self) -> anyhow::Result<S3VectorsClient> {
+        let config = get_config();
+        let region = self.region();
+        let mut builder = S3VectorsClient::builder(&region);
+
+        let base: Arc<dyn CredentialsProvider> = match &self.profile {
+            Some(profile) => {
+                tracing::info!("Using AWS profile: {}", profile);
+                match ProfileProvider::new(profile) {
+                    Ok(provider) => Arc::new(provider.mfa_token_provider(mfa_prompt())),
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to load profile '{}': {:#}. Using default client.",
+                            profile,
+                            e
+                        );
+                        Arc::new(DefaultCredentialsChain::new(&region)?)
+                    }
+                }
+            }
+            None => Arc::new(DefaultCredentialsChain::new(&region)?),
+        };
+
+        builder = match &self.role_arn {
+            Some(role_arn) => {
+                tracing::info!("Assuming role: {}", role_arn);
+                let mut provider = AssumeRoleProvider::new(role_arn, base, &region);
+                if let Some(external_id) = &self.external_id {
+                    provider = provider.external_id(external_id);
+                }
+                if let Some(serial_number) = &self.mfa_serial {
+                    provider = provider.mfa(serial_number, mfa_prompt());
+                }
+                builder.credentials_provider(provider)
+            }
+            None => builder.credentials_provider(base),
+        };
+
+        if let Some(url) = self.endpoint_url.as_deref().or(config.endpoint_url()) {
+            tracing::info!("Using custom endpoint: {}", url);
+            builder = builder.endpoint(url);
+        }
+        if let Some(path) = &self.ca_bundle {
+            builder = builder.ca_bundle(path);
+        }
+        if self.no_verify_ssl {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        builder.build()
+    }
+}
+
+/// Ask for MFA codes on the terminal when a role session has to be created
+fn mfa_prompt() -> MfaTokenProvider {
+    MfaTokenProvider::new(|serial_number| {
+        dialoguer::Input::<String>::new()
+            .with_prompt(format!("MFA code for {serial_number}"))
+            .interact_text()
+            .map_err(|e| anyhow::anyhow!("Failed to read MFA code: {e}"))
+    })
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     #[command(about = "Initialize AWS credentials")]
@@ -79,8 +207,14 @@ pub enum Commands {
     #[command(about = "Manage bucket policies")]
     Policy(policy::PolicyCommand),
 
+    #[command(about = "Manage vector bucket and index tags")]
+    Tag(tag::TagCommand),
+
     #[command(about = "RAG (Retrieval-Augmented Generation) operations")]
     Rag(rag::RagCommand),
+
+    #[command(about = "Run a local S3 Vectors emulator")]
+    Emulate(emulate::EmulateCommand),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
@@ -100,11 +234,43 @@ impl fmt::Display for OutputFormat {
     }
 }
 
+/// `--wait` flags shared by commands that create or delete resources
+#[derive(clap::Args, Debug, Clone, Copy, PartialEq)]
+pub struct WaitArgs {
+    #[arg(long, help = "Wait until the change has taken effect")]
+    pub wait: bool,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        default_value = "300",
+        requires = "wait",
+        help = "Give up waiting after this many seconds"
+    )]
+    pub wait_timeout: u64,
+}
+
+impl WaitArgs {
+    /// The waiter to use, if `--wait` was given
+    pub fn waiter(&self) -> Option<Waiter> {
+        self.wait
+            .then(|| Waiter::with_deadline(Duration::from_secs(self.wait_timeout)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use clap::Parser;
 
+    #[test]
+    fn test_cli_definition_is_consistent() {
+        // Catches clashing flags (e.g. a subcommand `-p` against the global
+        // `--profile`), which clap otherwise only reports when run
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
     #[test]
     fn test_parse_cli_without_command() {
         // Test that CLI can be parsed without a command (for interactive mode)
@@ -131,11 +297,74 @@ mod tests {
             "list",
         ];
         let cli = Cli::try_parse_from(args).unwrap();
-        assert_eq!(cli.region, "us-west-2");
+        assert_eq!(cli.region(), "us-west-2");
         assert!(cli.verbose);
         assert!(matches!(cli.command, Some(Commands::Bucket(_))));
     }
 
+    #[test]
+    fn test_parse_cli_with_endpoint_url() {
+        let args = vec![
+            "s3-vectors",
+            "--endpoint-url",
+            "http://localhost:8080",
+            "bucket",
+            "list",
+        ];
+        let cli = Cli::try_parse_from(args).unwrap();
+        assert_eq!(cli.endpoint_url.as_deref(), Some("http://localhost:8080"));
+    }
+
+    #[test]
+    fn test_parse_cli_with_assume_role_options() {
+        let cli = Cli::try_parse_from([
+            "s3-vectors",
+            "--role-arn",
+            "arn:aws:iam::111122223333:role/vectors-reader",
+            "--external-id",
+            "shared-ml",
+            "--mfa-serial",
+            "arn:aws:iam::444455556666:mfa/dev",
+            "bucket",
+            "list",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.role_arn.as_deref(),
+            Some("arn:aws:iam::111122223333:role/vectors-reader")
+        );
+        assert_eq!(cli.external_id.as_deref(), Some("shared-ml"));
+        assert!(cli.build_client().is_ok());
+
+        // External ID and MFA only make sense together with a role
+        assert!(
+            Cli::try_parse_from(["s3-vectors", "--external-id", "x", "bucket", "list"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_build_client_honours_tls_flags() {
+        let cli = Cli::try_parse_from([
+            "s3-vectors",
+            "--no-verify-ssl",
+            "--ca-bundle",
+            "/nonexistent/ca.pem",
+            "bucket",
+            "list",
+        ])
+        .unwrap();
+        assert!(cli.no_verify_ssl);
+        assert_eq!(cli.ca_bundle, Some(PathBuf::from("/nonexistent/ca.pem")));
+
+        // The bundle is read while building, so a bad path fails loudly
+        // instead of silently falling back to the default trust store
+        let err = cli.build_client().unwrap_err();
+        assert!(err.to_string().contains("CA bundle"));
+
+        let insecure = Cli::try_parse_from(["s3-vectors", "--no-verify-ssl"]).unwrap();
+        assert!(insecure.build_client().is_ok());
+    }
+
     #[test]
     fn test_output_format_display() {
         assert_eq!(OutputFormat::Json.to_string(), "json");
@@ -1,44 +1,166 @@
-use crate::cli::output::print_output;
+use crate::cli::output::{print_output, print_table};
+use crate::cli::resource::BucketTarget;
 use crate::cli::OutputFormat;
-use crate::S3VectorsClient;
+use crate::policy::{
+    Decision, HistoryEntry, PolicyDocument, PolicyHistory, PolicyTemplate, SimulationRequest,
+};
+use crate::{ResourceArn, S3VectorsClient, S3VectorsError};
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
+use serde::Serialize;
 use std::fs;
+use std::path::PathBuf;
+use tabled::Tabled;
 
 #[derive(Args, Debug)]
 pub struct PolicyCommand {
     #[command(subcommand)]
     pub command: PolicySubcommands,
+
+    #[arg(
+        long,
+        global = true,
+        env = "S3_VECTORS_POLICY_HISTORY",
+        help = "Where replaced policies are saved [default: ~/.s3-vectors/policy-history]"
+    )]
+    pub history_dir: Option<PathBuf>,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum PolicySubcommands {
     #[command(about = "Put a bucket policy")]
     Put {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN")]
         bucket: String,
 
-        #[arg(short, long, help = "Policy JSON document")]
+        // No `-p`: that is the global `--profile`
+        #[arg(long, help = "Policy JSON document")]
         policy: Option<String>,
 
         #[arg(short, long, help = "Path to policy JSON file")]
         file: Option<String>,
     },
 
+    #[command(about = "Show how a new policy differs from the bucket's current one")]
+    Diff {
+        #[arg(help = "Vector bucket name or ARN")]
+        bucket: String,
+
+        #[arg(long, help = "New policy JSON document")]
+        policy: Option<String>,
+
+        #[arg(short, long, help = "Path to the new policy JSON file")]
+        file: Option<String>,
+    },
+
+    #[command(about = "List saved versions of a bucket policy")]
+    History {
+        #[arg(help = "Vector bucket name or ARN")]
+        bucket: String,
+    },
+
+    #[command(about = "Restore a saved bucket policy")]
+    Rollback {
+        #[arg(help = "Vector bucket name or ARN")]
+        bucket: String,
+
+        #[arg(
+            long,
+            help = "Timestamp (or unique prefix) from `policy history` [default: latest]"
+        )]
+        to: Option<String>,
+    },
+
     #[command(about = "Get bucket policy")]
     Get {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN")]
         bucket: String,
     },
 
     #[command(about = "Delete bucket policy")]
     Delete {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN")]
         bucket: String,
 
         #[arg(long, help = "Skip confirmation prompt")]
         force: bool,
     },
+
+    #[command(about = "Generate a bucket policy from a template")]
+    Generate {
+        #[arg(help = "Vector bucket name or ARN")]
+        bucket: String,
+
+        #[arg(long, value_enum, help = "Policy template")]
+        template: PolicyTemplate,
+
+        #[arg(long, help = "IAM user or role ARN, or a 12-digit account ID")]
+        principal: String,
+    },
+
+    #[command(about = "Check whether a bucket policy allows a request")]
+    Simulate {
+        #[arg(help = "Vector bucket name or ARN")]
+        bucket: String,
+
+        #[arg(
+            long,
+            help = "Caller: IAM user, role or assumed-role ARN, or account ID"
+        )]
+        principal: String,
+
+        #[arg(long, help = "Action, e.g. s3vectors:QueryVectors (prefix optional)")]
+        action: String,
+
+        #[arg(long, help = "Bucket or index ARN the action is performed on")]
+        resource: String,
+
+        #[arg(
+            long = "context",
+            help = "Condition key of the request (key=value); repeat a key for several values"
+        )]
+        context: Vec<String>,
+
+        #[arg(long, help = "Policy JSON document instead of the stored policy")]
+        policy: Option<String>,
+
+        #[arg(
+            short,
+            long,
+            help = "Path to a policy JSON file instead of the stored policy",
+            conflicts_with = "policy"
+        )]
+        file: Option<String>,
+    },
+}
+
+impl PolicySubcommands {
+    fn bucket(&self) -> &str {
+        match self {
+            PolicySubcommands::Put { bucket, .. }
+            | PolicySubcommands::Diff { bucket, .. }
+            | PolicySubcommands::History { bucket }
+            | PolicySubcommands::Rollback { bucket, .. }
+            | PolicySubcommands::Get { bucket }
+            | PolicySubcommands::Delete { bucket, .. }
+            | PolicySubcommands::Generate { bucket, .. }
+            | PolicySubcommands::Simulate { bucket, .. } => bucket,
+        }
+    }
+}
+
+#[derive(Serialize, Tabled)]
+struct HistoryRow {
+    timestamp: String,
+    path: String,
+}
+
+#[derive(Serialize, Tabled)]
+struct StatementRow {
+    statement: String,
+    effect: String,
+    matched: String,
+    reason: String,
 }
 
 impl PolicyCommand {
@@ -47,12 +169,11 @@ impl PolicyCommand {
         client: &S3VectorsClient,
         output_format: OutputFormat,
     ) -> Result<()> {
+        let target = BucketTarget::parse(self.command.bucket())?;
+        let client = &target.client(client)?;
+        let bucket = &target.bucket;
         match &self.command {
-            PolicySubcommands::Put {
-                bucket,
-                policy,
-                file,
-            } => {
+            PolicySubcommands::Put { policy, file, .. } => {
                 self.put_policy(
                     client,
                     bucket,
@@ -62,13 +183,62 @@ impl PolicyCommand {
                 )
                 .await
             }
-            PolicySubcommands::Get { bucket } => {
-                self.get_policy(client, bucket, output_format).await
+            PolicySubcommands::Diff { policy, file, .. } => {
+                self.diff_policy(
+                    client,
+                    bucket,
+                    policy.as_deref(),
+                    file.as_deref(),
+                    output_format,
+                )
+                .await
             }
-            PolicySubcommands::Delete { bucket, force } => {
+            PolicySubcommands::History { .. } => self.list_history(client, bucket, output_format),
+            PolicySubcommands::Rollback { to, .. } => {
+                self.rollback_policy(client, bucket, to.as_deref(), output_format)
+                    .await
+            }
+            PolicySubcommands::Get { .. } => self.get_policy(client, bucket, output_format).await,
+            PolicySubcommands::Delete { force, .. } => {
                 self.delete_policy(client, bucket, *force, output_format)
                     .await
             }
+            PolicySubcommands::Generate {
+                template,
+                principal,
+                ..
+            } => {
+                self.generate_policy(client, bucket, *template, principal, output_format)
+                    .await
+            }
+            PolicySubcommands::Simulate {
+                principal,
+                action,
+                resource,
+                context,
+                policy,
+                file,
+                ..
+            } => {
+                let action = if action.contains(':') {
+                    action.clone()
+                } else {
+                    format!("s3vectors:{action}")
+                };
+                let mut request = SimulationRequest::new(principal, action, resource);
+                for (key, values) in parse_context(context)? {
+                    request = request.with_context(&key, values);
+                }
+                self.simulate_policy(
+                    client,
+                    bucket,
+                    request,
+                    policy.as_deref(),
+                    file.as_deref(),
+                    output_format,
+                )
+                .await
+            }
         }
     }
 
@@ -80,33 +250,126 @@ impl PolicyCommand {
         file: Option<&str>,
         output_format: OutputFormat,
     ) -> Result<()> {
-        let policy_json = match (policy, file) {
-            (Some(p), None) => p.to_string(),
-            (None, Some(f)) => fs::read_to_string(f).context("Failed to read policy file")?,
+        let policy_json = read_policy(policy, file)?;
+        parse_policy(&policy_json)?;
+
+        let backup = self.backup_policy(client, bucket).await?;
+        client
+            .put_vector_bucket_policy(bucket, &policy_json)
+            .await?;
+
+        match output_format {
+            OutputFormat::Table => {
+                println!("✓ Bucket policy updated successfully for '{bucket}'");
+                if let Some(backup) = &backup {
+                    println!("  Previous policy saved as {}", backup.path.display());
+                }
+            }
             _ => {
-                return Err(anyhow::anyhow!(
-                    "Either --policy or --file must be provided"
-                ))
+                let result = serde_json::json!({
+                    "status": "success",
+                    "bucket": bucket,
+                    "message": "Policy updated",
+                    "backup": backup.map(|b| b.path),
+                });
+                print_output(&result, output_format)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn diff_policy(
+        &self,
+        client: &S3VectorsClient,
+        bucket: &str,
+        policy: Option<&str>,
+        file: Option<&str>,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        let new = parse_policy(&read_policy(policy, file)?)?;
+        // A bucket without a policy diffs as an empty one
+        let current = match current_policy(client, bucket).await? {
+            Some(current) => {
+                parse_policy(&current).context("Cannot read the bucket's current policy")?
             }
+            None => PolicyDocument::new([]),
         };
+        let diff = current.diff(&new);
+
+        match output_format {
+            OutputFormat::Table => print!("{diff}"),
+            _ => print_output(&diff, output_format)?,
+        }
+
+        Ok(())
+    }
+
+    fn list_history(
+        &self,
+        client: &S3VectorsClient,
+        bucket: &str,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        let history = self.history(client)?.list(bucket)?;
+
+        match output_format {
+            OutputFormat::Table => {
+                if history.is_empty() {
+                    println!("No saved policies for bucket '{bucket}'");
+                    return Ok(());
+                }
+                print_table(
+                    history
+                        .into_iter()
+                        .map(|entry| HistoryRow {
+                            timestamp: entry.timestamp,
+                            path: entry.path.display().to_string(),
+                        })
+                        .collect(),
+                )?;
+            }
+            _ => print_output(&history, output_format)?,
+        }
+
+        Ok(())
+    }
 
-        // Validate JSON
-        let _: serde_json::Value =
-            serde_json::from_str(&policy_json).context("Invalid JSON policy")?;
+    async fn rollback_policy(
+        &self,
+        client: &S3VectorsClient,
+        bucket: &str,
+        to: Option<&str>,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        let history = self.history(client)?;
+        let entry = history.find(bucket, to)?;
+        let policy_json = history.load(&entry)?;
+        parse_policy(&policy_json)
+            .with_context(|| format!("Saved policy {} is invalid", entry.path.display()))?;
 
+        // The policy being replaced is saved too, so a rollback can be undone
+        let backup = self.backup_policy(client, bucket).await?;
         client
             .put_vector_bucket_policy(bucket, &policy_json)
             .await?;
 
         match output_format {
             OutputFormat::Table => {
-                println!("✓ Bucket policy updated successfully for '{bucket}'");
+                println!(
+                    "✓ Restored bucket policy for '{bucket}' from {}",
+                    entry.timestamp
+                );
+                if let Some(backup) = &backup {
+                    println!("  Replaced policy saved as {}", backup.path.display());
+                }
             }
             _ => {
                 let result = serde_json::json!({
                     "status": "success",
                     "bucket": bucket,
-                    "message": "Policy updated"
+                    "restored": entry.timestamp,
+                    "backup": backup.map(|b| b.path),
                 });
                 print_output(&result, output_format)?;
             }
@@ -115,6 +378,29 @@ impl PolicyCommand {
         Ok(())
     }
 
+    /// Backups for this client's region; bucket names are only unique per
+    /// region and account
+    fn history(&self, client: &S3VectorsClient) -> Result<PolicyHistory> {
+        let dir = match &self.history_dir {
+            Some(dir) => dir.clone(),
+            None => PolicyHistory::default_dir()
+                .ok_or_else(|| anyhow::anyhow!("Cannot find home directory; pass --history-dir"))?,
+        };
+        Ok(PolicyHistory::new(dir.join(client.region())))
+    }
+
+    /// Save the bucket's current policy, if it has one
+    async fn backup_policy(
+        &self,
+        client: &S3VectorsClient,
+        bucket: &str,
+    ) -> Result<Option<HistoryEntry>> {
+        match current_policy(client, bucket).await? {
+            Some(current) => Ok(Some(self.history(client)?.save(bucket, &current)?)),
+            None => Ok(None),
+        }
+    }
+
     async fn get_policy(
         &self,
         client: &S3VectorsClient,
@@ -142,6 +428,87 @@ impl PolicyCommand {
         Ok(())
     }
 
+    async fn generate_policy(
+        &self,
+        client: &S3VectorsClient,
+        bucket: &str,
+        template: PolicyTemplate,
+        principal: &str,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        let bucket_arn = client
+            .describe_vector_bucket(bucket)
+            .await?
+            .vector_bucket_arn;
+        let policy = PolicyDocument::from_template(template, principal, &bucket_arn)?;
+
+        match output_format {
+            // The policy itself, ready for `policy put -f`
+            OutputFormat::Table => println!("{}", serde_json::to_string_pretty(&policy)?),
+            _ => print_output(&policy, output_format)?,
+        }
+
+        Ok(())
+    }
+
+    async fn simulate_policy(
+        &self,
+        client: &S3VectorsClient,
+        bucket: &str,
+        request: SimulationRequest,
+        policy: Option<&str>,
+        file: Option<&str>,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        request.validate()?;
+        let resource: ResourceArn = request.resource.parse()?;
+        if resource.bucket().bucket_name != bucket {
+            anyhow::bail!(
+                "Resource '{}' is not in bucket '{bucket}'",
+                request.resource
+            );
+        }
+        let policy_json = match (policy, file) {
+            (None, None) => client.get_vector_bucket_policy(bucket).await?,
+            _ => read_policy(policy, file)?,
+        };
+        let policy = parse_policy(&policy_json)?;
+        let simulation = policy.simulate(&request);
+
+        match output_format {
+            OutputFormat::Table => {
+                let rows = simulation
+                    .statements
+                    .iter()
+                    .map(|s| StatementRow {
+                        statement: match &s.sid {
+                            Some(sid) => format!("{} ({sid})", s.index),
+                            None => s.index.to_string(),
+                        },
+                        effect: s.effect.as_str().to_string(),
+                        matched: if s.matched { "yes" } else { "no" }.to_string(),
+                        reason: s.reason.clone(),
+                    })
+                    .collect();
+                print_table(rows)?;
+                let mark = if simulation.allowed() { "✓" } else { "✗" };
+                println!(
+                    "\n{mark} {} {} on {}: {}",
+                    request.principal,
+                    request.action,
+                    request.resource,
+                    simulation.explanation()
+                );
+                if simulation.decision == Decision::ImplicitDeny {
+                    println!("  An identity policy in the bucket's account may still allow it.");
+                }
+            }
+            _ => print_output(&simulation, output_format)?,
+        }
+
+        Ok(())
+    }
+
     async fn delete_policy(
         &self,
         client: &S3VectorsClient,
@@ -164,17 +531,22 @@ impl PolicyCommand {
             }
         }
 
+        let backup = self.backup_policy(client, bucket).await?;
         client.delete_vector_bucket_policy(bucket).await?;
 
         match output_format {
             OutputFormat::Table => {
                 println!("✓ Bucket policy deleted successfully for '{bucket}'");
+                if let Some(backup) = &backup {
+                    println!("  Deleted policy saved as {}", backup.path.display());
+                }
             }
             _ => {
                 let result = serde_json::json!({
                     "status": "success",
                     "bucket": bucket,
-                    "message": "Policy deleted"
+                    "message": "Policy deleted",
+                    "backup": backup.map(|b| b.path),
                 });
                 print_output(&result, output_format)?;
             }
@@ -184,6 +556,59 @@ impl PolicyCommand {
     }
 }
 
+/// The policy given inline with `--policy` or as a file with `--file`
+fn read_policy(policy: Option<&str>, file: Option<&str>) -> Result<String> {
+    match (policy, file) {
+        (Some(p), None) => Ok(p.to_string()),
+        (None, Some(f)) => fs::read_to_string(f).context("Failed to read policy file"),
+        _ => Err(anyhow::anyhow!(
+            "Either --policy or --file must be provided"
+        )),
+    }
+}
+
+/// Parse a policy given to `put`, `diff`, `simulate` or `rollback`. Catches
+/// bad actions, ARNs and principals before the service does, but only warns
+/// about them: the service may accept what this client does not know (e.g. a
+/// newer condition operator). `generate` builds only policies that validate.
+fn parse_policy(policy_json: &str) -> Result<PolicyDocument> {
+    let value: serde_json::Value =
+        serde_json::from_str(policy_json).context("The policy is not valid JSON")?;
+    let policy = PolicyDocument::from_json_unvalidated(&value)?;
+    if let Err(e) = policy.validate() {
+        eprintln!("Warning: {e}");
+    }
+    Ok(policy)
+}
+
+/// The bucket's policy, `None` if it has none
+async fn current_policy(client: &S3VectorsClient, bucket: &str) -> Result<Option<String>> {
+    match client.get_vector_bucket_policy(bucket).await {
+        Ok(policy) => Ok(Some(policy)),
+        Err(S3VectorsError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// `key=value` pairs grouped by key, so repeated keys become multi-valued
+fn parse_context(pairs: &[String]) -> Result<Vec<(String, Vec<String>)>> {
+    let mut context: Vec<(String, Vec<String>)> = Vec::new();
+    for pair in pairs {
+        let Some((key, value)) = pair.split_once('=') else {
+            anyhow::bail!("Invalid context '{pair}': expected key=value");
+        };
+        let key = key.trim();
+        match context
+            .iter_mut()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        {
+            Some((_, values)) => values.push(value.trim().to_string()),
+            None => context.push((key.to_string(), vec![value.trim().to_string()])),
+        }
+    }
+    Ok(context)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -246,6 +671,217 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_generate_policy_command() {
+        let args = vec![
+            "test",
+            "generate",
+            "my-bucket",
+            "--template",
+            "cross-account-query",
+            "--principal",
+            "444455556666",
+        ];
+        let cli = TestCli::parse_from(args);
+
+        match cli.command {
+            PolicySubcommands::Generate {
+                bucket,
+                template,
+                principal,
+            } => {
+                assert_eq!(bucket, "my-bucket");
+                assert_eq!(template, PolicyTemplate::CrossAccountQuery);
+                assert_eq!(principal, "444455556666");
+            }
+            _ => panic!("Expected Generate command"),
+        }
+        assert!(TestCli::try_parse_from(["test", "generate", "my-bucket"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_simulate_policy_command() {
+        let args = vec![
+            "test",
+            "simulate",
+            "my-bucket",
+            "--principal",
+            "arn:aws:iam::111122223333:role/app",
+            "--action",
+            "s3vectors:PutVectors",
+            "--resource",
+            "arn:aws:s3vectors:us-east-1:111122223333:bucket/my-bucket/index/docs",
+            "--context",
+            "aws:SourceIp=10.0.0.1",
+            "--context",
+            "aws:TagKeys=a",
+            "--context",
+            "aws:tagkeys=b",
+        ];
+        let cli = TestCli::parse_from(args);
+
+        match cli.command {
+            PolicySubcommands::Simulate {
+                bucket,
+                action,
+                context,
+                policy,
+                file,
+                ..
+            } => {
+                assert_eq!(bucket, "my-bucket");
+                assert_eq!(action, "s3vectors:PutVectors");
+                assert!(policy.is_none() && file.is_none());
+                let context = parse_context(&context).unwrap();
+                assert_eq!(
+                    context[0],
+                    ("aws:SourceIp".to_string(), vec!["10.0.0.1".to_string()])
+                );
+                assert_eq!(context[1].1, ["a", "b"]);
+            }
+            _ => panic!("Expected Simulate command"),
+        }
+        assert!(parse_context(&["aws:SourceIp".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_parse_diff_and_rollback_commands() {
+        let cli = TestCli::parse_from(["test", "diff", "my-bucket", "-f", "new.json"]);
+        match cli.command {
+            PolicySubcommands::Diff {
+                bucket,
+                policy,
+                file,
+            } => {
+                assert_eq!(bucket, "my-bucket");
+                assert!(policy.is_none());
+                assert_eq!(file.as_deref(), Some("new.json"));
+            }
+            _ => panic!("Expected Diff command"),
+        }
+
+        let cli = TestCli::parse_from(["test", "rollback", "my-bucket", "--to", "20250101T12"]);
+        match cli.command {
+            PolicySubcommands::Rollback { bucket, to } => {
+                assert_eq!(bucket, "my-bucket");
+                assert_eq!(to.as_deref(), Some("20250101T12"));
+            }
+            _ => panic!("Expected Rollback command"),
+        }
+        match TestCli::parse_from(["test", "rollback", "my-bucket"]).command {
+            PolicySubcommands::Rollback { to, .. } => assert!(to.is_none()),
+            _ => panic!("Expected Rollback command"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_and_rollback_keep_history() {
+        use crate::emulator::Emulator;
+        let emulator = Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        client.create_vector_bucket("docs-bucket").await.unwrap();
+
+        let command = |command| PolicyCommand {
+            command,
+            history_dir: Some(dir.path().to_path_buf()),
+        };
+        let arn = "arn:aws:s3vectors:us-east-1:123456789012:bucket/docs-bucket";
+        let policy = |action: &str| {
+            format!(
+                r#"{{"Version": "2012-10-17", "Statement": {{"Effect": "Allow",
+                "Principal": {{"AWS": "444455556666"}}, "Action": "s3vectors:{action}",
+                "Resource": "{arn}"}}}}"#
+            )
+        };
+        let put = |action: &str| {
+            command(PolicySubcommands::Put {
+                bucket: "docs-bucket".into(),
+                policy: Some(policy(action)),
+                file: None,
+            })
+        };
+
+        // Nothing to save before the first policy
+        put("GetVectors")
+            .execute(&client, OutputFormat::Json)
+            .await
+            .unwrap();
+        let history = command(PolicySubcommands::History { bucket: "x".into() })
+            .history(&client)
+            .unwrap();
+        assert!(history.list("docs-bucket").unwrap().is_empty());
+
+        put("QueryVectors")
+            .execute(&client, OutputFormat::Json)
+            .await
+            .unwrap();
+        let saved = history.list("docs-bucket").unwrap();
+        assert_eq!(saved.len(), 1);
+        assert!(history
+            .load(&saved[0])
+            .unwrap()
+            .contains("s3vectors:GetVectors"));
+
+        command(PolicySubcommands::Rollback {
+            bucket: "docs-bucket".into(),
+            to: None,
+        })
+        .execute(&client, OutputFormat::Json)
+        .await
+        .unwrap();
+        let current = client
+            .get_vector_bucket_policy("docs-bucket")
+            .await
+            .unwrap();
+        assert!(current.contains("s3vectors:GetVectors"));
+        // The rolled-back policy is saved as well
+        assert_eq!(history.list("docs-bucket").unwrap().len(), 2);
+
+        // Actions this client does not know are left for the service to judge
+        put("SomeFutureAction")
+            .execute(&client, OutputFormat::Json)
+            .await
+            .unwrap();
+        let current = client
+            .get_vector_bucket_policy("docs-bucket")
+            .await
+            .unwrap();
+        assert!(current.contains("s3vectors:SomeFutureAction"));
+        assert!(parse_policy("{\"Version\": \"2012-10-17\"}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_simulate_rejects_resource_in_another_bucket() {
+        let client = S3VectorsClient::new("us-east-1");
+        let simulate = |resource: &str| PolicyCommand {
+            command: PolicySubcommands::Simulate {
+                bucket: "my-bucket".into(),
+                principal: "111122223333".into(),
+                action: "s3vectors:QueryVectors".into(),
+                resource: resource.into(),
+                context: vec![],
+                policy: Some(
+                    r#"{"Version": "2012-10-17", "Statement": {"Effect": "Allow",
+                    "Principal": "*", "Action": "s3vectors:*", "Resource": "*"}}"#
+                        .into(),
+                ),
+                file: None,
+            },
+            history_dir: None,
+        };
+
+        let err = simulate("arn:aws:s3vectors:us-east-1:111122223333:bucket/other/index/docs")
+            .execute(&client, OutputFormat::Json)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not in bucket 'my-bucket'"));
+        simulate("arn:aws:s3vectors:us-east-1:111122223333:bucket/my-bucket/index/docs")
+            .execute(&client, OutputFormat::Json)
+            .await
+            .unwrap();
+    }
+
     #[test]
     fn test_parse_delete_policy_command() {
         let args = vec!["test", "delete", "my-bucket", "--force"];
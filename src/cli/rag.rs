@@ -5,9 +5,9 @@ use std::io::{self, Write};
 use std::path::PathBuf;
 
 use crate::{
-    cli::OutputFormat,
-    rag::{rag_query, RagConfig, RagPipeline},
-    S3VectorsClient,
+    cli::{where_clause::parse_where, OutputFormat},
+    rag::{rag_query, rag_query_with_filter, RagConfig, RagPipeline},
+    Filter, IndexMismatchError, S3VectorsClient,
 };
 
 #[derive(Args, Debug)]
@@ -35,6 +35,20 @@ pub enum RagSubcommands {
             default_value = "documents-default"
         )]
         index: String,
+
+        #[arg(
+            long,
+            value_delimiter = ',',
+            default_value = "content",
+            help = "Comma-separated metadata keys to exclude from query filters (\"\" for none)"
+        )]
+        non_filterable_keys: Vec<String>,
+
+        #[arg(
+            long,
+            help = "Delete and recreate the index if its settings differ (deletes all its vectors)"
+        )]
+        recreate: bool,
     },
 
     #[command(about = "Ingest documents from a directory")]
@@ -82,6 +96,13 @@ pub enum RagSubcommands {
             default_value = "documents-default"
         )]
         index: String,
+
+        #[arg(
+            short = 'w',
+            long = "where",
+            help = "Only search documents matching this expression, e.g. \"source = 'guide.md'\""
+        )]
+        where_clause: Option<String>,
     },
 
     #[command(about = "Interactive RAG query mode")]
@@ -111,8 +132,21 @@ impl RagCommand {
         output_format: OutputFormat,
     ) -> Result<()> {
         match &self.command {
-            RagSubcommands::Init { bucket, index } => {
-                self.init_rag(client, bucket, index, output_format).await
+            RagSubcommands::Init {
+                bucket,
+                index,
+                non_filterable_keys,
+                recreate,
+            } => {
+                self.init_rag(
+                    client,
+                    bucket,
+                    index,
+                    non_filterable_keys,
+                    *recreate,
+                    output_format,
+                )
+                .await
             }
             RagSubcommands::Ingest {
                 directory,
@@ -127,8 +161,10 @@ impl RagCommand {
                 top_k,
                 bucket,
                 index,
+                where_clause,
             } => {
-                self.query_rag(client, query, *top_k, bucket, index, output_format)
+                let filter = where_clause.as_deref().map(parse_where).transpose()?;
+                self.query_rag(client, query, *top_k, bucket, index, filter, output_format)
                     .await
             }
             RagSubcommands::Interactive { bucket, index } => {
@@ -143,6 +179,8 @@ impl RagCommand {
         client: &S3VectorsClient,
         bucket_name: &str,
         index_name: &str,
+        non_filterable_keys: &[String],
+        recreate: bool,
         output_format: OutputFormat,
     ) -> Result<()> {
         println!("🚀 {} RAG pipeline...", "Initializing".cyan());
@@ -150,6 +188,8 @@ impl RagCommand {
         let config = RagConfig {
             bucket_name: bucket_name.to_string(),
             index_name: index_name.to_string(),
+            non_filterable_metadata_keys: listed_keys(non_filterable_keys),
+            recreate_index_on_mismatch: recreate,
             ..Default::default()
         };
 
@@ -162,6 +202,10 @@ impl RagCommand {
                     println!("   Bucket: {}", bucket_name.cyan());
                     println!("   Index: {}", index_name.cyan());
                     println!("   Region: {}", client.region().cyan());
+                    println!(
+                        "   Non-filterable keys: {}",
+                        non_filterable_keys.join(", ").cyan()
+                    );
                 }
                 _ => {
                     let result = serde_json::json!({
@@ -169,12 +213,18 @@ impl RagCommand {
                         "bucket": bucket_name,
                         "index": index_name,
                         "region": client.region(),
+                        "nonFilterableMetadataKeys": non_filterable_keys,
                     });
                     crate::cli::output::print_output(&result, output_format)?;
                 }
             },
             Err(e) => {
                 eprintln!("❌ {} initializing RAG pipeline: {}", "Failed".red(), e);
+                if e.downcast_ref::<IndexMismatchError>().is_some() {
+                    eprintln!(
+                        "   Use a different --index, or pass --recreate to delete and recreate it"
+                    );
+                }
                 return Err(e);
             }
         }
@@ -268,7 +318,7 @@ impl RagCommand {
         // Check if bucket exists
         match client.describe_vector_bucket(bucket_name).await {
             Ok(_) => {}
-            Err(crate::deploy::S3VectorsError::NotFound(_)) => {
+            Err(crate::S3VectorsError::NotFound(_)) => {
                 return Err(anyhow::anyhow!(
                     "Bucket '{}' not found. The RAG pipeline needs to be initialized first.",
                     bucket_name
@@ -280,7 +330,7 @@ impl RagCommand {
         // Check if index exists
         match client.describe_index(bucket_name, index_name).await {
             Ok(_) => {}
-            Err(crate::deploy::S3VectorsError::NotFound(_)) => {
+            Err(crate::S3VectorsError::NotFound(_)) => {
                 return Err(anyhow::anyhow!(
                     "Index '{}' not found in bucket '{}'. The RAG pipeline needs to be initialized first.", 
                     index_name, bucket_name
@@ -292,6 +342,7 @@ impl RagCommand {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn query_rag(
         &self,
         client: &S3VectorsClient,
@@ -299,6 +350,7 @@ impl RagCommand {
         top_k: u32,
         bucket_name: &str,
         index_name: &str,
+        filter: Option<Filter>,
         output_format: OutputFormat,
     ) -> Result<()> {
         // Pre-flight check: Verify bucket and index exist
@@ -329,7 +381,7 @@ impl RagCommand {
 
         let pipeline = RagPipeline::new(config, client.clone());
 
-        match rag_query(&pipeline, query, top_k).await {
+        match rag_query_with_filter(&pipeline, query, top_k, filter).await {
             Ok(response) => match output_format {
                 OutputFormat::Table => {
                     println!("{response}");
@@ -427,6 +479,11 @@ impl RagCommand {
     }
 }
 
+/// Keys given to `--non-filterable-keys`, where `""` turns the default off
+fn listed_keys(keys: &[String]) -> Vec<String> {
+    keys.iter().filter(|key| !key.is_empty()).cloned().collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -444,9 +501,16 @@ mod tests {
         let cli = TestCli::parse_from(args);
 
         match cli.command {
-            RagSubcommands::Init { bucket, index } => {
+            RagSubcommands::Init {
+                bucket,
+                index,
+                non_filterable_keys,
+                recreate,
+            } => {
                 assert_eq!(bucket, "rag-vectors-default");
                 assert_eq!(index, "documents-default");
+                assert_eq!(non_filterable_keys, vec!["content"]);
+                assert!(!recreate);
             }
             _ => panic!("Expected init command"),
         }
@@ -461,13 +525,34 @@ mod tests {
             "my-bucket",
             "--index",
             "my-index",
+            "--non-filterable-keys",
+            "content,source",
+            "--recreate",
         ];
         let cli = TestCli::parse_from(args);
 
         match cli.command {
-            RagSubcommands::Init { bucket, index } => {
+            RagSubcommands::Init {
+                bucket,
+                index,
+                non_filterable_keys,
+                recreate,
+            } => {
                 assert_eq!(bucket, "my-bucket");
                 assert_eq!(index, "my-index");
+                assert_eq!(non_filterable_keys, vec!["content", "source"]);
+                assert!(recreate);
+            }
+            _ => panic!("Expected init command"),
+        }
+
+        let cli = TestCli::parse_from(["test", "init", "--non-filterable-keys", ""]);
+        match cli.command {
+            RagSubcommands::Init {
+                non_filterable_keys,
+                ..
+            } => {
+                assert!(listed_keys(&non_filterable_keys).is_empty());
             }
             _ => panic!("Expected init command"),
         }
@@ -0,0 +1,164 @@
+//! Buckets and indexes given on the command line by name or by ARN.
+//!
+//! Wherever a command takes a bucket name it also takes the bucket's ARN,
+//! and where it takes a bucket and an index name, an index ARN can replace
+//! both. The region in an ARN takes precedence over `--region`.
+
+use anyhow::{bail, Result};
+
+use crate::{IndexArn, ResourceArn, S3VectorsClient, VectorBucketArn};
+
+/// A bucket given as a name or a vector bucket ARN
+#[derive(Clone, Debug, PartialEq)]
+pub struct BucketTarget {
+    pub bucket: String,
+    /// Region of the ARN, if one was given
+    pub region: Option<String>,
+}
+
+/// An index given as bucket and index names, a bucket ARN and an index name,
+/// or an index ARN
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexTarget {
+    pub bucket: String,
+    pub index: String,
+    /// Region of the ARN, if one was given
+    pub region: Option<String>,
+}
+
+impl BucketTarget {
+    pub fn parse(bucket: &str) -> Result<Self> {
+        if !is_arn(bucket) {
+            return Ok(Self {
+                bucket: bucket.to_string(),
+                region: None,
+            });
+        }
+        let arn: VectorBucketArn = bucket.parse()?;
+        Ok(Self {
+            bucket: arn.bucket_name,
+            region: Some(arn.region),
+        })
+    }
+
+    /// `client`, moved to the region of the ARN
+    pub fn client(&self, client: &S3VectorsClient) -> Result<S3VectorsClient> {
+        regional_client(client, self.region.as_deref())
+    }
+}
+
+impl IndexTarget {
+    /// `bucket` followed by the index name, or an index ARN on its own
+    pub fn parse(bucket: &str, index: Option<&str>) -> Result<Self> {
+        match Self::parse_shifted(bucket, index)? {
+            (target, None) => Ok(target),
+            (_, Some(extra)) => {
+                bail!("Unexpected argument '{extra}': the index ARN already names the index")
+            }
+        }
+    }
+
+    /// Like [`parse`](Self::parse), for commands with more positional
+    /// arguments: after an index ARN, `index` holds the next one, which is
+    /// returned
+    pub fn parse_shifted(bucket: &str, index: Option<&str>) -> Result<(Self, Option<String>)> {
+        let (bucket, region) = if is_arn(bucket) {
+            match bucket.parse::<ResourceArn>()? {
+                ResourceArn::Index(IndexArn { bucket, index_name }) => {
+                    let target = Self {
+                        bucket: bucket.bucket_name,
+                        index: index_name,
+                        region: Some(bucket.region),
+                    };
+                    return Ok((target, index.map(String::from)));
+                }
+                ResourceArn::Bucket(arn) => (arn.bucket_name, Some(arn.region)),
+            }
+        } else {
+            (bucket.to_string(), None)
+        };
+
+        let Some(index) = index else {
+            bail!("Missing index name (or give an index ARN instead of the bucket)");
+        };
+        let target = Self {
+            bucket,
+            index: index.to_string(),
+            region,
+        };
+        Ok((target, None))
+    }
+
+    /// `client`, moved to the region of the ARN
+    pub fn client(&self, client: &S3VectorsClient) -> Result<S3VectorsClient> {
+        regional_client(client, self.region.as_deref())
+    }
+}
+
+fn is_arn(value: &str) -> bool {
+    value.starts_with("arn:")
+}
+
+fn regional_client(client: &S3VectorsClient, region: Option<&str>) -> Result<S3VectorsClient> {
+    match region {
+        Some(region) if region != client.region() => {
+            tracing::info!("Using region {} from the ARN", region);
+            client.with_region(region)
+        }
+        _ => Ok(client.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUCKET: &str = "arn:aws:s3vectors:eu-west-1:111122223333:bucket/docs";
+
+    #[test]
+    fn test_bucket_target() {
+        let by_name = BucketTarget::parse("docs").unwrap();
+        assert_eq!(by_name.region, None);
+        let by_arn = BucketTarget::parse(BUCKET).unwrap();
+        assert_eq!(by_arn.bucket, "docs");
+        assert_eq!(by_arn.region.as_deref(), Some("eu-west-1"));
+        // Policies belong to buckets, not indexes
+        assert!(BucketTarget::parse(&format!("{BUCKET}/index/embeddings")).is_err());
+        assert!(BucketTarget::parse("arn:aws:s3:::docs").is_err());
+    }
+
+    #[test]
+    fn test_index_target() {
+        let expected = IndexTarget {
+            bucket: "docs".into(),
+            index: "embeddings".into(),
+            region: Some("eu-west-1".into()),
+        };
+        let index_arn = format!("{BUCKET}/index/embeddings");
+        assert_eq!(IndexTarget::parse(&index_arn, None).unwrap(), expected);
+        assert_eq!(
+            IndexTarget::parse(BUCKET, Some("embeddings")).unwrap(),
+            expected
+        );
+        let by_name = IndexTarget::parse("docs", Some("embeddings")).unwrap();
+        assert_eq!(by_name.region, None);
+
+        assert!(IndexTarget::parse("docs", None).is_err());
+        assert!(IndexTarget::parse(BUCKET, None).is_err());
+        assert!(IndexTarget::parse(&index_arn, Some("other")).is_err());
+
+        // After an index ARN, the next positional argument moves up
+        let (target, next) = IndexTarget::parse_shifted(&index_arn, Some("key1")).unwrap();
+        assert_eq!(target, expected);
+        assert_eq!(next.as_deref(), Some("key1"));
+    }
+
+    #[test]
+    fn test_arn_region_overrides_client_region() {
+        let client = S3VectorsClient::new("us-east-1");
+        let target = BucketTarget::parse(BUCKET).unwrap();
+        assert_eq!(target.client(&client).unwrap().region(), "eu-west-1");
+        let target = BucketTarget::parse("docs").unwrap();
+        assert_eq!(target.client(&client).unwrap().region(), "us-east-1");
+    }
+}
@@ -0,0 +1,204 @@
+use crate::cli::output::{print_output, print_table};
+use crate::cli::OutputFormat;
+use crate::S3VectorsClient;
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use tabled::Tabled;
+
+#[derive(Args, Debug)]
+pub struct TagCommand {
+    #[command(subcommand)]
+    pub command: TagSubcommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagSubcommands {
+    #[command(about = "Add or overwrite tags on a vector bucket or index")]
+    Add {
+        #[arg(help = "ARN of the vector bucket or index")]
+        arn: String,
+
+        #[arg(
+            help = "Tags in key=value format",
+            value_delimiter = ',',
+            required = true
+        )]
+        tags: Vec<String>,
+    },
+
+    #[command(about = "Remove tags from a vector bucket or index")]
+    Remove {
+        #[arg(help = "ARN of the vector bucket or index")]
+        arn: String,
+
+        #[arg(help = "Tag keys to remove", value_delimiter = ',', required = true)]
+        keys: Vec<String>,
+    },
+
+    #[command(about = "List tags of a vector bucket or index")]
+    List {
+        #[arg(help = "ARN of the vector bucket or index")]
+        arn: String,
+    },
+}
+
+#[derive(Serialize, Tabled)]
+struct TagInfo {
+    key: String,
+    value: String,
+}
+
+/// `--tag` filter: `key=value` matches that value, a bare `key` any value
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagFilter {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl FromStr for TagFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = match s.split_once('=') {
+            Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+            None => (s.trim(), None),
+        };
+        if key.is_empty() {
+            return Err(format!(
+                "Invalid tag filter '{s}': expected key or key=value"
+            ));
+        }
+        Ok(Self {
+            key: key.to_string(),
+            value,
+        })
+    }
+}
+
+impl TagFilter {
+    pub fn matches(&self, tags: &BTreeMap<String, String>) -> bool {
+        match (tags.get(&self.key), &self.value) {
+            (Some(actual), Some(expected)) => actual == expected,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+/// Whether `tags` satisfy every filter
+pub fn matches_all(filters: &[TagFilter], tags: &BTreeMap<String, String>) -> bool {
+    filters.iter().all(|filter| filter.matches(tags))
+}
+
+/// Parse `key=value` tags; values may be empty but every tag needs an `=`,
+/// and each key may only be given once
+pub fn parse_tags(tags: &[String]) -> Result<BTreeMap<String, String>> {
+    let mut parsed = BTreeMap::new();
+    for tag in tags {
+        let Some((key, value)) = tag.split_once('=') else {
+            anyhow::bail!("Invalid tag '{tag}': expected key=value");
+        };
+        let key = key.trim().to_string();
+        if parsed.contains_key(&key) {
+            anyhow::bail!("Tag '{key}' is given more than once");
+        }
+        parsed.insert(key, value.trim().to_string());
+    }
+    Ok(parsed)
+}
+
+impl TagCommand {
+    pub async fn execute(
+        &self,
+        client: &S3VectorsClient,
+        output_format: OutputFormat,
+    ) -> Result<()> {
+        match &self.command {
+            TagSubcommands::Add { arn, tags } => {
+                let tags = parse_tags(tags)?;
+                let count = tags.len();
+                client.tag_resource(arn, tags).await?;
+                match output_format {
+                    OutputFormat::Table => println!("✓ Added {count} tag(s) to {arn}"),
+                    _ => print_output(
+                        serde_json::json!({ "status": "success", "resourceArn": arn, "tagsAdded": count }),
+                        output_format,
+                    )?,
+                }
+                Ok(())
+            }
+            TagSubcommands::Remove { arn, keys } => {
+                client.untag_resource(arn, keys).await?;
+                match output_format {
+                    OutputFormat::Table => println!("✓ Removed {} tag(s) from {arn}", keys.len()),
+                    _ => print_output(
+                        serde_json::json!({ "status": "success", "resourceArn": arn, "tagsRemoved": keys }),
+                        output_format,
+                    )?,
+                }
+                Ok(())
+            }
+            TagSubcommands::List { arn } => {
+                let tags = client.list_tags_for_resource(arn).await?;
+                match output_format {
+                    OutputFormat::Table => print_table(
+                        tags.into_iter()
+                            .map(|(key, value)| TagInfo { key, value })
+                            .collect(),
+                    )?,
+                    _ => print_output(&tags, output_format)?,
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser, Debug)]
+    struct TestCli {
+        #[command(subcommand)]
+        command: TagSubcommands,
+    }
+
+    const ARN: &str = "arn:aws:s3vectors:us-east-1:111122223333:bucket/docs";
+
+    #[test]
+    fn test_parse_tag_commands() {
+        match TestCli::parse_from(["test", "add", ARN, "team=search,env=prod"]).command {
+            TagSubcommands::Add { arn, tags } => {
+                assert_eq!(arn, ARN);
+                assert_eq!(tags, ["team=search", "env=prod"]);
+            }
+            _ => panic!("Expected Add command"),
+        }
+        match TestCli::parse_from(["test", "remove", ARN, "team,env"]).command {
+            TagSubcommands::Remove { keys, .. } => assert_eq!(keys, ["team", "env"]),
+            _ => panic!("Expected Remove command"),
+        }
+        assert!(TestCli::try_parse_from(["test", "add", ARN]).is_err());
+    }
+
+    #[test]
+    fn test_tag_parsing_and_filters() {
+        let tags = parse_tags(&["env=prod".to_string(), "note=".to_string()]).unwrap();
+        assert_eq!(tags["env"], "prod");
+        assert_eq!(tags["note"], "");
+        assert!(parse_tags(&["env".to_string()]).is_err());
+        assert!(parse_tags(&["env=prod".to_string(), "env=dev".to_string()]).is_err());
+
+        let filter = |s: &str| s.parse::<TagFilter>().unwrap();
+        assert!(matches_all(&[filter("env=prod"), filter("note")], &tags));
+        assert!(!filter("env=dev").matches(&tags));
+        assert!(!filter("team").matches(&tags));
+        assert!(matches_all(&[], &tags));
+        assert!("=prod".parse::<TagFilter>().is_err());
+    }
+}
@@ -1,12 +1,15 @@
 use crate::cli::output::{print_output, print_table};
-use crate::cli::OutputFormat;
+use crate::cli::resource::IndexTarget;
+use crate::cli::where_clause::parse_where;
+use crate::cli::{OutputFormat, WaitArgs};
 use crate::{
-    DeleteVectorsRequest, GetVectorsRequest, ListVectorsRequest, PutVectorsRequest, QueryVector,
-    QueryVectorsRequest, S3VectorsClient, Vector, VectorData,
+    batch_put_vectors_with, BatchPutConfig, DeleteVectorsRequest, Filter, GetVectorsRequest,
+    ListVectorsRequest, ListVectorsResponse, PaginationConfig, QueryVector, QueryVectorsRequest,
+    S3VectorsClient, Vector, VectorData,
 };
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
-use indicatif::{ProgressBar, ProgressStyle};
+use futures_util::TryStreamExt;
 use serde::Serialize;
 use std::fs;
 use tabled::Tabled;
@@ -21,14 +24,14 @@ pub struct VectorCommand {
 pub enum VectorSubcommands {
     #[command(about = "Put vectors into an index")]
     Put {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN, or an index ARN")]
         bucket: String,
 
-        #[arg(help = "Name of the index")]
-        index: String,
+        #[arg(help = "Name of the index (omit with an index ARN)")]
+        index: Option<String>,
 
         #[arg(help = "Vector key")]
-        key: String,
+        key: Option<String>,
 
         #[arg(short, long, help = "Vector data as comma-separated floats")]
         data: String,
@@ -38,15 +41,18 @@ pub enum VectorSubcommands {
 
         #[arg(short, long, help = "Batch input file (JSON array of vectors)")]
         file: Option<String>,
+
+        #[command(flatten)]
+        wait: WaitArgs,
     },
 
     #[command(about = "Get vectors by keys")]
     Get {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN, or an index ARN")]
         bucket: String,
 
-        #[arg(help = "Name of the index")]
-        index: String,
+        #[arg(help = "Name of the index (omit with an index ARN)")]
+        index: Option<String>,
 
         #[arg(help = "Vector keys to retrieve", value_delimiter = ',')]
         keys: Vec<String>,
@@ -60,11 +66,11 @@ pub enum VectorSubcommands {
 
     #[command(about = "List vectors in an index")]
     List {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN, or an index ARN")]
         bucket: String,
 
-        #[arg(help = "Name of the index")]
-        index: String,
+        #[arg(help = "Name of the index (omit with an index ARN)")]
+        index: Option<String>,
 
         #[arg(short, long, help = "Maximum number of results", default_value = "100")]
         max_results: u32,
@@ -74,15 +80,24 @@ pub enum VectorSubcommands {
 
         #[arg(long, help = "Include metadata in response")]
         include_metadata: bool,
+
+        #[arg(long, help = "Fetch all pages (--max-results sets the page size)")]
+        all: bool,
+
+        #[arg(
+            long,
+            help = "Scan the whole index with this many parallel segments (1-16); implies --all"
+        )]
+        segments: Option<u32>,
     },
 
     #[command(about = "Delete vectors by keys")]
     Delete {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN, or an index ARN")]
         bucket: String,
 
-        #[arg(help = "Name of the index")]
-        index: String,
+        #[arg(help = "Name of the index (omit with an index ARN)")]
+        index: Option<String>,
 
         #[arg(help = "Vector keys to delete", value_delimiter = ',')]
         keys: Vec<String>,
@@ -93,11 +108,11 @@ pub enum VectorSubcommands {
 
     #[command(about = "Query vectors for similarity search")]
     Query {
-        #[arg(help = "Name of the vector bucket")]
+        #[arg(help = "Vector bucket name or ARN, or an index ARN")]
         bucket: String,
 
-        #[arg(help = "Name of the index")]
-        index: String,
+        #[arg(help = "Name of the index (omit with an index ARN)")]
+        index: Option<String>,
 
         #[arg(short = 'q', long, help = "Query vector as comma-separated floats")]
         vector: String,
@@ -113,6 +128,14 @@ pub enum VectorSubcommands {
         #[arg(short, long, help = "Filter expression as JSON")]
         filter: Option<String>,
 
+        #[arg(
+            short = 'w',
+            long = "where",
+            conflicts_with = "filter",
+            help = "Filter as an expression, e.g. \"genre = 'scifi' AND year >= 2020\""
+        )]
+        where_clause: Option<String>,
+
         #[arg(long, help = "Include distance scores in response")]
         include_distance: bool,
 
@@ -128,6 +151,13 @@ struct VectorInfo {
     has_metadata: String,
 }
 
+#[derive(Serialize, Tabled)]
+struct ListedVector {
+    key: String,
+    dimensions: String,
+    metadata: String,
+}
+
 #[derive(Serialize, Tabled)]
 struct QueryResult {
     key: String,
@@ -149,15 +179,27 @@ impl VectorCommand {
                 data,
                 metadata,
                 file,
+                wait,
             } => {
+                let (target, next) = IndexTarget::parse_shifted(bucket, index.as_deref())?;
+                let key = match (next, key.clone()) {
+                    (Some(key), None) | (None, Some(key)) => key,
+                    // Batch files carry their own keys
+                    (None, None) if file.is_some() => String::new(),
+                    (None, None) => anyhow::bail!("Missing vector key"),
+                    (Some(_), Some(extra)) => anyhow::bail!(
+                        "Unexpected argument '{extra}': the index ARN already names the index"
+                    ),
+                };
                 self.put_vectors(
-                    client,
-                    bucket,
-                    index,
-                    key,
+                    &target.client(client)?,
+                    &target.bucket,
+                    &target.index,
+                    &key,
                     data,
                     metadata.as_deref(),
                     file.as_deref(),
+                    wait,
                     output_format,
                 )
                 .await
@@ -169,11 +211,12 @@ impl VectorCommand {
                 include_data,
                 include_metadata,
             } => {
+                let (target, keys) = index_and_keys(bucket, index.as_deref(), keys)?;
                 self.get_vectors(
-                    client,
-                    bucket,
-                    index,
-                    keys,
+                    &target.client(client)?,
+                    &target.bucket,
+                    &target.index,
+                    &keys,
                     *include_data,
                     *include_metadata,
                     output_format,
@@ -186,14 +229,19 @@ impl VectorCommand {
                 max_results,
                 include_data,
                 include_metadata,
+                all,
+                segments,
             } => {
+                let target = IndexTarget::parse(bucket, index.as_deref())?;
                 self.list_vectors(
-                    client,
-                    bucket,
-                    index,
+                    &target.client(client)?,
+                    &target.bucket,
+                    &target.index,
                     *max_results,
                     *include_data,
                     *include_metadata,
+                    *all,
+                    *segments,
                     output_format,
                 )
                 .await
@@ -204,8 +252,16 @@ impl VectorCommand {
                 keys,
                 force,
             } => {
-                self.delete_vectors(client, bucket, index, keys, *force, output_format)
-                    .await
+                let (target, keys) = index_and_keys(bucket, index.as_deref(), keys)?;
+                self.delete_vectors(
+                    &target.client(client)?,
+                    &target.bucket,
+                    &target.index,
+                    &keys,
+                    *force,
+                    output_format,
+                )
+                .await
             }
             VectorSubcommands::Query {
                 bucket,
@@ -213,16 +269,25 @@ impl VectorCommand {
                 vector,
                 top_k,
                 filter,
+                where_clause,
                 include_distance,
                 include_metadata,
             } => {
+                let filter = match (filter, where_clause) {
+                    (Some(json), _) => {
+                        Some(json.parse::<Filter>().context("Failed to parse filter")?)
+                    }
+                    (None, Some(expression)) => Some(parse_where(expression)?),
+                    (None, None) => None,
+                };
+                let target = IndexTarget::parse(bucket, index.as_deref())?;
                 self.query_vectors(
-                    client,
-                    bucket,
-                    index,
+                    &target.client(client)?,
+                    &target.bucket,
+                    &target.index,
                     vector,
                     *top_k,
-                    filter.as_deref(),
+                    filter,
                     *include_distance,
                     *include_metadata,
                     output_format,
@@ -242,6 +307,7 @@ impl VectorCommand {
         data: &str,
         metadata: Option<&str>,
         file: Option<&str>,
+        wait: &WaitArgs,
         output_format: OutputFormat,
     ) -> Result<()> {
         let vectors = if let Some(file_path) = file {
@@ -271,49 +337,58 @@ impl VectorCommand {
             }]
         };
 
-        let request = PutVectorsRequest {
-            vector_bucket_name: bucket.to_string(),
-            index_name: index.to_string(),
-            vectors: vectors.clone(),
-        };
-
-        if vectors.len() > 10 {
-            let pb = ProgressBar::new(vectors.len() as u64);
-            pb.set_style(
-                ProgressStyle::default_bar()
-                    .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-                    .context("Failed to set progress bar template")?
-                    .progress_chars("#>-")
-            );
-
-            // Process in batches of 500
-            for chunk in vectors.chunks(500) {
-                let batch_request = PutVectorsRequest {
-                    vector_bucket_name: bucket.to_string(),
-                    index_name: index.to_string(),
-                    vectors: chunk.to_vec(),
-                };
-                client.put_vectors(batch_request).await?;
-                pb.inc(chunk.len() as u64);
-            }
-            pb.finish_with_message("Done");
-        } else {
-            client.put_vectors(request).await?;
+        // The service checks dimensions against the index; locally, vectors
+        // only have to agree with each other
+        let dimensions = vectors.first().map_or(0, |v| v.data.float32.len() as u32);
+        let total = vectors.len();
+        let report = batch_put_vectors_with(
+            client,
+            bucket,
+            index,
+            vectors,
+            dimensions,
+            &BatchPutConfig::default(),
+        )
+        .await?;
+
+        if let Some(waiter) = wait.waiter() {
+            waiter
+                .vectors_visible(client, bucket, index, &report.succeeded)
+                .await?;
         }
 
         match output_format {
             OutputFormat::Table => {
-                println!("✓ Successfully put {} vector(s)", vectors.len());
+                println!("✓ Successfully put {} vector(s)", report.succeeded.len());
+                for failure in &report.failed {
+                    println!(
+                        "✗ Failed to put {}: {}",
+                        failure.keys.join(", "),
+                        failure.error
+                    );
+                }
             }
             _ => {
+                let failed: Vec<_> = report
+                    .failed
+                    .iter()
+                    .map(|f| serde_json::json!({ "keys": f.keys, "error": f.error.to_string() }))
+                    .collect();
                 let result = serde_json::json!({
-                    "status": "success",
-                    "vectors_added": vectors.len()
+                    "status": if report.is_complete() { "success" } else { "partial" },
+                    "vectors_added": report.succeeded.len(),
+                    "failed": failed,
                 });
                 print_output(&result, output_format)?;
             }
         }
 
+        if !report.is_complete() {
+            anyhow::bail!(
+                "{} of {total} vector(s) could not be put",
+                report.failed_keys().count()
+            );
+        }
         Ok(())
     }
 
@@ -332,11 +407,11 @@ impl VectorCommand {
             vector_bucket_name: bucket.to_string(),
             index_name: index.to_string(),
             keys: keys.to_vec(),
-            return_vector: include_data,
+            return_data: include_data,
             return_metadata: include_metadata,
         };
 
-        let response = client.get_vectors(request).await?;
+        let response = client.get_vectors_chunked(request).await?;
 
         match output_format {
             OutputFormat::Table => {
@@ -345,12 +420,15 @@ impl VectorCommand {
                     .iter()
                     .map(|v| VectorInfo {
                         key: v.key.clone(),
-                        has_data: if v.vector.is_some() { "Yes" } else { "No" }.to_string(),
+                        has_data: if v.data.is_some() { "Yes" } else { "No" }.to_string(),
                         has_metadata: if v.metadata.is_some() { "Yes" } else { "No" }.to_string(),
                     })
                     .collect();
 
                 print_table(vectors)?;
+                if !response.missing_keys.is_empty() {
+                    println!("Not found: {}", response.missing_keys.join(", "));
+                }
             }
             _ => print_output(&response, output_format)?,
         }
@@ -365,8 +443,10 @@ impl VectorCommand {
         bucket: &str,
         index: &str,
         max_results: u32,
-        _include_data: bool,
-        _include_metadata: bool,
+        include_data: bool,
+        include_metadata: bool,
+        all: bool,
+        segments: Option<u32>,
         output_format: OutputFormat,
     ) -> Result<()> {
         let request = ListVectorsRequest {
@@ -374,18 +454,62 @@ impl VectorCommand {
             index_name: index.to_string(),
             max_results: Some(max_results),
             next_token: None,
+            segment_count: None,
+            segment_index: None,
+            return_data: include_data,
+            return_metadata: include_metadata,
+        };
+        let pagination = PaginationConfig::default().page_size(max_results);
+
+        let response = if let Some(segments) = segments {
+            ListVectorsResponse {
+                vectors: client
+                    .scan_vectors(request, segments, pagination)
+                    .try_collect()
+                    .await?,
+                next_token: None,
+            }
+        } else if all {
+            ListVectorsResponse {
+                vectors: client
+                    .list_vectors_paginated(request, pagination)
+                    .try_collect()
+                    .await?,
+                next_token: None,
+            }
+        } else {
+            client.list_vectors(request).await?
         };
-
-        let response = client.list_vectors(request).await?;
 
         match output_format {
             OutputFormat::Table => {
-                println!("Found {} vectors", response.keys.len());
-                for key in &response.keys {
-                    println!("  - {key}");
+                println!("Found {} vectors", response.vectors.len());
+                if include_data || include_metadata {
+                    let vectors: Vec<ListedVector> = response
+                        .vectors
+                        .iter()
+                        .map(|v| ListedVector {
+                            key: v.key.clone(),
+                            dimensions: v
+                                .data
+                                .as_ref()
+                                .map(|d| d.float32.len().to_string())
+                                .unwrap_or_else(|| "-".to_string()),
+                            metadata: v
+                                .metadata
+                                .as_ref()
+                                .map(|m| m.to_string())
+                                .unwrap_or_else(|| "-".to_string()),
+                        })
+                        .collect();
+                    print_table(vectors)?;
+                } else {
+                    for vector in &response.vectors {
+                        println!("  - {}", vector.key);
+                    }
                 }
                 if response.next_token.is_some() {
-                    println!("\nMore results available. Use pagination token to continue.");
+                    println!("\nMore results available. Use --all to fetch every page.");
                 }
             }
             _ => print_output(&response, output_format)?,
@@ -425,21 +549,41 @@ impl VectorCommand {
             keys: keys.to_vec(),
         };
 
-        client.delete_vectors(request).await?;
+        let report = client.delete_vectors_chunked(request).await?;
 
         match output_format {
             OutputFormat::Table => {
-                println!("✓ Successfully deleted {} vector(s)", keys.len());
+                println!("✓ Successfully deleted {} vector(s)", report.deleted.len());
+                for failure in &report.failed {
+                    println!(
+                        "✗ Failed to delete {}: {}",
+                        failure.keys.join(", "),
+                        failure.error
+                    );
+                }
             }
             _ => {
+                let failed: Vec<_> = report
+                    .failed
+                    .iter()
+                    .map(|f| serde_json::json!({ "keys": f.keys, "error": f.error.to_string() }))
+                    .collect();
                 let result = serde_json::json!({
-                    "status": "success",
-                    "vectors_deleted": keys.len()
+                    "status": if report.is_complete() { "success" } else { "partial" },
+                    "vectors_deleted": report.deleted.len(),
+                    "failed": failed,
                 });
                 print_output(&result, output_format)?;
             }
         }
 
+        if !report.is_complete() {
+            anyhow::bail!(
+                "{} of {} vector(s) could not be deleted",
+                report.failed_keys().count(),
+                keys.len()
+            );
+        }
         Ok(())
     }
 
@@ -451,7 +595,7 @@ impl VectorCommand {
         index: &str,
         vector: &str,
         top_k: u32,
-        filter: Option<&str>,
+        filter: Option<Filter>,
         include_distance: bool,
         include_metadata: bool,
         output_format: OutputFormat,
@@ -462,12 +606,6 @@ impl VectorCommand {
             .collect::<Result<Vec<f32>, _>>()
             .context("Failed to parse query vector")?;
 
-        let filter_value = if let Some(f) = filter {
-            Some(serde_json::from_str(f).context("Failed to parse filter")?)
-        } else {
-            None
-        };
-
         let request = QueryVectorsRequest {
             vector_bucket_name: bucket.to_string(),
             index_name: index.to_string(),
@@ -475,7 +613,7 @@ impl VectorCommand {
                 float32: float_data,
             },
             top_k,
-            filter: filter_value,
+            filter,
             return_metadata: include_metadata,
             return_distance: include_distance,
         };
@@ -510,6 +648,22 @@ impl VectorCommand {
     }
 }
 
+/// The index and vector keys of `get` and `delete`; after an index ARN, the
+/// argument in the index position is the first list of keys
+fn index_and_keys(
+    bucket: &str,
+    index: Option<&str>,
+    keys: &[String],
+) -> Result<(IndexTarget, Vec<String>)> {
+    let (target, first) = IndexTarget::parse_shifted(bucket, index)?;
+    let keys = first
+        .iter()
+        .flat_map(|first| first.split(',').map(String::from))
+        .chain(keys.iter().cloned())
+        .collect();
+    Ok((target, keys))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -543,8 +697,8 @@ mod tests {
                 ..
             } => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(index, "my-index");
-                assert_eq!(key, "key1");
+                assert_eq!(index.as_deref(), Some("my-index"));
+                assert_eq!(key.as_deref(), Some("key1"));
                 assert_eq!(data, "0.1,0.2,0.3");
             }
             _ => panic!("Expected Put command"),
@@ -564,7 +718,7 @@ mod tests {
                 ..
             } => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(index, "my-index");
+                assert_eq!(index.as_deref(), Some("my-index"));
                 assert_eq!(keys, vec!["key1", "key2"]);
             }
             _ => panic!("Expected Get command"),
@@ -594,7 +748,7 @@ mod tests {
                 ..
             } => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(index, "my-index");
+                assert_eq!(index.as_deref(), Some("my-index"));
                 assert_eq!(vector, "0.1,0.2,0.3");
                 assert_eq!(top_k, 10);
             }
@@ -622,11 +776,170 @@ mod tests {
                 force,
             } => {
                 assert_eq!(bucket, "my-bucket");
-                assert_eq!(index, "my-index");
+                assert_eq!(index.as_deref(), Some("my-index"));
                 assert_eq!(keys, vec!["key1", "key2"]);
                 assert!(force);
             }
             _ => panic!("Expected Delete command"),
         }
     }
+
+    #[test]
+    fn test_parse_list_vectors_command() {
+        let cli = TestCli::parse_from([
+            "test",
+            "list",
+            "my-bucket",
+            "my-index",
+            "--all",
+            "--include-metadata",
+            "--segments",
+            "4",
+        ]);
+
+        match cli.command {
+            VectorSubcommands::List {
+                bucket,
+                max_results,
+                include_data,
+                include_metadata,
+                all,
+                segments,
+                ..
+            } => {
+                assert_eq!(bucket, "my-bucket");
+                assert_eq!(max_results, 100);
+                assert!(!include_data);
+                assert!(include_metadata);
+                assert!(all);
+                assert_eq!(segments, Some(4));
+            }
+            _ => panic!("Expected List command"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_put_reports_vectors_that_failed() {
+        let emulator = crate::emulator::Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+        client.create_vector_bucket("docs").await.unwrap();
+        client
+            .create_index(crate::CreateIndexRequest {
+                vector_bucket_name: "docs".to_string(),
+                index_name: "emb".to_string(),
+                data_type: crate::DataType::Float32,
+                dimension: 2,
+                distance_metric: crate::DistanceMetric::Euclidean,
+                metadata_configuration: None,
+            })
+            .await
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batch.json");
+        let mut vectors: Vec<_> = (0..11)
+            .map(|i| serde_json::json!({"key": format!("key-{i}"), "data": {"float32": [i, 0.0]}}))
+            .collect();
+        vectors.push(serde_json::json!({"key": "odd", "data": {"float32": [1.0, 2.0, 3.0]}}));
+        fs::write(&path, serde_json::to_string(&vectors).unwrap()).unwrap();
+
+        let cli = TestCli::parse_from([
+            "test",
+            "put",
+            "docs",
+            "emb",
+            "-f",
+            path.to_str().unwrap(),
+            "-d",
+            "0",
+        ]);
+        let err = VectorCommand {
+            command: cli.command,
+        }
+        .execute(&client, OutputFormat::Json)
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "1 of 12 vector(s) could not be put");
+
+        let written = client
+            .get_vectors_chunked(GetVectorsRequest {
+                vector_bucket_name: "docs".to_string(),
+                index_name: "emb".to_string(),
+                keys: vec!["key-0".into(), "key-10".into(), "odd".into()],
+                return_data: false,
+                return_metadata: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(written.vectors.len(), 2);
+        assert_eq!(written.missing_keys, ["odd"]);
+    }
+
+    #[test]
+    fn test_index_arn_replaces_bucket_and_index() {
+        const INDEX: &str = "arn:aws:s3vectors:eu-west-1:111122223333:bucket/docs/index/emb";
+
+        let cli = TestCli::parse_from(["test", "get", INDEX, "key1,key2", "key3"]);
+        let VectorSubcommands::Get {
+            bucket,
+            index,
+            keys,
+            ..
+        } = cli.command
+        else {
+            panic!("Expected Get command");
+        };
+        let (target, keys) = index_and_keys(&bucket, index.as_deref(), &keys).unwrap();
+        assert_eq!(
+            (target.bucket.as_str(), target.index.as_str()),
+            ("docs", "emb")
+        );
+        assert_eq!(target.region.as_deref(), Some("eu-west-1"));
+        assert_eq!(keys, ["key1", "key2", "key3"]);
+
+        // With names, nothing moves
+        let (target, keys) = index_and_keys("docs", Some("emb"), &["key1".into()]).unwrap();
+        assert_eq!(target.index, "emb");
+        assert_eq!(keys, ["key1"]);
+
+        let cli = TestCli::parse_from(["test", "query", INDEX, "-q", "0.1,0.2"]);
+        assert!(matches!(
+            cli.command,
+            VectorSubcommands::Query { index: None, .. }
+        ));
+
+        // A batch file needs no argument after the ARN at all
+        let cli = TestCli::parse_from(["test", "put", INDEX, "--file", "batch.json", "-d", "3"]);
+        let VectorSubcommands::Put {
+            bucket,
+            index,
+            key,
+            file,
+            ..
+        } = cli.command
+        else {
+            panic!("Expected Put command");
+        };
+        assert!(index.is_none() && key.is_none());
+        assert_eq!(file.as_deref(), Some("batch.json"));
+        let (target, next) = IndexTarget::parse_shifted(&bucket, index.as_deref()).unwrap();
+        assert_eq!(target.index, "emb");
+        assert!(next.is_none());
+
+        // Without an index ARN the index name is still required
+        let cli = TestCli::parse_from(["test", "delete", "docs"]);
+        let VectorSubcommands::Delete {
+            bucket,
+            index,
+            keys,
+            ..
+        } = cli.command
+        else {
+            panic!("Expected Delete command");
+        };
+        assert!(index_and_keys(&bucket, index.as_deref(), &keys)
+            .unwrap_err()
+            .to_string()
+            .contains("Missing index name"));
+    }
 }
@@ -0,0 +1,463 @@
+//! `--where` expressions: a small SQL-like language compiled to a [`Filter`].
+//!
+//! ```text
+//! genre = 'scifi' AND year >= 2020 AND lang IN ('en', 'ko')
+//! (draft IS NULL OR draft = false) AND tags != 'nsfw'
+//! ```
+//!
+//! Comparisons are `=`, `!=` (or `<>`), `<`, `<=`, `>`, `>=`, `[NOT] IN (...)`
+//! and `IS [NOT] NULL` for `$exists`. Conditions combine with `AND`, `OR` and
+//! parentheses; `AND` binds tighter. Keywords are case-insensitive, strings
+//! use single or double quotes (doubled to escape), and keys with unusual
+//! characters can be written in backticks.
+
+use std::fmt;
+
+use crate::filter::{Filter, FilterValue};
+
+/// A parse error pointing at a column of the expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhereError {
+    pub input: String,
+    /// 1-based character column of the problem
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for WhereError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Invalid --where expression at column {}: {}",
+            self.column, self.message
+        )?;
+        writeln!(f, "  {}", self.input)?;
+        write!(f, "  {}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl std::error::Error for WhereError {}
+
+/// Compile a `--where` expression to a filter
+pub fn parse_where(input: &str) -> Result<Filter, WhereError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        input,
+        tokens,
+        pos: 0,
+    };
+    let filter = parser.or_expr()?;
+    match parser.peek() {
+        None => Ok(filter),
+        Some(token) => Err(parser.error_at(
+            token.column,
+            format!(
+                "expected AND, OR or end of expression, found {}",
+                token.kind
+            ),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    /// A keyword-looking bare word, kept upper-cased
+    Word(String),
+    Str(String),
+    Number(f64),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Ident(name) => write!(f, "`{name}`"),
+            TokenKind::Word(word) => write!(f, "{word}"),
+            TokenKind::Str(s) => write!(f, "'{s}'"),
+            TokenKind::Number(n) => write!(f, "{n}"),
+            TokenKind::Op(op) => write!(f, "'{op}'"),
+            TokenKind::LParen => write!(f, "'('"),
+            TokenKind::RParen => write!(f, "')'"),
+            TokenKind::Comma => write!(f, "','"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+const KEYWORDS: &[&str] = &["AND", "OR", "NOT", "IN", "IS", "NULL", "TRUE", "FALSE"];
+
+fn tokenize(input: &str) -> Result<Vec<Token>, WhereError> {
+    let error = |column: usize, message: String| WhereError {
+        input: input.to_string(),
+        column,
+        message,
+    };
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = match c {
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            ',' => TokenKind::Comma,
+            '=' => {
+                // Accept `==` as well
+                if chars.get(i + 1) == Some(&'=') {
+                    i += 1;
+                }
+                TokenKind::Op("=")
+            }
+            '!' | '<' | '>' => {
+                let next = chars.get(i + 1).copied();
+                let op = match (c, next) {
+                    ('!', Some('=')) | ('<', Some('>')) => "!=",
+                    ('<', Some('=')) => "<=",
+                    ('>', Some('=')) => ">=",
+                    ('<', _) => "<",
+                    ('>', _) => ">",
+                    _ => return Err(error(column, "expected '!='".to_string())),
+                };
+                if op.len() == 2 {
+                    i += 1;
+                }
+                TokenKind::Op(op)
+            }
+            '\'' | '"' | '`' => {
+                // Quoted string or backtick identifier; a doubled quote escapes
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(error(
+                                column,
+                                format!("unterminated text starting with {c}"),
+                            ));
+                        }
+                        Some(&q) if q == c && chars.get(i + 1) == Some(&c) => {
+                            value.push(c);
+                            i += 2;
+                        }
+                        Some(&q) if q == c => break,
+                        Some(&other) => {
+                            value.push(other);
+                            i += 1;
+                        }
+                    }
+                }
+                if c == '`' {
+                    TokenKind::Ident(value)
+                } else {
+                    TokenKind::Str(value)
+                }
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let start = i;
+                while i + 1 < chars.len()
+                    && (chars[i + 1].is_ascii_alphanumeric()
+                        || chars[i + 1] == '.'
+                        || (matches!(chars[i + 1], '-' | '+') && matches!(chars[i], 'e' | 'E')))
+                {
+                    i += 1;
+                }
+                let text: String = chars[start..=i].iter().collect();
+                let number = text
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|n| n.is_finite())
+                    .ok_or_else(|| error(column, format!("invalid number '{text}'")))?;
+                TokenKind::Number(number)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i + 1 < chars.len()
+                    && (chars[i + 1].is_alphanumeric() || matches!(chars[i + 1], '_' | '.' | '-'))
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..=i].iter().collect();
+                let upper = word.to_ascii_uppercase();
+                if KEYWORDS.contains(&upper.as_str()) {
+                    TokenKind::Word(upper)
+                } else {
+                    TokenKind::Ident(word)
+                }
+            }
+            other => return Err(error(column, format!("unexpected character '{other}'"))),
+        };
+        tokens.push(Token { kind, column });
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn error_at(&self, column: usize, message: impl Into<String>) -> WhereError {
+        WhereError {
+            input: self.input.to_string(),
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Error about the current token, or the end of the input
+    fn unexpected(&self, expected: &str) -> WhereError {
+        match self.peek() {
+            Some(token) => self.error_at(
+                token.column,
+                format!("expected {expected}, found {}", token.kind),
+            ),
+            None => self.error_at(
+                self.input.chars().count() + 1,
+                format!("expected {expected}, found end of expression"),
+            ),
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Some(Token { kind: TokenKind::Word(w), .. }) if w == word) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> Result<(), WhereError> {
+        if self.peek().map(|t| &t.kind) == Some(&kind) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected(expected))
+        }
+    }
+
+    fn or_expr(&mut self) -> Result<Filter, WhereError> {
+        let mut filters = vec![self.and_expr()?];
+        while self.eat_word("OR") {
+            filters.push(self.and_expr()?);
+        }
+        Ok(combine(filters, Filter::Or))
+    }
+
+    fn and_expr(&mut self) -> Result<Filter, WhereError> {
+        let mut filters = vec![self.primary()?];
+        while self.eat_word("AND") {
+            filters.push(self.primary()?);
+        }
+        Ok(combine(filters, Filter::And))
+    }
+
+    fn primary(&mut self) -> Result<Filter, WhereError> {
+        if self.peek().map(|t| &t.kind) == Some(&TokenKind::LParen) {
+            self.pos += 1;
+            let filter = self.or_expr()?;
+            self.expect(TokenKind::RParen, "')'")?;
+            return Ok(filter);
+        }
+        self.predicate()
+    }
+
+    fn predicate(&mut self) -> Result<Filter, WhereError> {
+        let key = match self.peek() {
+            Some(Token {
+                kind: TokenKind::Ident(key),
+                ..
+            }) => key.clone(),
+            _ => return Err(self.unexpected("a metadata key")),
+        };
+        self.pos += 1;
+
+        if self.eat_word("IS") {
+            let negated = self.eat_word("NOT");
+            if !self.eat_word("NULL") {
+                return Err(self.unexpected("NULL"));
+            }
+            return Ok(Filter::exists(key, negated));
+        }
+        if self.eat_word("NOT") {
+            if !self.eat_word("IN") {
+                return Err(self.unexpected("IN"));
+            }
+            return Ok(Filter::not_in(key, self.value_list()?));
+        }
+        if self.eat_word("IN") {
+            return Ok(Filter::is_in(key, self.value_list()?));
+        }
+
+        let op = match self.peek() {
+            Some(Token {
+                kind: TokenKind::Op(op),
+                ..
+            }) => *op,
+            _ => return Err(self.unexpected("a comparison operator")),
+        };
+        self.pos += 1;
+
+        if matches!(op, "=" | "!=") {
+            let value = self.value()?;
+            return Ok(if op == "=" {
+                Filter::eq(key, value)
+            } else {
+                Filter::ne(key, value)
+            });
+        }
+        let number = match self.peek() {
+            Some(Token {
+                kind: TokenKind::Number(n),
+                ..
+            }) => *n,
+            _ => return Err(self.unexpected(&format!("a number after '{op}'"))),
+        };
+        self.pos += 1;
+        Ok(match op {
+            "<" => Filter::lt(key, number),
+            "<=" => Filter::lte(key, number),
+            ">" => Filter::gt(key, number),
+            _ => Filter::gte(key, number),
+        })
+    }
+
+    fn value(&mut self) -> Result<FilterValue, WhereError> {
+        let value = match self.peek().map(|t| &t.kind) {
+            Some(TokenKind::Str(s)) => FilterValue::String(s.clone()),
+            Some(TokenKind::Number(n)) => FilterValue::Number(*n),
+            Some(TokenKind::Word(w)) if w == "TRUE" => FilterValue::Bool(true),
+            Some(TokenKind::Word(w)) if w == "FALSE" => FilterValue::Bool(false),
+            Some(TokenKind::Ident(name)) => {
+                let message =
+                    format!("expected a value, found `{name}`; quote strings as '{name}'");
+                return Err(self.error_at(self.peek().unwrap().column, message));
+            }
+            _ => return Err(self.unexpected("a value")),
+        };
+        self.pos += 1;
+        Ok(value)
+    }
+
+    fn value_list(&mut self) -> Result<Vec<FilterValue>, WhereError> {
+        self.expect(TokenKind::LParen, "'(' to start a value list")?;
+        let mut values = vec![self.value()?];
+        loop {
+            match self.next().map(|t| t.kind) {
+                Some(TokenKind::Comma) => values.push(self.value()?),
+                Some(TokenKind::RParen) => return Ok(values),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.unexpected("',' or ')'"));
+                }
+            }
+        }
+    }
+}
+
+fn combine(mut filters: Vec<Filter>, combinator: fn(Vec<Filter>) -> Filter) -> Filter {
+    if filters.len() == 1 {
+        filters.remove(0)
+    } else {
+        combinator(filters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_where_compiles_to_filter_json() {
+        let filter =
+            parse_where("genre = 'scifi' AND year >= 2020 AND lang IN ('en','ko')").unwrap();
+        assert_eq!(
+            filter.to_json(),
+            json!({"$and": [
+                {"genre": {"$eq": "scifi"}},
+                {"year": {"$gte": 2020}},
+                {"lang": {"$in": ["en", "ko"]}},
+            ]})
+        );
+
+        let filter = parse_where(
+            "(draft is null or draft == false) and `rating.avg` > 4.5 and tags not in (\"it's\", 'o''k') and lang <> 'de'",
+        )
+        .unwrap();
+        assert_eq!(
+            filter.to_json(),
+            json!({"$and": [
+                {"$or": [{"draft": {"$exists": false}}, {"draft": {"$eq": false}}]},
+                {"rating.avg": {"$gt": 4.5}},
+                {"tags": {"$nin": ["it's", "o'k"]}},
+                {"lang": {"$ne": "de"}},
+            ]})
+        );
+
+        // AND binds tighter than OR
+        let filter = parse_where("a = 1 OR b = 2 AND c IS NOT NULL").unwrap();
+        assert_eq!(
+            filter.to_json(),
+            json!({"$or": [
+                {"a": {"$eq": 1}},
+                {"$and": [{"b": {"$eq": 2}}, {"c": {"$exists": true}}]},
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_parse_where_errors_point_at_column() {
+        let cases = [
+            ("genre = scifi", 9, "quote strings"),
+            ("year >= 'x'", 9, "a number"),
+            ("year >=", 8, "end of expression"),
+            ("genre = 'scifi' year > 1", 17, "expected AND, OR"),
+            ("lang IN ('en' 'ko')", 15, "',' or ')'"),
+            ("(a = 1", 7, "')'"),
+            ("a = 'open", 5, "unterminated"),
+            ("a ~ 1", 3, "unexpected character"),
+            ("= 1", 1, "a metadata key"),
+        ];
+        for (input, column, message) in cases {
+            let err = parse_where(input).unwrap_err();
+            assert_eq!(err.column, column, "{input}: {err}");
+            assert!(err.message.contains(message), "{input}: {err}");
+        }
+
+        let rendered = parse_where("year >= 'x'").unwrap_err().to_string();
+        assert!(
+            rendered.ends_with("  year >= 'x'\n          ^"),
+            "{rendered}"
+        );
+    }
+}
@@ -11,6 +11,8 @@ pub struct Config {
     pub aws_access_key_id: Option<String>,
     pub aws_secret_access_key: Option<String>,
     pub aws_session_token: Option<String>,
+    pub aws_endpoint_url_s3vectors: Option<String>,
+    pub aws_endpoint_url: Option<String>,
 }
 
 impl fmt::Debug for Config {
@@ -32,6 +34,11 @@ impl fmt::Debug for Config {
                 "aws_session_token",
                 &self.aws_session_token.as_ref().map(|_| "***REDACTED***"),
             )
+            .field(
+                "aws_endpoint_url_s3vectors",
+                &self.aws_endpoint_url_s3vectors,
+            )
+            .field("aws_endpoint_url", &self.aws_endpoint_url)
             .finish()
     }
 }
@@ -40,6 +47,13 @@ impl Config {
     pub fn has_credentials(&self) -> bool {
         self.aws_access_key_id.is_some() && self.aws_secret_access_key.is_some()
     }
+
+    /// Endpoint override, preferring the service-specific variable over the global one
+    pub fn endpoint_url(&self) -> Option<&str> {
+        self.aws_endpoint_url_s3vectors
+            .as_deref()
+            .or(self.aws_endpoint_url.as_deref())
+    }
 }
 
 fn default_region() -> String {
@@ -61,6 +75,8 @@ pub fn get_config() -> Config {
             aws_access_key_id: None,
             aws_secret_access_key: None,
             aws_session_token: None,
+            aws_endpoint_url_s3vectors: None,
+            aws_endpoint_url: None,
         },
     }
 }
@@ -76,6 +92,8 @@ mod tests {
             aws_access_key_id: Some("AKIAXXXXXXXX".to_string()),
             aws_secret_access_key: Some("secret123".to_string()),
             aws_session_token: Some("token456".to_string()),
+            aws_endpoint_url_s3vectors: None,
+            aws_endpoint_url: None,
         };
 
         let debug_output = format!("{:?}", config);
@@ -125,4 +143,20 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_endpoint_url_prefers_service_specific_variable() {
+        let mut config = Config {
+            aws_region: "us-east-1".to_string(),
+            aws_access_key_id: None,
+            aws_secret_access_key: None,
+            aws_session_token: None,
+            aws_endpoint_url_s3vectors: None,
+            aws_endpoint_url: Some("http://localhost:4566".to_string()),
+        };
+        assert_eq!(config.endpoint_url(), Some("http://localhost:4566"));
+
+        config.aws_endpoint_url_s3vectors = Some("http://localhost:8080".to_string());
+        assert_eq!(config.endpoint_url(), Some("http://localhost:8080"));
+    }
 }
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use anyhow::Context;
+
+use super::{sts, Credentials, CredentialsError, CredentialsProvider, Env, ProvideCredentials};
+use crate::auth::AwsV4Signer;
+
+type TokenCodeFn = dyn Fn(&str) -> anyhow::Result<String> + Send + Sync;
+
+/// Supplies the current MFA code for a device serial number, e.g. by
+/// prompting on the terminal. It is called on a blocking thread, so it may
+/// block.
+#[derive(Clone)]
+pub struct MfaTokenProvider(Arc<TokenCodeFn>);
+
+impl MfaTokenProvider {
+    pub fn new(provider: impl Fn(&str) -> anyhow::Result<String> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+
+    /// Ask for a code on a blocking thread: providers usually read from the
+    /// terminal, and this runs while the credentials cache is locked
+    async fn token_code(&self, serial_number: &str) -> anyhow::Result<String> {
+        let provider = self.0.clone();
+        let serial_number = serial_number.to_string();
+        tokio::task::spawn_blocking(move || provider(&serial_number)).await?
+    }
+}
+
+impl fmt::Debug for MfaTokenProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MfaTokenProvider")
+    }
+}
+
+/// Role credentials from `sts:AssumeRole`, signed with credentials from a
+/// source provider.
+///
+/// Every call assumes the role again; wrap the provider in a
+/// [`CachingProvider`](super::CachingProvider) (the client builder does) to
+/// reuse the session until it expires and to avoid repeated MFA prompts.
+#[derive(Debug)]
+pub struct AssumeRoleProvider {
+    role_arn: String,
+    session_name: String,
+    external_id: Option<String>,
+    mfa_serial: Option<String>,
+    mfa_token: Option<MfaTokenProvider>,
+    duration_seconds: Option<u32>,
+    source: Arc<dyn CredentialsProvider>,
+    region: String,
+    endpoint: String,
+    http: reqwest::Client,
+}
+
+impl AssumeRoleProvider {
+    pub fn new(role_arn: &str, source: Arc<dyn CredentialsProvider>, region: &str) -> Self {
+        Self::with_env(&Env::default(), role_arn, source, region)
+    }
+
+    pub(crate) fn with_env(
+        env: &Env,
+        role_arn: &str,
+        source: Arc<dyn CredentialsProvider>,
+        region: &str,
+    ) -> Self {
+        Self {
+            role_arn: role_arn.to_string(),
+            session_name: format!("s3-vectors-{}", chrono::Utc::now().timestamp()),
+            external_id: None,
+            mfa_serial: None,
+            mfa_token: None,
+            duration_seconds: None,
+            source,
+            region: region.to_string(),
+            endpoint: sts::endpoint(env, region),
+            http: crate::HTTP_CLIENT.clone(),
+        }
+    }
+
+    pub fn session_name(mut self, session_name: &str) -> Self {
+        self.session_name = session_name.to_string();
+        self
+    }
+
+    /// External ID required by the role's trust policy
+    pub fn external_id(mut self, external_id: &str) -> Self {
+        self.external_id = Some(external_id.to_string());
+        self
+    }
+
+    /// MFA device whose code is requested from `token` on each AssumeRole call
+    pub fn mfa(mut self, serial_number: &str, token: MfaTokenProvider) -> Self {
+        self.mfa_serial = Some(serial_number.to_string());
+        self.mfa_token = Some(token);
+        self
+    }
+
+    /// Requested session lifetime (STS default: one hour)
+    pub fn duration_seconds(mut self, seconds: u32) -> Self {
+        self.duration_seconds = Some(seconds);
+        self
+    }
+
+    async fn fetch(&self) -> Result<Credentials, CredentialsError> {
+        let source = self.source.provide_credentials().await?;
+        let mfa = match &self.mfa_serial {
+            Some(serial_number) => {
+                let token = self
+                    .mfa_token
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("MFA code required for {serial_number}"))?
+                    .token_code(serial_number)
+                    .await?;
+                Some((serial_number, token))
+            }
+            None => None,
+        };
+
+        // The serializer is not Send, so keep it out of scope across awaits
+        let body = {
+            let mut form = url::form_urlencoded::Serializer::new(String::new());
+            form.append_pair("Action", "AssumeRole")
+                .append_pair("Version", "2011-06-15")
+                .append_pair("RoleArn", &self.role_arn)
+                .append_pair("RoleSessionName", &self.session_name);
+            if let Some(external_id) = &self.external_id {
+                form.append_pair("ExternalId", external_id);
+            }
+            if let Some((serial_number, token)) = &mfa {
+                form.append_pair("SerialNumber", serial_number)
+                    .append_pair("TokenCode", token.trim());
+            }
+            if let Some(seconds) = self.duration_seconds {
+                form.append_pair("DurationSeconds", &seconds.to_string());
+            }
+            form.finish()
+        };
+
+        let url = format!("{}/", self.endpoint);
+        let signer =
+            AwsV4Signer::with_provider(Arc::new(source), self.region.clone()).with_service("sts");
+        let headers = signer
+            .sign_request(
+                "POST",
+                &url,
+                HashMap::from([(
+                    "content-type".to_string(),
+                    "application/x-www-form-urlencoded; charset=utf-8".to_string(),
+                )]),
+                body.as_bytes(),
+            )
+            .await?;
+
+        let mut request = self.http.post(&url).body(body);
+        for (key, value) in headers {
+            request = request.header(key, value);
+        }
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to call STS AssumeRole for {}", self.role_arn))?;
+
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .await
+            .context("Failed to read STS response")?;
+        sts::parse_response(status, &body)
+    }
+}
+
+impl CredentialsProvider for AssumeRoleProvider {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move {
+            let credentials = self.fetch().await?;
+            tracing::debug!("Assumed role {}", self.role_arn);
+            Ok(credentials)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::CachingProvider;
+    use crate::test_support::{MockResponse, MockServer};
+
+    fn assume_role_response(expiration: &str) -> String {
+        format!(
+            "<AssumeRoleResponse><AssumeRoleResult><Credentials>\
+             <AccessKeyId>ASIAROLE</AccessKeyId><SecretAccessKey>role-secret</SecretAccessKey>\
+             <SessionToken>role-session</SessionToken><Expiration>{expiration}</Expiration>\
+             </Credentials></AssumeRoleResult></AssumeRoleResponse>"
+        )
+    }
+
+    fn mock_sts_env(sts: &MockServer) -> Env {
+        Env::from_vars(&[("AWS_ENDPOINT_URL_STS", &sts.url())])
+    }
+
+    #[tokio::test]
+    async fn test_assume_role_signs_request_for_sts() {
+        let expiration = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+        let sts =
+            MockServer::start(move |_| MockResponse::new(200, assume_role_response(&expiration)))
+                .await;
+
+        let source = Arc::new(Credentials::new("AKIDSOURCE", "source-secret", None));
+        let provider = AssumeRoleProvider::with_env(
+            &mock_sts_env(&sts),
+            "arn:aws:iam::111122223333:role/vectors-reader",
+            source,
+            "us-west-2",
+        )
+        .session_name("nightly-ingest")
+        .external_id("shared-ml")
+        .mfa(
+            "arn:aws:iam::444455556666:mfa/dev",
+            MfaTokenProvider::new(|_| Ok("123456".to_string())),
+        );
+
+        let credentials = provider.provide_credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "ASIAROLE");
+        assert_eq!(credentials.session_token(), Some("role-session"));
+
+        let request = &sts.requests()[0];
+        let authorization = request.header("authorization").unwrap();
+        assert!(authorization.contains("Credential=AKIDSOURCE/"));
+        assert!(authorization.contains("/us-west-2/sts/aws4_request"));
+        assert!(authorization.contains("content-type"));
+
+        let body = request.body_str();
+        assert!(body.contains("Action=AssumeRole&"));
+        assert!(body.contains("RoleArn=arn%3Aaws%3Aiam%3A%3A111122223333%3Arole%2Fvectors-reader"));
+        assert!(body.contains("RoleSessionName=nightly-ingest"));
+        assert!(body.contains("ExternalId=shared-ml"));
+        assert!(body.contains("TokenCode=123456"));
+    }
+
+    #[tokio::test]
+    async fn test_assume_role_credentials_cached_until_expiry() {
+        let expiration = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+        let sts =
+            MockServer::start(move |_| MockResponse::new(200, assume_role_response(&expiration)))
+                .await;
+
+        let provider = CachingProvider::new(Arc::new(AssumeRoleProvider::with_env(
+            &mock_sts_env(&sts),
+            "arn:aws:iam::111122223333:role/vectors-reader",
+            Arc::new(Credentials::new("AKIDSOURCE", "source-secret", None)),
+            "us-east-1",
+        )));
+        for _ in 0..3 {
+            provider.provide_credentials().await.unwrap();
+        }
+        assert_eq!(sts.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_assume_role_surfaces_sts_errors() {
+        let sts = MockServer::start(|_| {
+            MockResponse::new(
+                403,
+                "<ErrorResponse><Error><Code>AccessDenied</Code>\
+                 <Message>not trusted</Message></Error></ErrorResponse>",
+            )
+        })
+        .await;
+
+        let provider = AssumeRoleProvider::with_env(
+            &mock_sts_env(&sts),
+            "arn:aws:iam::111122223333:role/vectors-reader",
+            Arc::new(Credentials::new("AKIDSOURCE", "source-secret", None)),
+            "us-east-1",
+        );
+        let err = provider.provide_credentials().await.unwrap_err();
+        assert!(err.to_string().contains("AccessDenied: not trusted"));
+
+        // MFA configured on the role but no way to obtain a code
+        let mut missing_code = AssumeRoleProvider::with_env(
+            &mock_sts_env(&sts),
+            "arn:aws:iam::111122223333:role/vectors-reader",
+            Arc::new(Credentials::new("AKIDSOURCE", "source-secret", None)),
+            "us-east-1",
+        );
+        missing_code.mfa_serial = Some("arn:aws:iam::444455556666:mfa/dev".to_string());
+        let err = missing_code.provide_credentials().await.unwrap_err();
+        assert!(err.to_string().contains("MFA code required"));
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use super::{
+    ContainerProvider, CredentialsError, CredentialsProvider, Env, EnvironmentProvider,
+    ImdsProvider, ProfileProvider, ProvideCredentials, WebIdentityProvider,
+};
+
+/// The standard AWS lookup order:
+///
+/// 1. `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY`
+/// 2. the profile named by `AWS_PROFILE` (or `default`) in the shared
+///    credentials and config files
+/// 3. `AWS_WEB_IDENTITY_TOKEN_FILE` + `AWS_ROLE_ARN`
+/// 4. the ECS / EKS container credentials endpoint
+/// 5. EC2 instance metadata (IMDSv2)
+///
+/// Sources that are not configured are skipped; the first configured source
+/// wins, and its errors are returned rather than masked by later sources.
+#[derive(Debug)]
+pub struct DefaultCredentialsChain {
+    providers: Vec<(&'static str, Arc<dyn CredentialsProvider>)>,
+}
+
+impl DefaultCredentialsChain {
+    /// Chain for `region`, used for regional STS calls
+    pub fn new(region: &str) -> anyhow::Result<Self> {
+        Self::with_env(Env::default(), region)
+    }
+
+    pub(crate) fn with_env(env: Env, region: &str) -> anyhow::Result<Self> {
+        let profile = env
+            .get("AWS_PROFILE")
+            .unwrap_or_else(|| "default".to_string());
+
+        let mut providers: Vec<(&'static str, Arc<dyn CredentialsProvider>)> = vec![
+            (
+                "environment",
+                Arc::new(EnvironmentProvider::with_env(env.clone())),
+            ),
+            (
+                "profile",
+                Arc::new(ProfileProvider::with_env(&profile, env.clone())),
+            ),
+        ];
+        if let Some(provider) = WebIdentityProvider::from_env(&env, region) {
+            providers.push(("web identity", Arc::new(provider)));
+        }
+        if let Some(provider) = ContainerProvider::with_env(env.clone())? {
+            providers.push(("container", Arc::new(provider)));
+        }
+        providers.push(("instance metadata", Arc::new(ImdsProvider::with_env(&env))));
+
+        Ok(Self { providers })
+    }
+}
+
+impl CredentialsProvider for DefaultCredentialsChain {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move {
+            let mut skipped = Vec::new();
+            for (name, provider) in &self.providers {
+                match provider.provide_credentials().await {
+                    Ok(credentials) => {
+                        tracing::debug!("Loaded credentials from {} provider", name);
+                        return Ok(credentials);
+                    }
+                    Err(CredentialsError::NotLoaded(reason)) => {
+                        tracing::debug!("Skipping {} credentials: {}", name, reason);
+                        skipped.push(format!("{name}: {reason}"));
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            Err(CredentialsError::NotLoaded(format!(
+                "No credentials found ({})",
+                skipped.join("; ")
+            )))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    fn container_server_response() -> MockResponse {
+        MockResponse::json(
+            200,
+            serde_json::json!({
+                "AccessKeyId": "ASIACONTAINER",
+                "SecretAccessKey": "secret",
+                "Token": "session",
+                "Expiration": "2030-01-01T00:00:00Z",
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_chain_skips_unconfigured_sources() {
+        let server = MockServer::start(|_| container_server_response()).await;
+        let home = tempfile::tempdir().unwrap();
+        let full_uri = format!("{}/creds", server.url());
+
+        let env = Env::from_vars(&[
+            ("HOME", home.path().to_str().unwrap()),
+            ("AWS_CONTAINER_CREDENTIALS_FULL_URI", &full_uri),
+        ]);
+        let chain = DefaultCredentialsChain::with_env(env, "us-east-1").unwrap();
+        let credentials = chain.provide_credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "ASIACONTAINER");
+
+        // Environment credentials take precedence over the container endpoint
+        let env = Env::from_vars(&[
+            ("HOME", home.path().to_str().unwrap()),
+            ("AWS_ACCESS_KEY_ID", "AKIDENV"),
+            ("AWS_SECRET_ACCESS_KEY", "secret"),
+            ("AWS_CONTAINER_CREDENTIALS_FULL_URI", &full_uri),
+        ]);
+        let chain = DefaultCredentialsChain::with_env(env, "us-east-1").unwrap();
+        let credentials = chain.provide_credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "AKIDENV");
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chain_reports_when_nothing_is_configured() {
+        let home = tempfile::tempdir().unwrap();
+        let env = Env::from_vars(&[
+            ("HOME", home.path().to_str().unwrap()),
+            ("AWS_EC2_METADATA_DISABLED", "true"),
+        ]);
+        let chain = DefaultCredentialsChain::with_env(env, "us-east-1").unwrap();
+
+        match chain.provide_credentials().await {
+            Err(CredentialsError::NotLoaded(reason)) => {
+                assert!(reason.contains("environment"));
+                assert!(reason.contains("Profile 'default' not found"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::Context;
+
+use super::{
+    CredentialsDocument, CredentialsError, CredentialsProvider, Env, ProvideCredentials,
+    METADATA_HTTP_CLIENT,
+};
+
+/// Base for `AWS_CONTAINER_CREDENTIALS_RELATIVE_URI` (ECS task roles)
+const ECS_ENDPOINT: &str = "http://169.254.170.2";
+
+/// Container credentials endpoint used by ECS task roles and EKS Pod Identity
+#[derive(Debug)]
+pub struct ContainerProvider {
+    uri: String,
+    env: Env,
+}
+
+impl ContainerProvider {
+    /// Configured through `AWS_CONTAINER_CREDENTIALS_RELATIVE_URI` or
+    /// `AWS_CONTAINER_CREDENTIALS_FULL_URI`; `None` when neither is set
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        Self::with_env(Env::default())
+    }
+
+    pub(crate) fn with_env(env: Env) -> anyhow::Result<Option<Self>> {
+        let uri = if let Some(relative) = env.get("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") {
+            format!("{ECS_ENDPOINT}{relative}")
+        } else if let Some(full) = env.get("AWS_CONTAINER_CREDENTIALS_FULL_URI") {
+            validate_full_uri(&full)?;
+            full
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self { uri, env }))
+    }
+
+    /// Authorization header value, preferring the token file (refreshed by
+    /// the agent) over the static variable
+    fn authorization(&self) -> anyhow::Result<Option<String>> {
+        if let Some(path) = self.env.get("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE") {
+            let token = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read container authorization token: {path}"))?;
+            return Ok(Some(token.trim().to_string()));
+        }
+        Ok(self.env.get("AWS_CONTAINER_AUTHORIZATION_TOKEN"))
+    }
+
+    async fn fetch(&self) -> anyhow::Result<CredentialsDocument> {
+        let mut request = METADATA_HTTP_CLIENT.get(&self.uri);
+        if let Some(token) = self.authorization()? {
+            request = request.header("Authorization", token);
+        }
+
+        let response = request.send().await.with_context(|| {
+            format!(
+                "Failed to reach container credentials endpoint {}",
+                self.uri
+            )
+        })?;
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!(
+                "Container credentials endpoint returned {status}: {}",
+                response.text().await.unwrap_or_default()
+            );
+        }
+
+        response
+            .json()
+            .await
+            .context("Invalid container credentials response")
+    }
+}
+
+/// Full URIs may only point at HTTPS or at loopback / the container agent
+/// addresses, so that credentials never travel in cleartext off-host
+fn validate_full_uri(uri: &str) -> anyhow::Result<()> {
+    let parsed = url::Url::parse(uri)
+        .with_context(|| format!("Invalid AWS_CONTAINER_CREDENTIALS_FULL_URI: {uri}"))?;
+    if parsed.scheme() == "https" {
+        return Ok(());
+    }
+
+    let allowed = match parsed.host() {
+        Some(url::Host::Ipv4(ip)) => {
+            ip.is_loopback()
+                || ip.octets() == [169, 254, 170, 2]
+                || ip.octets() == [169, 254, 170, 23]
+        }
+        Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+        Some(url::Host::Domain(domain)) => domain == "localhost",
+        None => false,
+    };
+    if !allowed {
+        anyhow::bail!(
+            "AWS_CONTAINER_CREDENTIALS_FULL_URI must use https or a loopback address: {uri}"
+        );
+    }
+    Ok(())
+}
+
+impl CredentialsProvider for ContainerProvider {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move {
+            self.fetch()
+                .await
+                .map_err(CredentialsError::from)?
+                .into_credentials()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn test_container_provider_sends_authorization_token() {
+        let server = MockServer::start(|request| {
+            if request.header("authorization") != Some("secret-token") {
+                return MockResponse::new(401, "unauthorized");
+            }
+            MockResponse::json(
+                200,
+                serde_json::json!({
+                    "AccessKeyId": "ASIACONTAINER",
+                    "SecretAccessKey": "secret",
+                    "Token": "session",
+                    "Expiration": "2030-01-01T00:00:00Z",
+                }),
+            )
+        })
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("token");
+        std::fs::write(&token_file, "secret-token\n").unwrap();
+
+        let env = Env::from_vars(&[
+            (
+                "AWS_CONTAINER_CREDENTIALS_FULL_URI",
+                &format!("{}/v2/credentials", server.url()),
+            ),
+            (
+                "AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE",
+                token_file.to_str().unwrap(),
+            ),
+        ]);
+        let provider = ContainerProvider::with_env(env).unwrap().unwrap();
+        let credentials = provider.provide_credentials().await.unwrap();
+
+        assert_eq!(credentials.access_key_id(), "ASIACONTAINER");
+        assert_eq!(credentials.session_token(), Some("session"));
+        assert_eq!(server.requests()[0].path, "/v2/credentials");
+    }
+
+    #[test]
+    fn test_container_provider_rejects_remote_http_uri() {
+        let env = Env::from_vars(&[(
+            "AWS_CONTAINER_CREDENTIALS_FULL_URI",
+            "http://example.com/creds",
+        )]);
+        assert!(ContainerProvider::with_env(env).is_err());
+
+        let env = Env::from_vars(&[(
+            "AWS_CONTAINER_CREDENTIALS_RELATIVE_URI",
+            "/v2/credentials/abc",
+        )]);
+        let provider = ContainerProvider::with_env(env).unwrap().unwrap();
+        assert_eq!(provider.uri, "http://169.254.170.2/v2/credentials/abc");
+
+        assert!(ContainerProvider::with_env(Env::from_vars(&[]))
+            .unwrap()
+            .is_none());
+    }
+}
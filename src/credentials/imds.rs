@@ -0,0 +1,203 @@
+use anyhow::Context;
+use serde::Deserialize;
+
+use super::{
+    CredentialsDocument, CredentialsError, CredentialsProvider, Env, ProvideCredentials,
+    METADATA_HTTP_CLIENT,
+};
+
+const DEFAULT_ENDPOINT: &str = "http://169.254.169.254";
+const TOKEN_TTL_SECONDS: &str = "21600";
+
+/// EC2 instance profile credentials from the instance metadata service (IMDSv2)
+#[derive(Debug)]
+pub struct ImdsProvider {
+    endpoint: String,
+    disabled: bool,
+}
+
+#[derive(Deserialize)]
+struct ImdsCredentials {
+    #[serde(rename = "Code")]
+    code: String,
+    #[serde(flatten)]
+    credentials: CredentialsDocument,
+}
+
+impl Default for ImdsProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ImdsProvider {
+    /// Honours `AWS_EC2_METADATA_SERVICE_ENDPOINT` and `AWS_EC2_METADATA_DISABLED`
+    pub fn new() -> Self {
+        Self::with_env(&Env::default())
+    }
+
+    pub(crate) fn with_env(env: &Env) -> Self {
+        Self {
+            endpoint: env
+                .get("AWS_EC2_METADATA_SERVICE_ENDPOINT")
+                .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string())
+                .trim_end_matches('/')
+                .to_string(),
+            disabled: env
+                .get("AWS_EC2_METADATA_DISABLED")
+                .is_some_and(|v| v.eq_ignore_ascii_case("true")),
+        }
+    }
+
+    async fn session_token(&self) -> Result<String, CredentialsError> {
+        let response = METADATA_HTTP_CLIENT
+            .put(format!("{}/latest/api/token", self.endpoint))
+            .header("X-aws-ec2-metadata-token-ttl-seconds", TOKEN_TTL_SECONDS)
+            .send()
+            .await
+            // Nothing listening: we are not on EC2
+            .map_err(|e| CredentialsError::NotLoaded(format!("IMDS unavailable: {e}")))?;
+
+        if !response.status().is_success() {
+            return Err(CredentialsError::NotLoaded(format!(
+                "IMDS token request returned {}",
+                response.status()
+            )));
+        }
+        Ok(response.text().await.context("Failed to read IMDS token")?)
+    }
+
+    async fn get(&self, token: &str, path: &str) -> Result<reqwest::Response, CredentialsError> {
+        let response = METADATA_HTTP_CLIENT
+            .get(format!("{}{path}", self.endpoint))
+            .header("X-aws-ec2-metadata-token", token)
+            .send()
+            .await
+            .with_context(|| format!("IMDS request to {path} failed"))?;
+
+        match response.status().as_u16() {
+            200 => Ok(response),
+            404 => Err(CredentialsError::NotLoaded(
+                "No instance profile attached to this instance".to_string(),
+            )),
+            status => Err(anyhow::anyhow!("IMDS request to {path} returned {status}").into()),
+        }
+    }
+
+    async fn fetch(&self) -> Result<super::Credentials, CredentialsError> {
+        const CREDENTIALS_PATH: &str = "/latest/meta-data/iam/security-credentials/";
+
+        let token = self.session_token().await?;
+        let role = self
+            .get(&token, CREDENTIALS_PATH)
+            .await?
+            .text()
+            .await
+            .context("Failed to read IMDS role name")?;
+        let role = role.lines().next().unwrap_or_default().trim();
+        if role.is_empty() {
+            return Err(CredentialsError::NotLoaded(
+                "No instance profile attached to this instance".to_string(),
+            ));
+        }
+
+        let document: ImdsCredentials = self
+            .get(&token, &format!("{CREDENTIALS_PATH}{role}"))
+            .await?
+            .json()
+            .await
+            .context("Invalid IMDS credentials response")?;
+        if document.code != "Success" {
+            return Err(
+                anyhow::anyhow!("IMDS returned credentials with code {}", document.code).into(),
+            );
+        }
+        document.credentials.into_credentials()
+    }
+}
+
+impl CredentialsProvider for ImdsProvider {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move {
+            if self.disabled {
+                return Err(CredentialsError::NotLoaded(
+                    "AWS_EC2_METADATA_DISABLED is set".to_string(),
+                ));
+            }
+            self.fetch().await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn test_imds_provider_uses_session_token() {
+        let server =
+            MockServer::start(
+                |request| match (request.method.as_str(), request.path.as_str()) {
+                    ("PUT", "/latest/api/token") => MockResponse::new(200, "imds-token"),
+                    _ if request.header("x-aws-ec2-metadata-token") != Some("imds-token") => {
+                        MockResponse::new(401, "")
+                    }
+                    ("GET", "/latest/meta-data/iam/security-credentials/") => {
+                        MockResponse::new(200, "ml-instance-role")
+                    }
+                    ("GET", "/latest/meta-data/iam/security-credentials/ml-instance-role") => {
+                        MockResponse::json(
+                            200,
+                            serde_json::json!({
+                                "Code": "Success",
+                                "Type": "AWS-HMAC",
+                                "AccessKeyId": "ASIAIMDS",
+                                "SecretAccessKey": "secret",
+                                "Token": "session",
+                                "Expiration": "2030-01-01T00:00:00Z",
+                            }),
+                        )
+                    }
+                    _ => MockResponse::new(404, ""),
+                },
+            )
+            .await;
+
+        let env = Env::from_vars(&[("AWS_EC2_METADATA_SERVICE_ENDPOINT", &server.url())]);
+        let credentials = ImdsProvider::with_env(&env)
+            .provide_credentials()
+            .await
+            .unwrap();
+
+        assert_eq!(credentials.access_key_id(), "ASIAIMDS");
+        assert_eq!(credentials.session_token(), Some("session"));
+        assert_eq!(
+            server.requests()[0].header("x-aws-ec2-metadata-token-ttl-seconds"),
+            Some("21600")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_imds_provider_not_loaded_without_instance_profile() {
+        let server = MockServer::start(|request| match request.method.as_str() {
+            "PUT" => MockResponse::new(200, "imds-token"),
+            _ => MockResponse::new(404, ""),
+        })
+        .await;
+
+        let env = Env::from_vars(&[("AWS_EC2_METADATA_SERVICE_ENDPOINT", &server.url())]);
+        assert!(matches!(
+            ImdsProvider::with_env(&env).provide_credentials().await,
+            Err(CredentialsError::NotLoaded(_))
+        ));
+
+        let disabled = Env::from_vars(&[("AWS_EC2_METADATA_DISABLED", "true")]);
+        assert!(matches!(
+            ImdsProvider::with_env(&disabled)
+                .provide_credentials()
+                .await,
+            Err(CredentialsError::NotLoaded(_))
+        ));
+    }
+}
@@ -0,0 +1,369 @@
+//! AWS credential providers.
+//!
+//! A [`CredentialsProvider`] hands out [`Credentials`] on demand. The signer asks
+//! its provider before every request, so temporary credentials are refreshed
+//! transparently; wrap expensive providers in a [`CachingProvider`] to avoid
+//! hitting the source each time.
+
+mod assume_role;
+mod chain;
+mod container;
+mod imds;
+mod process;
+mod profile;
+mod sts;
+mod web_identity;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, LazyLock};
+
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+
+pub use assume_role::{AssumeRoleProvider, MfaTokenProvider};
+pub use chain::DefaultCredentialsChain;
+pub use container::ContainerProvider;
+pub use imds::ImdsProvider;
+pub use process::ProcessProvider;
+pub use profile::{profile_region, ProfileProvider};
+pub use web_identity::WebIdentityProvider;
+
+/// Refresh credentials this long before they expire
+const REFRESH_WINDOW: chrono::Duration = chrono::Duration::minutes(5);
+
+/// HTTP client for link-local metadata endpoints: short timeouts so that the
+/// chain moves on quickly when not running on AWS compute, and never proxied
+static METADATA_HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(1))
+        .timeout(std::time::Duration::from_secs(5))
+        .no_proxy()
+        .build()
+        .unwrap_or_default()
+});
+
+/// A set of AWS credentials, optionally expiring
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    expiration: Option<DateTime<Utc>>,
+}
+
+impl Credentials {
+    pub fn new(
+        access_key_id: impl Into<String>,
+        secret_access_key: impl Into<String>,
+        session_token: Option<String>,
+    ) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token,
+            expiration: None,
+        }
+    }
+
+    pub fn with_expiration(mut self, expiration: DateTime<Utc>) -> Self {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    pub fn access_key_id(&self) -> &str {
+        &self.access_key_id
+    }
+
+    pub fn secret_access_key(&self) -> &str {
+        &self.secret_access_key
+    }
+
+    pub fn session_token(&self) -> Option<&str> {
+        self.session_token.as_deref()
+    }
+
+    pub fn expiration(&self) -> Option<DateTime<Utc>> {
+        self.expiration
+    }
+
+    /// Whether these credentials expire before `now + window`
+    pub fn expires_within(&self, now: DateTime<Utc>, window: chrono::Duration) -> bool {
+        self.expiration
+            .is_some_and(|expiration| expiration <= now + window)
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"***REDACTED***")
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| "***REDACTED***"),
+            )
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CredentialsError {
+    /// The source is not configured here; a chain should try the next one
+    #[error("Credentials not loaded: {0}")]
+    NotLoaded(String),
+
+    /// The source is configured but failed to produce credentials
+    #[error(transparent)]
+    ProviderError(#[from] anyhow::Error),
+}
+
+pub type ProvideCredentials<'a> = BoxFuture<'a, Result<Credentials, CredentialsError>>;
+
+/// Source of AWS credentials
+pub trait CredentialsProvider: fmt::Debug + Send + Sync {
+    fn provide_credentials(&self) -> ProvideCredentials<'_>;
+}
+
+/// Fixed credentials are their own provider
+impl CredentialsProvider for Credentials {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move { Ok(self.clone()) })
+    }
+}
+
+impl<P: CredentialsProvider + ?Sized> CredentialsProvider for Arc<P> {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        (**self).provide_credentials()
+    }
+}
+
+/// Reads `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN`
+#[derive(Debug, Default)]
+pub struct EnvironmentProvider {
+    env: Env,
+}
+
+impl EnvironmentProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn with_env(env: Env) -> Self {
+        Self { env }
+    }
+}
+
+impl CredentialsProvider for EnvironmentProvider {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move {
+            match (
+                self.env.get("AWS_ACCESS_KEY_ID"),
+                self.env.get("AWS_SECRET_ACCESS_KEY"),
+            ) {
+                (Some(access_key_id), Some(secret_access_key)) => Ok(Credentials::new(
+                    access_key_id,
+                    secret_access_key,
+                    self.env.get("AWS_SESSION_TOKEN"),
+                )),
+                _ => Err(CredentialsError::NotLoaded(
+                    "AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY are not set".to_string(),
+                )),
+            }
+        })
+    }
+}
+
+/// Caches credentials from another provider until shortly before they expire
+#[derive(Debug)]
+pub struct CachingProvider {
+    inner: Arc<dyn CredentialsProvider>,
+    cached: tokio::sync::Mutex<Option<Credentials>>,
+}
+
+impl CachingProvider {
+    pub fn new(inner: Arc<dyn CredentialsProvider>) -> Self {
+        Self {
+            inner,
+            cached: tokio::sync::Mutex::new(None),
+        }
+    }
+}
+
+impl CredentialsProvider for CachingProvider {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move {
+            // Holding the lock across the refresh makes concurrent requests
+            // wait for a single fetch instead of stampeding the source
+            let mut cached = self.cached.lock().await;
+            if let Some(credentials) = cached.as_ref() {
+                if !credentials.expires_within(Utc::now(), REFRESH_WINDOW) {
+                    return Ok(credentials.clone());
+                }
+            }
+
+            let credentials = self.inner.provide_credentials().await?;
+            *cached = Some(credentials.clone());
+            Ok(credentials)
+        })
+    }
+}
+
+/// Environment lookup that tests can replace with a fixed set of variables
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Env {
+    vars: Option<Arc<HashMap<String, String>>>,
+}
+
+impl Env {
+    #[cfg(test)]
+    pub(crate) fn from_vars(vars: &[(&str, &str)]) -> Self {
+        Self {
+            vars: Some(Arc::new(
+                vars.iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Value of a variable, treating empty values as unset
+    pub(crate) fn get(&self, key: &str) -> Option<String> {
+        let value = match &self.vars {
+            Some(vars) => vars.get(key).cloned(),
+            None => std::env::var(key).ok(),
+        };
+        value.filter(|v| !v.is_empty())
+    }
+
+    /// Home directory, overridable with `HOME` in test environments
+    pub(crate) fn home_dir(&self) -> Option<std::path::PathBuf> {
+        match &self.vars {
+            Some(_) => self.get("HOME").map(Into::into),
+            None => dirs::home_dir(),
+        }
+    }
+}
+
+/// JSON credential document shared by `credential_process`, the container
+/// endpoint and IMDS (the latter two call the session token `Token`)
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct CredentialsDocument {
+    access_key_id: String,
+    secret_access_key: String,
+    #[serde(alias = "Token")]
+    session_token: Option<String>,
+    expiration: Option<String>,
+}
+
+impl CredentialsDocument {
+    fn into_credentials(self) -> Result<Credentials, CredentialsError> {
+        let credentials = Credentials::new(
+            self.access_key_id,
+            self.secret_access_key,
+            self.session_token,
+        );
+        match self.expiration {
+            Some(expiration) => Ok(credentials.with_expiration(parse_timestamp(&expiration)?)),
+            None => Ok(credentials),
+        }
+    }
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, CredentialsError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|e| anyhow::anyhow!("Invalid credential expiration '{value}': {e}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Hands out numbered credentials that expire after `lifetime`
+    #[derive(Debug)]
+    struct CountingProvider {
+        calls: AtomicUsize,
+        lifetime: chrono::Duration,
+    }
+
+    impl CredentialsProvider for CountingProvider {
+        fn provide_credentials(&self) -> ProvideCredentials<'_> {
+            Box::pin(async move {
+                let n = self.calls.fetch_add(1, Ordering::SeqCst);
+                Ok(Credentials::new(format!("AKID{n}"), "secret", None)
+                    .with_expiration(Utc::now() + self.lifetime))
+            })
+        }
+    }
+
+    #[test]
+    fn test_credentials_debug_redacts_secrets() {
+        let credentials = Credentials::new("AKIDEXAMPLE", "supersecret", Some("token".into()));
+        let debug = format!("{credentials:?}");
+        assert!(debug.contains("AKIDEXAMPLE"));
+        assert!(!debug.contains("supersecret"));
+        assert!(!debug.contains("\"token\""));
+    }
+
+    #[tokio::test]
+    async fn test_environment_provider() {
+        let provider = EnvironmentProvider::with_env(Env::from_vars(&[
+            ("AWS_ACCESS_KEY_ID", "AKIDENV"),
+            ("AWS_SECRET_ACCESS_KEY", "secret"),
+            ("AWS_SESSION_TOKEN", "token"),
+        ]));
+        let credentials = provider.provide_credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "AKIDENV");
+        assert_eq!(credentials.session_token(), Some("token"));
+
+        let empty = EnvironmentProvider::with_env(Env::from_vars(&[("AWS_ACCESS_KEY_ID", "")]));
+        assert!(matches!(
+            empty.provide_credentials().await,
+            Err(CredentialsError::NotLoaded(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_caching_provider_refreshes_expiring_credentials() {
+        let long_lived = CachingProvider::new(Arc::new(CountingProvider {
+            calls: AtomicUsize::new(0),
+            lifetime: chrono::Duration::hours(1),
+        }));
+        assert_eq!(
+            long_lived
+                .provide_credentials()
+                .await
+                .unwrap()
+                .access_key_id(),
+            "AKID0"
+        );
+        assert_eq!(
+            long_lived
+                .provide_credentials()
+                .await
+                .unwrap()
+                .access_key_id(),
+            "AKID0"
+        );
+
+        // Inside the refresh window every call fetches fresh credentials
+        let short_lived = CachingProvider::new(Arc::new(CountingProvider {
+            calls: AtomicUsize::new(0),
+            lifetime: chrono::Duration::minutes(1),
+        }));
+        short_lived.provide_credentials().await.unwrap();
+        assert_eq!(
+            short_lived
+                .provide_credentials()
+                .await
+                .unwrap()
+                .access_key_id(),
+            "AKID1"
+        );
+    }
+}
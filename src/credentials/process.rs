@@ -0,0 +1,105 @@
+use anyhow::Context;
+use serde::Deserialize;
+
+use super::{CredentialsDocument, CredentialsError, CredentialsProvider, ProvideCredentials};
+
+/// Runs a `credential_process` command and parses its JSON output
+#[derive(Debug)]
+pub struct ProcessProvider {
+    command: String,
+}
+
+#[derive(Deserialize)]
+struct ProcessOutput {
+    #[serde(rename = "Version")]
+    version: u32,
+    #[serde(flatten)]
+    credentials: CredentialsDocument,
+}
+
+impl ProcessProvider {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+        }
+    }
+
+    async fn run(&self) -> anyhow::Result<ProcessOutput> {
+        let mut command = if cfg!(windows) {
+            let mut command = tokio::process::Command::new("cmd");
+            command.arg("/C");
+            command
+        } else {
+            let mut command = tokio::process::Command::new("sh");
+            command.arg("-c");
+            command
+        };
+
+        let output = command
+            .arg(&self.command)
+            .output()
+            .await
+            .with_context(|| format!("Failed to run credential_process: {}", self.command))?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "credential_process exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        let parsed: ProcessOutput = serde_json::from_slice(&output.stdout)
+            .context("credential_process printed invalid JSON")?;
+        if parsed.version != 1 {
+            anyhow::bail!(
+                "Unsupported credential_process output version: {}",
+                parsed.version
+            );
+        }
+        Ok(parsed)
+    }
+}
+
+impl CredentialsProvider for ProcessProvider {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move {
+            self.run()
+                .await
+                .map_err(CredentialsError::from)?
+                .credentials
+                .into_credentials()
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_process_provider_parses_output() {
+        let provider = ProcessProvider::new(
+            r#"echo '{"Version": 1, "AccessKeyId": "AKIDPROC", "SecretAccessKey": "secret", "SessionToken": "token", "Expiration": "2030-01-01T00:00:00Z"}'"#,
+        );
+        let credentials = provider.provide_credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "AKIDPROC");
+        assert_eq!(credentials.session_token(), Some("token"));
+        assert!(credentials.expiration().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_process_provider_reports_failures() {
+        let err = ProcessProvider::new("echo denied >&2; exit 3")
+            .provide_credentials()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("denied"));
+
+        let err = ProcessProvider::new(r#"echo '{"Version": 2}'"#)
+            .provide_credentials()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CredentialsError::ProviderError(_)));
+    }
+}
@@ -0,0 +1,534 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+
+use super::{
+    AssumeRoleProvider, ContainerProvider, Credentials, CredentialsError, CredentialsProvider, Env,
+    EnvironmentProvider, ImdsProvider, MfaTokenProvider, ProcessProvider, ProvideCredentials,
+    WebIdentityProvider,
+};
+
+type Properties = HashMap<String, String>;
+
+/// Profiles merged from the shared config and credentials files
+#[derive(Debug, Default)]
+pub(crate) struct ProfileSet {
+    profiles: HashMap<String, Properties>,
+}
+
+impl ProfileSet {
+    /// Load `~/.aws/config` and `~/.aws/credentials` (or `AWS_CONFIG_FILE` /
+    /// `AWS_SHARED_CREDENTIALS_FILE`); missing files are treated as empty.
+    /// Values from the credentials file win over the config file.
+    pub(crate) fn load(env: &Env) -> anyhow::Result<Self> {
+        let mut set = Self::default();
+        let home = env.home_dir();
+
+        let config_path = env
+            .get("AWS_CONFIG_FILE")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|h| h.join(".aws/config")));
+        let credentials_path = env
+            .get("AWS_SHARED_CREDENTIALS_FILE")
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|h| h.join(".aws/credentials")));
+
+        for (path, is_config) in [(config_path, true), (credentials_path, false)] {
+            let Some(path) = path.filter(|p| p.exists()) else {
+                continue;
+            };
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            set.parse(&contents, is_config);
+        }
+
+        Ok(set)
+    }
+
+    fn parse(&mut self, contents: &str, is_config: bool) {
+        let mut current: Option<String> = None;
+
+        for line in contents.lines() {
+            // Indented lines continue a nested property (e.g. `s3 =`), which
+            // no credential source uses
+            if line.starts_with([' ', '\t']) {
+                continue;
+            }
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let section = section.trim();
+                current = if !is_config {
+                    Some(section.to_string())
+                } else if section == "default" {
+                    Some("default".to_string())
+                } else {
+                    // Other config sections (sso-session, services) are not profiles
+                    section
+                        .strip_prefix("profile ")
+                        .map(|name| name.trim().to_string())
+                };
+                if let Some(name) = &current {
+                    self.profiles.entry(name.clone()).or_default();
+                }
+            } else if let (Some(name), Some((key, value))) = (&current, line.split_once('=')) {
+                self.profiles
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&Properties> {
+        self.profiles.get(name)
+    }
+}
+
+/// Where a profile's credentials come from
+#[derive(Debug)]
+pub(crate) enum ProfileSource {
+    Static(Credentials),
+    Process(String),
+    WebIdentity {
+        token_file: String,
+        role_arn: String,
+        session_name: Option<String>,
+    },
+    /// `credential_source` of a role profile: `Environment`,
+    /// `Ec2InstanceMetadata` or `EcsContainer`
+    CredentialSource(String),
+    AssumeRole {
+        role_arn: String,
+        external_id: Option<String>,
+        mfa_serial: Option<String>,
+        session_name: Option<String>,
+        duration_seconds: Option<u32>,
+        source: Box<ProfileSource>,
+    },
+}
+
+impl ProfileSet {
+    /// Resolve a profile, following `source_profile` links
+    pub(crate) fn resolve(&self, name: &str) -> Result<ProfileSource, CredentialsError> {
+        self.resolve_inner(name, &mut Vec::new())
+    }
+
+    fn resolve_inner(
+        &self,
+        name: &str,
+        visited: &mut Vec<String>,
+    ) -> Result<ProfileSource, CredentialsError> {
+        let properties = self
+            .get(name)
+            .ok_or_else(|| CredentialsError::NotLoaded(format!("Profile '{name}' not found")))?;
+
+        if visited.iter().any(|v| v == name) {
+            visited.push(name.to_string());
+            return Err(anyhow::anyhow!(
+                "Profile source_profile chain has a cycle: {}",
+                visited.join(" -> ")
+            )
+            .into());
+        }
+        visited.push(name.to_string());
+
+        let static_credentials = || match (
+            properties.get("aws_access_key_id"),
+            properties.get("aws_secret_access_key"),
+        ) {
+            (Some(access_key_id), Some(secret_access_key)) => {
+                Some(ProfileSource::Static(Credentials::new(
+                    access_key_id,
+                    secret_access_key,
+                    properties.get("aws_session_token").cloned(),
+                )))
+            }
+            _ => None,
+        };
+
+        if let Some(role_arn) = properties.get("role_arn") {
+            if let Some(token_file) = properties.get("web_identity_token_file") {
+                return Ok(ProfileSource::WebIdentity {
+                    token_file: token_file.clone(),
+                    role_arn: role_arn.clone(),
+                    session_name: properties.get("role_session_name").cloned(),
+                });
+            }
+
+            let source = match (
+                properties.get("source_profile"),
+                properties.get("credential_source"),
+            ) {
+                // A profile may name itself as the source to use its own keys
+                (Some(source_profile), None) if source_profile == name => static_credentials()
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Profile '{name}' is its own source_profile but has no keys"
+                        )
+                    })?,
+                (Some(source_profile), None) => self
+                    .resolve_inner(source_profile, visited)
+                    .map_err(|e| match e {
+                        CredentialsError::NotLoaded(msg) => anyhow::anyhow!(
+                            "source_profile '{source_profile}' of profile '{name}': {msg}"
+                        )
+                        .into(),
+                        other => other,
+                    })?,
+                (None, Some(credential_source)) => {
+                    ProfileSource::CredentialSource(credential_source.clone())
+                }
+                (Some(_), Some(_)) => {
+                    return Err(anyhow::anyhow!(
+                        "Profile '{name}' sets both source_profile and credential_source"
+                    )
+                    .into())
+                }
+                (None, None) => {
+                    return Err(anyhow::anyhow!(
+                        "Profile '{name}' sets role_arn without source_profile or credential_source"
+                    )
+                    .into())
+                }
+            };
+
+            let duration_seconds = properties
+                .get("duration_seconds")
+                .map(|v| v.parse::<u32>())
+                .transpose()
+                .with_context(|| format!("Profile '{name}' has an invalid duration_seconds"))?;
+
+            return Ok(ProfileSource::AssumeRole {
+                role_arn: role_arn.clone(),
+                external_id: properties.get("external_id").cloned(),
+                mfa_serial: properties.get("mfa_serial").cloned(),
+                session_name: properties.get("role_session_name").cloned(),
+                duration_seconds,
+                source: Box::new(source),
+            });
+        }
+
+        if let Some(command) = properties.get("credential_process") {
+            return Ok(ProfileSource::Process(command.clone()));
+        }
+
+        static_credentials().ok_or_else(|| {
+            CredentialsError::NotLoaded(format!("Profile '{name}' defines no credentials"))
+        })
+    }
+}
+
+/// Region configured for a profile in the shared config files
+pub fn profile_region(profile_name: &str) -> Option<String> {
+    ProfileSet::load(&Env::default())
+        .ok()?
+        .get(profile_name)?
+        .get("region")
+        .cloned()
+}
+
+/// Credentials from a named profile in the shared config and credentials files.
+///
+/// The files are re-read on every call, so edits (e.g. `aws sso login` or a
+/// rotated key) are picked up without restarting.
+#[derive(Debug)]
+pub struct ProfileProvider {
+    name: String,
+    env: Env,
+    mfa_token: Option<MfaTokenProvider>,
+}
+
+impl ProfileProvider {
+    /// Use the named profile, failing early if it does not exist or cannot
+    /// produce credentials
+    pub fn new(profile_name: &str) -> anyhow::Result<Self> {
+        let provider = Self::with_env(profile_name, Env::default());
+        provider
+            .source()
+            .with_context(|| format!("Failed to load profile: {profile_name}"))?;
+        Ok(provider)
+    }
+
+    /// Lazily resolved profile; a missing profile is reported as not loaded
+    pub(crate) fn with_env(profile_name: &str, env: Env) -> Self {
+        Self {
+            name: profile_name.to_string(),
+            env,
+            mfa_token: None,
+        }
+    }
+
+    /// Source of MFA codes for role profiles that set `mfa_serial`
+    pub fn mfa_token_provider(mut self, provider: MfaTokenProvider) -> Self {
+        self.mfa_token = Some(provider);
+        self
+    }
+
+    fn source(&self) -> Result<ProfileSource, CredentialsError> {
+        ProfileSet::load(&self.env)?.resolve(&self.name)
+    }
+
+    fn region(&self) -> String {
+        ProfileSet::load(&self.env)
+            .ok()
+            .and_then(|set| set.get(&self.name)?.get("region").cloned())
+            .or_else(|| self.env.get("AWS_REGION"))
+            .unwrap_or_else(|| "us-east-1".to_string())
+    }
+
+    /// Turn a resolved profile into the provider that produces its credentials
+    fn build(
+        &self,
+        source: ProfileSource,
+    ) -> Result<Arc<dyn CredentialsProvider>, CredentialsError> {
+        Ok(match source {
+            ProfileSource::Static(credentials) => Arc::new(credentials),
+            ProfileSource::Process(command) => Arc::new(ProcessProvider::new(&command)),
+            ProfileSource::WebIdentity {
+                token_file,
+                role_arn,
+                session_name,
+            } => {
+                let mut provider = WebIdentityProvider::with_env(
+                    &self.env,
+                    token_file.into(),
+                    &role_arn,
+                    &self.region(),
+                );
+                if let Some(session_name) = session_name {
+                    provider = provider.session_name(&session_name);
+                }
+                Arc::new(provider)
+            }
+            ProfileSource::CredentialSource(name) => match name.as_str() {
+                "Environment" => Arc::new(EnvironmentProvider::with_env(self.env.clone())),
+                "Ec2InstanceMetadata" => Arc::new(ImdsProvider::with_env(&self.env)),
+                "EcsContainer" => Arc::new(
+                    ContainerProvider::with_env(self.env.clone())?.ok_or_else(|| {
+                        anyhow::anyhow!("credential_source EcsContainer but no container endpoint is configured")
+                    })?,
+                ),
+                other => {
+                    return Err(anyhow::anyhow!("Unsupported credential_source: {other}").into())
+                }
+            },
+            ProfileSource::AssumeRole {
+                role_arn,
+                external_id,
+                mfa_serial,
+                session_name,
+                duration_seconds,
+                source,
+            } => {
+                let mut provider = AssumeRoleProvider::with_env(
+                    &self.env,
+                    &role_arn,
+                    self.build(*source)?,
+                    &self.region(),
+                );
+                if let Some(external_id) = external_id {
+                    provider = provider.external_id(&external_id);
+                }
+                if let Some(session_name) = session_name {
+                    provider = provider.session_name(&session_name);
+                }
+                if let Some(seconds) = duration_seconds {
+                    provider = provider.duration_seconds(seconds);
+                }
+                if let Some(serial_number) = mfa_serial {
+                    let token = self.mfa_token.clone().ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Profile '{}' requires an MFA code for {serial_number}, but no MFA prompt is available",
+                            self.name
+                        )
+                    })?;
+                    provider = provider.mfa(&serial_number, token);
+                }
+                Arc::new(provider)
+            }
+        })
+    }
+}
+
+impl CredentialsProvider for ProfileProvider {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move {
+            let provider = self.build(self.source()?)?;
+            provider.provide_credentials().await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+[default]
+region = us-west-2
+
+[profile ml]
+region = eu-west-1
+credential_process = echo unused
+
+[profile cross-account]
+role_arn = arn:aws:iam::123456789012:role/vectors
+source_profile = ml
+external_id = shared-ml
+
+[profile loop-a]
+role_arn = arn:aws:iam::123456789012:role/a
+source_profile = loop-b
+
+[profile loop-b]
+role_arn = arn:aws:iam::123456789012:role/b
+source_profile = loop-a
+
+[sso-session corp]
+sso_region = us-east-1
+";
+
+    const CREDENTIALS: &str = "\
+# comment
+[default]
+aws_access_key_id = AKIDDEFAULT
+aws_secret_access_key = secret
+
+[ml]
+aws_access_key_id = AKIDML
+aws_secret_access_key = secret
+";
+
+    fn profile_env(dir: &tempfile::TempDir) -> Env {
+        let config = dir.path().join("config");
+        let credentials = dir.path().join("credentials");
+        std::fs::write(&config, CONFIG).unwrap();
+        std::fs::write(&credentials, CREDENTIALS).unwrap();
+        Env::from_vars(&[
+            ("AWS_CONFIG_FILE", config.to_str().unwrap()),
+            ("AWS_SHARED_CREDENTIALS_FILE", credentials.to_str().unwrap()),
+        ])
+    }
+
+    #[test]
+    fn test_profile_set_merges_config_and_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let set = ProfileSet::load(&profile_env(&dir)).unwrap();
+
+        assert_eq!(set.get("default").unwrap()["region"], "us-west-2");
+        assert_eq!(
+            set.get("default").unwrap()["aws_access_key_id"],
+            "AKIDDEFAULT"
+        );
+        assert_eq!(set.get("ml").unwrap()["region"], "eu-west-1");
+        assert!(set.get("corp").is_none());
+        assert!(set.get("sso-session corp").is_none());
+    }
+
+    #[test]
+    fn test_profile_resolution() {
+        let dir = tempfile::tempdir().unwrap();
+        let set = ProfileSet::load(&profile_env(&dir)).unwrap();
+
+        assert!(matches!(
+            set.resolve("default").unwrap(),
+            ProfileSource::Static(_)
+        ));
+        // credential_process takes precedence over static keys
+        assert!(matches!(
+            set.resolve("ml").unwrap(),
+            ProfileSource::Process(_)
+        ));
+        match set.resolve("cross-account").unwrap() {
+            ProfileSource::AssumeRole {
+                role_arn,
+                external_id,
+                source,
+                ..
+            } => {
+                assert_eq!(role_arn, "arn:aws:iam::123456789012:role/vectors");
+                assert_eq!(external_id.as_deref(), Some("shared-ml"));
+                assert!(matches!(*source, ProfileSource::Process(_)));
+            }
+            other => panic!("unexpected source: {other:?}"),
+        }
+
+        let err = set.resolve("loop-a").unwrap_err();
+        assert!(err.to_string().contains("loop-a -> loop-b -> loop-a"));
+        assert!(matches!(
+            set.resolve("missing"),
+            Err(CredentialsError::NotLoaded(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_profile_provider_reads_static_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = ProfileProvider::with_env("default", profile_env(&dir));
+        let credentials = provider.provide_credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "AKIDDEFAULT");
+        assert_eq!(provider.region(), "us-west-2");
+    }
+
+    #[tokio::test]
+    async fn test_profile_provider_assumes_role_from_source_profile() {
+        use crate::test_support::{MockResponse, MockServer};
+
+        let sts = MockServer::start(|_| {
+            MockResponse::new(
+                200,
+                "<AssumeRoleResponse><AssumeRoleResult><Credentials>\
+                 <AccessKeyId>ASIAROLE</AccessKeyId><SecretAccessKey>s</SecretAccessKey>\
+                 <SessionToken>t</SessionToken><Expiration>2030-01-01T00:00:00Z</Expiration>\
+                 </Credentials></AssumeRoleResult></AssumeRoleResponse>",
+            )
+        })
+        .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config");
+        std::fs::write(
+            &config,
+            "[profile shared-ml]\n\
+             role_arn = arn:aws:iam::111122223333:role/vectors\n\
+             source_profile = base\n\
+             mfa_serial = arn:aws:iam::444455556666:mfa/dev\n\
+             region = eu-west-1\n\
+             [profile base]\n\
+             aws_access_key_id = AKIDBASE\n\
+             aws_secret_access_key = secret\n",
+        )
+        .unwrap();
+        let env = Env::from_vars(&[
+            ("AWS_CONFIG_FILE", config.to_str().unwrap()),
+            ("AWS_SHARED_CREDENTIALS_FILE", "/nonexistent"),
+            ("AWS_ENDPOINT_URL_STS", &sts.url()),
+        ]);
+
+        // mfa_serial without a way to prompt is a configuration error
+        let without_prompt = ProfileProvider::with_env("shared-ml", env.clone());
+        assert!(without_prompt.provide_credentials().await.is_err());
+
+        let provider = ProfileProvider::with_env("shared-ml", env)
+            .mfa_token_provider(MfaTokenProvider::new(|_| Ok("654321".to_string())));
+        let credentials = provider.provide_credentials().await.unwrap();
+        assert_eq!(credentials.access_key_id(), "ASIAROLE");
+
+        let request = &sts.requests()[0];
+        assert!(request
+            .header("authorization")
+            .unwrap()
+            .contains("Credential=AKIDBASE/"));
+        assert!(request
+            .header("authorization")
+            .unwrap()
+            .contains("/eu-west-1/sts/"));
+        assert!(request.body_str().contains("TokenCode=654321"));
+    }
+}
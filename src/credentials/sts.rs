@@ -0,0 +1,90 @@
+//! Helpers for the STS query API, which answers in XML.
+//!
+//! The responses we consume are small and flat, so a tag extractor is enough
+//! and avoids pulling in an XML parser.
+
+use super::{parse_timestamp, Credentials, CredentialsError};
+
+/// STS endpoint for a region, honouring `AWS_ENDPOINT_URL_STS`
+pub(crate) fn endpoint(env: &super::Env, region: &str) -> String {
+    env.get("AWS_ENDPOINT_URL_STS")
+        .unwrap_or_else(|| {
+            let suffix = crate::RegionCatalogue::global().dns_suffix(region);
+            format!("https://sts.{region}.{suffix}")
+        })
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Turn an STS response into credentials, surfacing `<Error>` documents
+pub(crate) fn parse_response(status: u16, body: &str) -> Result<Credentials, CredentialsError> {
+    if !(200..300).contains(&status) {
+        let code = xml_tag(body, "Code").unwrap_or_else(|| format!("HTTP {status}"));
+        let message = xml_tag(body, "Message").unwrap_or_default();
+        return Err(anyhow::anyhow!("STS request failed: {code}: {message}").into());
+    }
+
+    let block = xml_tag(body, "Credentials")
+        .ok_or_else(|| anyhow::anyhow!("STS response contains no credentials"))?;
+    let field = |name: &str| {
+        xml_tag(&block, name).ok_or_else(|| anyhow::anyhow!("STS credentials missing <{name}>"))
+    };
+
+    Ok(Credentials::new(
+        field("AccessKeyId")?,
+        field("SecretAccessKey")?,
+        Some(field("SessionToken")?),
+    )
+    .with_expiration(parse_timestamp(&field("Expiration")?)?))
+}
+
+/// Text content of the first `<tag>` element, with XML entities decoded
+pub(crate) fn xml_tag(body: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}>");
+    let close = format!("</{tag}>");
+    let start = body.find(&open)? + open.len();
+    let end = start + body[start..].find(&close)?;
+    Some(unescape(body[start..end].trim()))
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response_extracts_credentials() {
+        let body = r#"<AssumeRoleWithWebIdentityResponse xmlns="https://sts.amazonaws.com/doc/2011-06-15/">
+  <AssumeRoleWithWebIdentityResult>
+    <Credentials>
+      <AccessKeyId>ASIAEXAMPLE</AccessKeyId>
+      <SecretAccessKey>secret&amp;key</SecretAccessKey>
+      <SessionToken>token</SessionToken>
+      <Expiration>2030-01-01T00:00:00Z</Expiration>
+    </Credentials>
+  </AssumeRoleWithWebIdentityResult>
+</AssumeRoleWithWebIdentityResponse>"#;
+
+        let credentials = parse_response(200, body).unwrap();
+        assert_eq!(credentials.access_key_id(), "ASIAEXAMPLE");
+        assert_eq!(credentials.secret_access_key(), "secret&key");
+        assert_eq!(credentials.session_token(), Some("token"));
+        assert!(credentials.expiration().is_some());
+    }
+
+    #[test]
+    fn test_parse_response_surfaces_errors() {
+        let body = "<ErrorResponse><Error><Code>AccessDenied</Code>\
+                    <Message>Not authorized</Message></Error></ErrorResponse>";
+        let err = parse_response(403, body).unwrap_err();
+        assert!(err.to_string().contains("AccessDenied: Not authorized"));
+    }
+}
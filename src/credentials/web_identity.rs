@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use super::{sts, Credentials, CredentialsError, CredentialsProvider, Env, ProvideCredentials};
+
+/// Exchanges an OIDC token file for role credentials via
+/// `sts:AssumeRoleWithWebIdentity` (EKS IRSA, GitHub Actions, ...)
+#[derive(Debug)]
+pub struct WebIdentityProvider {
+    token_file: PathBuf,
+    role_arn: String,
+    session_name: String,
+    endpoint: String,
+    http: reqwest::Client,
+}
+
+impl WebIdentityProvider {
+    pub fn new(token_file: impl Into<PathBuf>, role_arn: &str, region: &str) -> Self {
+        Self::with_env(&Env::default(), token_file.into(), role_arn, region)
+    }
+
+    pub(crate) fn with_env(env: &Env, token_file: PathBuf, role_arn: &str, region: &str) -> Self {
+        Self {
+            token_file,
+            role_arn: role_arn.to_string(),
+            session_name: env
+                .get("AWS_ROLE_SESSION_NAME")
+                .unwrap_or_else(|| format!("s3-vectors-{}", chrono::Utc::now().timestamp())),
+            endpoint: sts::endpoint(env, region),
+            http: crate::HTTP_CLIENT.clone(),
+        }
+    }
+
+    /// Configured through `AWS_WEB_IDENTITY_TOKEN_FILE` and `AWS_ROLE_ARN`
+    pub(crate) fn from_env(env: &Env, region: &str) -> Option<Self> {
+        let token_file = env.get("AWS_WEB_IDENTITY_TOKEN_FILE")?;
+        let role_arn = env.get("AWS_ROLE_ARN")?;
+        Some(Self::with_env(env, token_file.into(), &role_arn, region))
+    }
+
+    pub fn session_name(mut self, session_name: &str) -> Self {
+        self.session_name = session_name.to_string();
+        self
+    }
+
+    async fn fetch(&self) -> anyhow::Result<Credentials> {
+        let token = tokio::fs::read_to_string(&self.token_file)
+            .await
+            .with_context(|| {
+                format!(
+                    "Failed to read web identity token file: {}",
+                    self.token_file.display()
+                )
+            })?;
+
+        // AssumeRoleWithWebIdentity is authorized by the token itself, so the
+        // request is sent unsigned
+        let response = self
+            .http
+            .post(format!("{}/", self.endpoint))
+            .form(&[
+                ("Action", "AssumeRoleWithWebIdentity"),
+                ("Version", "2011-06-15"),
+                ("RoleArn", self.role_arn.as_str()),
+                ("RoleSessionName", self.session_name.as_str()),
+                ("WebIdentityToken", token.trim()),
+            ])
+            .send()
+            .await
+            .context("Failed to call STS AssumeRoleWithWebIdentity")?;
+
+        let status = response.status().as_u16();
+        let body = response.text().await?;
+        Ok(sts::parse_response(status, &body)?)
+    }
+}
+
+impl CredentialsProvider for WebIdentityProvider {
+    fn provide_credentials(&self) -> ProvideCredentials<'_> {
+        Box::pin(async move { self.fetch().await.map_err(CredentialsError::from) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    const STS_RESPONSE: &str = "<AssumeRoleWithWebIdentityResponse>\
+        <AssumeRoleWithWebIdentityResult><Credentials>\
+        <AccessKeyId>ASIAWEBID</AccessKeyId><SecretAccessKey>secret</SecretAccessKey>\
+        <SessionToken>session</SessionToken><Expiration>2030-01-01T00:00:00Z</Expiration>\
+        </Credentials></AssumeRoleWithWebIdentityResult></AssumeRoleWithWebIdentityResponse>";
+
+    #[tokio::test]
+    async fn test_web_identity_exchanges_token_with_sts() {
+        let sts = MockServer::start(|_| MockResponse::new(200, STS_RESPONSE)).await;
+        let dir = tempfile::tempdir().unwrap();
+        let token_file = dir.path().join("token");
+        std::fs::write(&token_file, "oidc-token\n").unwrap();
+
+        let env = Env::from_vars(&[
+            ("AWS_WEB_IDENTITY_TOKEN_FILE", token_file.to_str().unwrap()),
+            ("AWS_ROLE_ARN", "arn:aws:iam::123456789012:role/ml-reader"),
+            ("AWS_ROLE_SESSION_NAME", "ci"),
+            ("AWS_ENDPOINT_URL_STS", &sts.url()),
+        ]);
+        let provider = WebIdentityProvider::from_env(&env, "us-east-1").unwrap();
+        let credentials = provider.provide_credentials().await.unwrap();
+
+        assert_eq!(credentials.access_key_id(), "ASIAWEBID");
+        assert_eq!(credentials.session_token(), Some("session"));
+
+        let request = &sts.requests()[0];
+        assert_eq!(request.method, "POST");
+        assert!(request
+            .body_str()
+            .contains("Action=AssumeRoleWithWebIdentity"));
+        assert!(request.body_str().ends_with("WebIdentityToken=oidc-token"));
+        assert!(request.body_str().contains("RoleSessionName=ci"));
+        assert!(request.header("authorization").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_web_identity_requires_token_file_and_role() {
+        let env = Env::from_vars(&[("AWS_ROLE_ARN", "arn:aws:iam::123456789012:role/x")]);
+        assert!(WebIdentityProvider::from_env(&env, "us-east-1").is_none());
+    }
+}
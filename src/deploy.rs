@@ -1,50 +1,28 @@
+use crate::auth::uri_encode;
+use crate::credentials::CredentialsError;
+use crate::error::{IndexMismatchError, IndexSettingMismatch, S3VectorsError};
+use crate::retry::parse_retry_after;
 use crate::types::*;
 use crate::validation::*;
+use crate::waiter::Waiter;
 use crate::S3VectorsClient;
-use crate::HTTP_CLIENT;
 use anyhow::{Context, Result};
-use reqwest::StatusCode;
+use futures_util::{StreamExt, TryStreamExt};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::HashMap;
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
-const MAX_RETRIES: u32 = 3;
-const INITIAL_BACKOFF_MS: u64 = 100;
-const MAX_BACKOFF_MS: u64 = 5000;
 const MAX_BATCH_SIZE: usize = 500;
-
-#[derive(Debug, thiserror::Error)]
-pub enum S3VectorsError {
-    #[error("Authentication required: {0}")]
-    AuthRequired(String),
-
-    #[error("Validation error: {0}")]
-    Validation(String),
-
-    #[error("Resource not found: {0}")]
-    NotFound(String),
-
-    #[error("Resource already exists: {0}")]
-    AlreadyExists(String),
-
-    #[error("Rate limit exceeded, retry after {0}ms")]
-    RateLimit(u64),
-
-    #[error("Service error: {0}")]
-    ServiceError(String),
-
-    #[error("Request error: {0}")]
-    RequestError(#[from] reqwest::Error),
-
-    #[error("JSON error: {0}")]
-    JsonError(#[from] serde_json::Error),
-
-    #[error("Other error: {0}")]
-    Other(#[from] anyhow::Error),
-}
+/// Service limits on keys per GetVectors and DeleteVectors request
+const MAX_GET_KEYS: usize = 100;
+const MAX_DELETE_KEYS: usize = 500;
+/// Requests in flight for the chunked key helpers
+const KEY_BATCH_CONCURRENCY: usize = 4;
+/// Default cap on a PutVectors request body
+const MAX_BATCH_BYTES: usize = 20 * 1024 * 1024;
 
 impl S3VectorsClient {
     #[tracing::instrument(skip(self, body), fields(path))]
@@ -52,6 +30,17 @@ impl S3VectorsClient {
         &self,
         path: &str,
         body: Option<impl Serialize>,
+    ) -> Result<T, S3VectorsError> {
+        self.execute(reqwest::Method::POST, path, body).await
+    }
+
+    /// Send a signed request with retries. `path` may carry an already
+    /// encoded query string; an empty response body parses as `{}`.
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<impl Serialize>,
     ) -> Result<T, S3VectorsError> {
         let url = format!("{}{}", self.endpoint, path);
 
@@ -60,91 +49,89 @@ impl S3VectorsClient {
             .as_ref()
             .ok_or_else(|| S3VectorsError::AuthRequired("No credentials configured".to_string()))?;
 
-        let mut retries = 0;
-        let mut backoff = INITIAL_BACKOFF_MS;
+        let body_bytes = match body {
+            Some(body) => serde_json::to_vec(&body)?,
+            None => vec![],
+        };
 
-        loop {
-            let mut request = HTTP_CLIENT.request(reqwest::Method::POST, &url);
+        let started = Instant::now();
+        let mut attempt = 0;
 
-            // Add body if present
-            let body_bytes = if let Some(ref body) = body {
-                let bytes = serde_json::to_vec(body)?;
-                request = request.body(bytes.clone());
-                bytes
-            } else {
-                vec![]
-            };
+        loop {
+            let wait = self.retry_strategy.before_attempt();
+            if !wait.is_zero() {
+                debug!("Client-side rate limit: delaying {} by {:?}", path, wait);
+                sleep(wait).await;
+            }
 
-            // Sign the request
+            // Sign every attempt so a retry after a long backoff never carries
+            // a stale x-amz-date
             let headers = signer
-                .sign_request("POST", &url, HashMap::new(), &body_bytes)
-                .await?;
-
+                .sign_request(method.as_str(), &url, HashMap::new(), &body_bytes)
+                .await
+                .map_err(|e| match e.downcast::<CredentialsError>() {
+                    Ok(CredentialsError::NotLoaded(reason)) => S3VectorsError::AuthRequired(reason),
+                    Ok(other) => S3VectorsError::Other(other.into()),
+                    Err(e) => S3VectorsError::Other(e),
+                })?;
+
+            let mut request = self
+                .http
+                .request(method.clone(), &url)
+                .body(body_bytes.clone());
             for (key, value) in headers {
                 request = request.header(key, value);
             }
-
             request = request.header("Content-Type", "application/json");
 
             debug!("Executing request to {}", path);
-            let response = request.send().await?;
-            let status = response.status();
-
-            if status.is_success() {
-                let result = response
-                    .json::<T>()
-                    .await
-                    .context("Failed to parse response")?;
-                return Ok(result);
-            }
+            let (err, retry_after) = match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    self.retry_strategy.on_success();
+                    let bytes = response.bytes().await?;
+                    let bytes: &[u8] = if bytes.is_empty() { b"{}" } else { &bytes };
+                    let result =
+                        serde_json::from_slice::<T>(bytes).context("Failed to parse response")?;
+                    return Ok(result);
+                }
+                Ok(response) => {
+                    let status = response.status();
+                    let headers = response.headers().clone();
+                    let retry_after = headers
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(parse_retry_after);
+                    let error_text = response.text().await.unwrap_or_default();
+                    (
+                        S3VectorsError::from_response(status.as_u16(), &headers, &error_text),
+                        retry_after,
+                    )
+                }
+                Err(e) => (S3VectorsError::RequestError(e), None),
+            };
 
-            // Handle errors
-            let error_text = response.text().await.unwrap_or_default();
-
-            if let Ok(service_error) = serde_json::from_str::<ServiceError>(&error_text) {
-                match status {
-                    StatusCode::NOT_FOUND => {
-                        return Err(S3VectorsError::NotFound(service_error.message));
-                    }
-                    StatusCode::CONFLICT => {
-                        if let Some(error_type) = service_error.error_type {
-                            if error_type.contains("ConflictException")
-                                || error_type.contains("AlreadyExistsException")
-                            {
-                                return Err(S3VectorsError::AlreadyExists(service_error.message));
-                            } else {
-                                return Err(S3VectorsError::ServiceError(service_error.message));
-                            }
-                        } else {
-                            return Err(S3VectorsError::ServiceError(service_error.message));
-                        }
-                    }
-                    StatusCode::TOO_MANY_REQUESTS => {
-                        if retries < MAX_RETRIES {
-                            warn!("Rate limited, retrying after {}ms", backoff);
-                            sleep(Duration::from_millis(backoff)).await;
-                            backoff = (backoff * 2).min(MAX_BACKOFF_MS);
-                            retries += 1;
-                            continue;
-                        }
-                        return Err(S3VectorsError::RateLimit(backoff));
-                    }
-                    _ if status.is_server_error() && retries < MAX_RETRIES => {
-                        warn!("Server error, retrying after {}ms", backoff);
-                        sleep(Duration::from_millis(backoff)).await;
-                        backoff = (backoff * 2).min(MAX_BACKOFF_MS);
-                        retries += 1;
-                        continue;
-                    }
-                    _ => {
-                        return Err(S3VectorsError::ServiceError(service_error.message));
-                    }
+            let Some(mut delay) = self.retry_strategy.retry_delay(attempt, &err, retry_after)
+            else {
+                return Err(err);
+            };
+            if let Some(deadline) = self.retry.deadline {
+                let remaining = deadline.saturating_sub(started.elapsed());
+                if remaining.is_zero() {
+                    debug!("Retry deadline of {:?} reached for {}", deadline, path);
+                    return Err(err);
                 }
+                // Never sleep past the deadline, so the last attempt runs just before it
+                delay = delay.min(remaining);
             }
 
-            return Err(S3VectorsError::ServiceError(format!(
-                "Request failed with status {status}: {error_text}"
-            )));
+            warn!(
+                "{} failed with {}, retrying after {:?}",
+                path,
+                err.code().unwrap_or("a transport error"),
+                delay
+            );
+            sleep(delay).await;
+            attempt += 1;
         }
     }
 
@@ -154,19 +141,55 @@ impl S3VectorsClient {
         &self,
         bucket_name: &str,
     ) -> Result<VectorBucket, S3VectorsError> {
-        validate_bucket_name(bucket_name).map_err(|e| S3VectorsError::Validation(e.to_string()))?;
-
-        let request = CreateVectorBucketRequest {
+        self.create_vector_bucket_with(CreateVectorBucketRequest {
             vector_bucket_name: bucket_name.to_string(),
-        };
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// Create a vector bucket with encryption settings and tags
+    #[tracing::instrument(skip(self, request), fields(bucket_name = %request.vector_bucket_name))]
+    pub async fn create_vector_bucket_with(
+        &self,
+        request: CreateVectorBucketRequest,
+    ) -> Result<VectorBucket, S3VectorsError> {
+        let validation = |e: anyhow::Error| S3VectorsError::Validation(e.to_string());
+        validate_bucket_name(&request.vector_bucket_name).map_err(validation)?;
+        if let Some(encryption) = &request.encryption_configuration {
+            match (encryption.sse_type.as_deref(), &encryption.kms_key_arn) {
+                (Some(EncryptionConfiguration::SSE_KMS), Some(arn)) => {
+                    validate_kms_key_arn(arn).map_err(validation)?
+                }
+                (Some(EncryptionConfiguration::SSE_KMS), None) => {
+                    return Err(S3VectorsError::Validation(
+                        "SSE-KMS encryption requires a KMS key ARN".to_string(),
+                    ))
+                }
+                (Some(EncryptionConfiguration::SSE_S3) | None, None) => {}
+                (Some(EncryptionConfiguration::SSE_S3) | None, Some(_)) => {
+                    return Err(S3VectorsError::Validation(
+                        "A KMS key ARN can only be used with SSE-KMS (aws:kms) encryption"
+                            .to_string(),
+                    ))
+                }
+                (Some(other), _) => {
+                    return Err(S3VectorsError::Validation(format!(
+                        "Unsupported encryption type '{other}'; use AES256 or aws:kms"
+                    )))
+                }
+            }
+        }
+        validate_tags(&request.tags).map_err(validation)?;
 
+        let bucket_name = request.vector_bucket_name.clone();
         info!("Creating vector bucket: {}", bucket_name);
         let _: CreateVectorBucketResponse = self
             .execute_request("/CreateVectorBucket", Some(request))
             .await?;
 
         // The API returns empty response body, so we need to describe the bucket to get its details
-        self.describe_vector_bucket(bucket_name).await
+        self.describe_vector_bucket(&bucket_name).await
     }
 
     #[tracing::instrument(skip(self), fields(bucket_name))]
@@ -242,12 +265,26 @@ impl S3VectorsClient {
             .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
         validate_dimensions(request.dimension)
             .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
+        if let Some(keys) = request
+            .metadata_configuration
+            .as_ref()
+            .and_then(|config| config.non_filterable_metadata_keys.as_deref())
+        {
+            validate_non_filterable_keys(keys)
+                .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
+        }
 
         info!(
             "Creating index {} in bucket {}",
             request.index_name, request.vector_bucket_name
         );
+        let cache_key = (
+            request.vector_bucket_name.clone(),
+            request.index_name.clone(),
+        );
         let _: serde_json::Value = self.execute_request("/CreateIndex", Some(request)).await?;
+        // Forget an earlier failure to describe the index before it existed
+        self.non_filterable_keys.lock().unwrap().remove(&cache_key);
 
         Ok(())
     }
@@ -268,6 +305,10 @@ impl S3VectorsClient {
         };
         self.execute_request::<serde_json::Value>("/DeleteIndex", Some(request))
             .await?;
+        self.non_filterable_keys
+            .lock()
+            .unwrap()
+            .remove(&(bucket_name.to_string(), index_name.to_string()));
 
         Ok(())
     }
@@ -370,6 +411,11 @@ impl S3VectorsClient {
         if request.keys.is_empty() {
             return Err(S3VectorsError::Validation("No keys provided".to_string()));
         }
+        if request.keys.len() > MAX_GET_KEYS {
+            return Err(S3VectorsError::Validation(format!(
+                "GetVectors accepts at most {MAX_GET_KEYS} keys per request; use get_vectors_chunked"
+            )));
+        }
 
         info!(
             "Getting {} vectors from index {} in bucket {}",
@@ -393,6 +439,11 @@ impl S3VectorsClient {
         if request.keys.is_empty() {
             return Err(S3VectorsError::Validation("No keys provided".to_string()));
         }
+        if request.keys.len() > MAX_DELETE_KEYS {
+            return Err(S3VectorsError::Validation(format!(
+                "DeleteVectors accepts at most {MAX_DELETE_KEYS} keys per request; use delete_vectors_chunked"
+            )));
+        }
 
         info!(
             "Deleting {} vectors from index {} in bucket {}",
@@ -407,6 +458,87 @@ impl S3VectorsClient {
         Ok(())
     }
 
+    /// Get any number of vectors, split into compliant requests sent
+    /// concurrently.
+    ///
+    /// Vectors come back in the order of `request.keys`; keys that do not
+    /// exist are listed in `missing_keys`. Fails on the first failed request.
+    pub async fn get_vectors_chunked(
+        &self,
+        request: GetVectorsRequest,
+    ) -> Result<GetVectorsReport, S3VectorsError> {
+        if request.keys.is_empty() {
+            return Err(S3VectorsError::Validation("No keys provided".to_string()));
+        }
+
+        let requests = request
+            .keys
+            .chunks(MAX_GET_KEYS)
+            .map(|keys| GetVectorsRequest {
+                keys: keys.to_vec(),
+                ..request.clone()
+            });
+        let responses: Vec<GetVectorsResponse> = futures_util::stream::iter(requests)
+            .map(|request| self.get_vectors(request))
+            .buffer_unordered(KEY_BATCH_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        let found: HashMap<String, RetrievedVector> = responses
+            .into_iter()
+            .flat_map(|response| response.vectors)
+            .map(|vector| (vector.key.clone(), vector))
+            .collect();
+
+        let mut report = GetVectorsReport::default();
+        for key in request.keys {
+            // Repeated keys get the same vector each time
+            match found.get(&key) {
+                Some(vector) => report.vectors.push(vector.clone()),
+                None => report.missing_keys.push(key),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Delete any number of vectors, split into compliant requests sent
+    /// concurrently. Failed requests are reported per key rather than
+    /// aborting the remaining deletes.
+    pub async fn delete_vectors_chunked(
+        &self,
+        request: DeleteVectorsRequest,
+    ) -> Result<DeleteVectorsReport, S3VectorsError> {
+        if request.keys.is_empty() {
+            return Err(S3VectorsError::Validation("No keys provided".to_string()));
+        }
+
+        let requests = request
+            .keys
+            .chunks(MAX_DELETE_KEYS)
+            .map(|keys| DeleteVectorsRequest {
+                keys: keys.to_vec(),
+                ..request.clone()
+            });
+        let mut results = futures_util::stream::iter(requests)
+            .map(|request| async move {
+                let result = self.delete_vectors(request.clone()).await;
+                (request.keys, result)
+            })
+            .buffer_unordered(KEY_BATCH_CONCURRENCY);
+
+        let mut report = DeleteVectorsReport::default();
+        while let Some((keys, result)) = results.next().await {
+            match result {
+                Ok(()) => report.deleted.extend(keys),
+                Err(error) => {
+                    warn!("Deleting {} vectors failed: {}", keys.len(), error);
+                    report.failed.push(BatchFailure { keys, error });
+                }
+            }
+        }
+        Ok(report)
+    }
+
     pub async fn list_vectors(
         &self,
         request: ListVectorsRequest,
@@ -415,6 +547,8 @@ impl S3VectorsClient {
             .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
         validate_index_name(&request.index_name)
             .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
+        validate_segment(request.segment_count, request.segment_index)
+            .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
 
         info!(
             "Listing vectors in index {} of bucket {}",
@@ -433,6 +567,20 @@ impl S3VectorsClient {
         validate_index_name(&request.index_name)
             .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
 
+        if let Some(filter) = &request.filter {
+            filter
+                .validate()
+                .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
+            if let Some(keys) = self
+                .index_non_filterable_keys(&request.vector_bucket_name, &request.index_name)
+                .await
+            {
+                filter
+                    .check_filterable(&keys)
+                    .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
+            }
+        }
+
         info!(
             "Querying vectors in index {} of bucket {}",
             request.index_name, request.vector_bucket_name
@@ -441,6 +589,44 @@ impl S3VectorsClient {
         self.execute_request("/QueryVectors", Some(request)).await
     }
 
+    /// Non-filterable metadata keys of an index, described once and cached.
+    /// `None` if the index cannot be described (e.g. without `GetIndex`
+    /// permission), leaving the check to the service. That outcome is cached
+    /// too unless the failure was transient, so queries don't each pay for
+    /// a failing `GetIndex`.
+    async fn index_non_filterable_keys(
+        &self,
+        bucket_name: &str,
+        index_name: &str,
+    ) -> Option<Vec<String>> {
+        let cache_key = (bucket_name.to_string(), index_name.to_string());
+        if let Some(keys) = self.non_filterable_keys.lock().unwrap().get(&cache_key) {
+            return keys.clone();
+        }
+
+        let keys = match self.describe_index(bucket_name, index_name).await {
+            Ok(index) => Some(
+                index
+                    .metadata_configuration
+                    .and_then(|config| config.non_filterable_metadata_keys)
+                    .unwrap_or_default(),
+            ),
+            Err(e) => {
+                debug!("Not checking filter keys for index {}: {}", index_name, e);
+                if e.is_retryable() {
+                    // Describe it again next time
+                    return None;
+                }
+                None
+            }
+        };
+        self.non_filterable_keys
+            .lock()
+            .unwrap()
+            .insert(cache_key, keys.clone());
+        keys
+    }
+
     pub async fn get_index(
         &self,
         vector_bucket_name: &str,
@@ -528,15 +714,132 @@ impl S3VectorsClient {
 
         Ok(())
     }
+
+    // Tagging operations
+
+    /// Add or overwrite tags on a vector bucket or index, given its ARN
+    #[tracing::instrument(skip(self, tags))]
+    pub async fn tag_resource(
+        &self,
+        resource_arn: &str,
+        tags: BTreeMap<String, String>,
+    ) -> Result<(), S3VectorsError> {
+        validate_resource_arn(resource_arn)
+            .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
+        if tags.is_empty() {
+            return Err(S3VectorsError::Validation("No tags provided".to_string()));
+        }
+        validate_tags(&tags).map_err(|e| S3VectorsError::Validation(e.to_string()))?;
+
+        info!("Tagging {} with {} tag(s)", resource_arn, tags.len());
+        self.execute::<serde_json::Value>(
+            reqwest::Method::POST,
+            &tags_path(resource_arn),
+            Some(TagResourceRequest { tags }),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove tags by key from a vector bucket or index
+    #[tracing::instrument(skip(self))]
+    pub async fn untag_resource(
+        &self,
+        resource_arn: &str,
+        tag_keys: &[String],
+    ) -> Result<(), S3VectorsError> {
+        validate_resource_arn(resource_arn)
+            .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
+        if tag_keys.is_empty() {
+            return Err(S3VectorsError::Validation(
+                "No tag keys provided".to_string(),
+            ));
+        }
+
+        let query: Vec<String> = tag_keys
+            .iter()
+            .map(|key| format!("tagKeys={}", uri_encode(key, false)))
+            .collect();
+        let path = format!("{}?{}", tags_path(resource_arn), query.join("&"));
+
+        info!("Removing {} tag(s) from {}", tag_keys.len(), resource_arn);
+        self.execute::<serde_json::Value>(reqwest::Method::DELETE, &path, None::<()>)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Tags of a vector bucket or index
+    #[tracing::instrument(skip(self))]
+    pub async fn list_tags_for_resource(
+        &self,
+        resource_arn: &str,
+    ) -> Result<BTreeMap<String, String>, S3VectorsError> {
+        validate_resource_arn(resource_arn)
+            .map_err(|e| S3VectorsError::Validation(e.to_string()))?;
+
+        let response: ListTagsForResourceResponse = self
+            .execute(reqwest::Method::GET, &tags_path(resource_arn), None::<()>)
+            .await?;
+
+        Ok(response.tags)
+    }
+}
+
+/// `/tags/{resourceArn}`, with the ARN encoded as a single path segment
+fn tags_path(resource_arn: &str) -> String {
+    format!("/tags/{}", uri_encode(resource_arn, false))
 }
 
 // Helper functions
+
+/// Settings for [`create_bucket_and_index_with`]
+#[derive(Clone, Debug, Default)]
+pub struct CreateIndexOptions {
+    /// Metadata keys of a newly created index, e.g. non-filterable keys
+    pub metadata_configuration: Option<MetadataConfiguration>,
+    /// Delete and recreate an existing index whose settings differ, losing
+    /// every vector in it, instead of failing with [`IndexMismatchError`]
+    pub recreate_on_mismatch: bool,
+}
+
+/// Create the bucket and index if they do not exist yet, with the default
+/// [`CreateIndexOptions`]
 pub async fn create_bucket_and_index(
     client: &S3VectorsClient,
     bucket_name: &str,
     index_name: &str,
     dimensions: u32,
     distance_metric: DistanceMetric,
+) -> Result<(VectorBucket, VectorIndex)> {
+    create_bucket_and_index_with(
+        client,
+        bucket_name,
+        index_name,
+        dimensions,
+        distance_metric,
+        &CreateIndexOptions::default(),
+    )
+    .await
+}
+
+/// Create the bucket and index if they do not exist yet.
+///
+/// An existing index is reused only when its dimension, data type, distance
+/// metric and non-filterable metadata keys match the request; otherwise this
+/// fails with [`IndexMismatchError`], or, with
+/// [`recreate_on_mismatch`](CreateIndexOptions::recreate_on_mismatch),
+/// deletes the index (and every vector in it) and creates it again. An index
+/// without any non-filterable keys is reused with a warning, whatever keys
+/// are requested.
+pub async fn create_bucket_and_index_with(
+    client: &S3VectorsClient,
+    bucket_name: &str,
+    index_name: &str,
+    dimensions: u32,
+    distance_metric: DistanceMetric,
+    options: &CreateIndexOptions,
 ) -> Result<(VectorBucket, VectorIndex)> {
     info!("Creating bucket {} and index {}", bucket_name, index_name);
 
@@ -553,7 +856,7 @@ pub async fn create_bucket_and_index(
                 Ok(_) => {
                     info!("Bucket {} created successfully", bucket_name);
                     // Wait for newly created bucket to be active
-                    wait_for_bucket_active(client, bucket_name).await?
+                    Waiter::default().bucket_active(client, bucket_name).await?
                 }
                 Err(S3VectorsError::AlreadyExists(_)) => {
                     // Race condition: bucket was created by another process
@@ -576,19 +879,60 @@ pub async fn create_bucket_and_index(
         dimension: dimensions,
         data_type: DataType::Float32,
         distance_metric,
-        metadata_configuration: None,
+        metadata_configuration: options.metadata_configuration.clone(),
     };
 
-    let index = match client.create_index(index_request).await {
+    let index = match client.create_index(index_request.clone()).await {
         Ok(()) => {
             info!("Index {} created successfully", index_name);
             // Wait for newly created index to be active
-            wait_for_index_active(client, bucket_name, index_name).await?
+            Waiter::default()
+                .index_active(client, bucket_name, index_name)
+                .await?
         }
         Err(S3VectorsError::AlreadyExists(_)) => {
-            info!("Index {} already exists, using existing", index_name);
             // Existing indexes are already active, just describe it
-            client.describe_index(bucket_name, index_name).await?
+            let existing = client.describe_index(bucket_name, index_name).await?;
+            let mismatches = index_mismatches(&existing, &index_request);
+            if mismatches.is_empty() {
+                info!("Index {} already exists, using existing", index_name);
+                let keys = |config: Option<&MetadataConfiguration>| {
+                    config
+                        .and_then(|config| config.non_filterable_metadata_keys.clone())
+                        .unwrap_or_default()
+                };
+                let requested = keys(index_request.metadata_configuration.as_ref());
+                if keys(existing.metadata_configuration.as_ref()).is_empty()
+                    && !requested.is_empty()
+                {
+                    warn!(
+                        "Index {} has no non-filterable metadata keys, so {} stay filterable \
+                         and count towards its filterable metadata limit; recreate it to change that",
+                        index_name,
+                        requested.join(", ")
+                    );
+                }
+                existing
+            } else if options.recreate_on_mismatch {
+                warn!(
+                    "Index {} has different settings, deleting and recreating it",
+                    index_name
+                );
+                let waiter = Waiter::default();
+                client.delete_index(bucket_name, index_name).await?;
+                waiter
+                    .index_deleted(client, bucket_name, index_name)
+                    .await?;
+                client.create_index(index_request).await?;
+                waiter.index_active(client, bucket_name, index_name).await?
+            } else {
+                return Err(IndexMismatchError {
+                    bucket: bucket_name.to_string(),
+                    index: index_name.to_string(),
+                    mismatches,
+                }
+                .into());
+            }
         }
         Err(e) => return Err(e.into()),
     };
@@ -596,93 +940,1076 @@ pub async fn create_bucket_and_index(
     Ok((bucket, index))
 }
 
+/// Settings of `existing` that differ from `request`; the order of
+/// non-filterable keys does not matter, and an index without any (e.g. one
+/// created before they were requested) accepts whatever is requested
+fn index_mismatches(
+    existing: &VectorIndex,
+    request: &CreateIndexRequest,
+) -> Vec<IndexSettingMismatch> {
+    fn wire_name<T: Serialize>(value: &T) -> String {
+        serde_json::to_value(value)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
+    fn sorted_keys(config: Option<&MetadataConfiguration>) -> Vec<String> {
+        let mut keys = config
+            .and_then(|config| config.non_filterable_metadata_keys.clone())
+            .unwrap_or_default();
+        keys.sort();
+        keys
+    }
+    fn key_list(keys: &[String]) -> String {
+        format!("[{}]", keys.join(", "))
+    }
+
+    let mut mismatches = Vec::new();
+    let mut compare = |field, existing: String, requested: String| {
+        if existing != requested {
+            mismatches.push(IndexSettingMismatch {
+                field,
+                existing,
+                requested,
+            });
+        }
+    };
+
+    compare(
+        "dimension",
+        existing.dimension.to_string(),
+        request.dimension.to_string(),
+    );
+    compare(
+        "dataType",
+        wire_name(&existing.data_type),
+        wire_name(&request.data_type),
+    );
+    compare(
+        "distanceMetric",
+        wire_name(&existing.distance_metric),
+        wire_name(&request.distance_metric),
+    );
+    let existing_keys = sorted_keys(existing.metadata_configuration.as_ref());
+    if !existing_keys.is_empty() {
+        compare(
+            "nonFilterableMetadataKeys",
+            key_list(&existing_keys),
+            key_list(&sorted_keys(request.metadata_configuration.as_ref())),
+        );
+    }
+
+    mismatches
+}
+
+/// Limits for [`batch_put_vectors_with`]
+#[derive(Clone, Debug)]
+pub struct BatchPutConfig {
+    /// Most vectors in one PutVectors request (service limit: 500)
+    pub max_batch_vectors: usize,
+    /// Most serialized bytes in one PutVectors request body
+    pub max_batch_bytes: usize,
+    /// PutVectors requests in flight at once
+    pub concurrency: usize,
+    /// Extra attempts for a batch whose error is retryable, on top of the
+    /// client's own retries
+    pub batch_retries: u32,
+    /// Delay before the first batch retry; doubled after each attempt
+    pub retry_backoff: Duration,
+}
+
+impl Default for BatchPutConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_vectors: MAX_BATCH_SIZE,
+            max_batch_bytes: MAX_BATCH_BYTES,
+            concurrency: 4,
+            batch_retries: 2,
+            retry_backoff: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Outcome of a batch put: every input key is either in `succeeded` or in
+/// exactly one entry of `failed`
+#[derive(Debug, Default)]
+pub struct BatchPutReport {
+    pub succeeded: Vec<String>,
+    pub failed: Vec<BatchFailure>,
+}
+
+/// Vectors that could not be written or deleted, and why
+#[derive(Debug)]
+pub struct BatchFailure {
+    pub keys: Vec<String>,
+    pub error: S3VectorsError,
+}
+
+impl BatchPutReport {
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    pub fn failed_keys(&self) -> impl Iterator<Item = &str> {
+        self.failed
+            .iter()
+            .flat_map(|f| f.keys.iter().map(String::as_str))
+    }
+
+    /// Turn a partial failure into an error naming the first failure
+    pub fn into_result(self) -> Result<Self> {
+        match self.failed.first() {
+            None => Ok(self),
+            Some(first) => Err(anyhow::anyhow!(
+                "{} of {} vectors failed to upload; first error: {}",
+                self.failed_keys().count(),
+                self.failed_keys().count() + self.succeeded.len(),
+                first.error
+            )),
+        }
+    }
+}
+
+/// Result of [`S3VectorsClient::get_vectors_chunked`]
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetVectorsReport {
+    /// Found vectors, in the order their keys were requested
+    pub vectors: Vec<RetrievedVector>,
+    pub missing_keys: Vec<String>,
+}
+
+/// Result of [`S3VectorsClient::delete_vectors_chunked`]
+#[derive(Debug, Default)]
+pub struct DeleteVectorsReport {
+    pub deleted: Vec<String>,
+    pub failed: Vec<BatchFailure>,
+}
+
+impl DeleteVectorsReport {
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    pub fn failed_keys(&self) -> impl Iterator<Item = &str> {
+        self.failed
+            .iter()
+            .flat_map(|f| f.keys.iter().map(String::as_str))
+    }
+}
+
+/// Validate and upload vectors with the default [`BatchPutConfig`]
 pub async fn batch_put_vectors(
     client: &S3VectorsClient,
     bucket_name: &str,
     index_name: &str,
     vectors: Vec<Vector>,
     expected_dimensions: u32,
-) -> Result<()> {
-    // Validate all vectors
-    for vector in &vectors {
-        vector.validate(expected_dimensions)?;
-    }
+) -> Result<BatchPutReport> {
+    batch_put_vectors_with(
+        client,
+        bucket_name,
+        index_name,
+        vectors,
+        expected_dimensions,
+        &BatchPutConfig::default(),
+    )
+    .await
+}
 
-    // Process in batches
-    for chunk in vectors.chunks(MAX_BATCH_SIZE) {
-        let request = PutVectorsRequest {
-            vector_bucket_name: bucket_name.to_string(),
-            index_name: index_name.to_string(),
-            vectors: chunk.to_vec(),
-        };
+/// Validate and upload vectors in concurrent batches split by count and size.
+///
+/// Failures are collected in the report instead of aborting the upload, so
+/// callers can resend just the failed keys. The whole call fails only for an
+/// invalid bucket or index name, or when not a single vector was written; the
+/// error then wraps the first failure's [`S3VectorsError`].
+pub async fn batch_put_vectors_with(
+    client: &S3VectorsClient,
+    bucket_name: &str,
+    index_name: &str,
+    vectors: Vec<Vector>,
+    expected_dimensions: u32,
+    config: &BatchPutConfig,
+) -> Result<BatchPutReport> {
+    validate_bucket_name(bucket_name)?;
+    validate_index_name(index_name)?;
+
+    let mut report = BatchPutReport::default();
+    let mut batches: Vec<Vec<Vector>> = Vec::new();
+    let mut current = Vec::new();
+    // Request envelope around the vectors array
+    let overhead = bucket_name.len() + index_name.len() + 64;
+    let mut current_bytes = overhead;
+
+    for vector in vectors {
+        if let Err(e) = vector.validate(expected_dimensions) {
+            report.failed.push(BatchFailure {
+                keys: vec![vector.key],
+                error: S3VectorsError::Validation(e.to_string()),
+            });
+            continue;
+        }
+
+        // Plus one for the separating comma
+        let size = serde_json::to_vec(&vector)?.len() + 1;
+        if overhead + size > config.max_batch_bytes {
+            report.failed.push(BatchFailure {
+                error: S3VectorsError::Validation(format!(
+                    "Vector {} is {} bytes, over the {} byte request limit",
+                    vector.key, size, config.max_batch_bytes
+                )),
+                keys: vec![vector.key],
+            });
+            continue;
+        }
 
-        client.put_vectors(request).await?;
+        if current.len() >= config.max_batch_vectors.max(1)
+            || current_bytes + size > config.max_batch_bytes
+        {
+            batches.push(std::mem::take(&mut current));
+            current_bytes = overhead;
+        }
+        current_bytes += size;
+        current.push(vector);
+    }
+    if !current.is_empty() {
+        batches.push(current);
+    }
 
-        // Small delay between batches to avoid rate limiting
-        if vectors.len() > MAX_BATCH_SIZE {
-            sleep(Duration::from_millis(100)).await;
+    debug!(
+        "Uploading {} batches with concurrency {}",
+        batches.len(),
+        config.concurrency
+    );
+    let mut results = futures_util::stream::iter(batches)
+        .map(|batch| put_batch(client, bucket_name, index_name, batch, config))
+        .buffer_unordered(config.concurrency.max(1));
+
+    while let Some((keys, result)) = results.next().await {
+        match result {
+            Ok(()) => report.succeeded.extend(keys),
+            Err(error) => {
+                warn!("Batch of {} vectors failed: {}", keys.len(), error);
+                report.failed.push(BatchFailure { keys, error });
+            }
         }
     }
 
-    info!("Successfully put {} vectors", vectors.len());
-    Ok(())
+    info!(
+        "Put {} vectors, {} failed",
+        report.succeeded.len(),
+        report.failed_keys().count()
+    );
+    if report.succeeded.is_empty() && !report.failed.is_empty() {
+        let failed = report.failed_keys().count();
+        let first = report.failed.swap_remove(0);
+        return Err(anyhow::Error::new(first.error)
+            .context(format!("All {failed} vectors failed to upload")));
+    }
+    Ok(report)
 }
 
-async fn wait_for_bucket_active(
+/// Send one batch, retrying retryable failures
+async fn put_batch(
     client: &S3VectorsClient,
     bucket_name: &str,
-) -> Result<VectorBucket> {
-    info!("Waiting for bucket {} to become active", bucket_name);
-
-    for _ in 0..60 {
-        let bucket = client.describe_vector_bucket(bucket_name).await?;
-        match bucket.status {
-            Some(BucketStatus::Active) => {
-                info!("Bucket {} is active", bucket_name);
-                return Ok(bucket);
+    index_name: &str,
+    vectors: Vec<Vector>,
+    config: &BatchPutConfig,
+) -> (Vec<String>, Result<(), S3VectorsError>) {
+    let keys = vectors.iter().map(|v| v.key.clone()).collect();
+    let request = PutVectorsRequest {
+        vector_bucket_name: bucket_name.to_string(),
+        index_name: index_name.to_string(),
+        vectors,
+    };
+
+    let mut backoff = config.retry_backoff;
+    let mut attempt = 0;
+    loop {
+        match client.put_vectors(request.clone()).await {
+            Err(e) if e.is_retryable() && attempt < config.batch_retries => {
+                debug!("Retrying batch after {:?}: {}", backoff, e);
+                sleep(backoff).await;
+                backoff *= 2;
+                attempt += 1;
             }
-            Some(BucketStatus::Failed) => {
-                error!("Bucket {} creation failed with status: Failed", bucket_name);
-                return Err(anyhow::anyhow!("Bucket creation failed"));
+            result => return (keys, result),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_service_errors_carry_code_and_request_id() {
+        let server = MockServer::start(|_| {
+            MockResponse::json(
+                404,
+                serde_json::json!({
+                    "__type": "NotFoundException",
+                    "message": "Vector bucket not found",
+                }),
+            )
+            .header("x-amz-request-id", "4442587FB7D0A2F9")
+        })
+        .await;
+
+        let err = server
+            .client()
+            .describe_vector_bucket("missing-bucket")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, S3VectorsError::NotFound(_)));
+        assert_eq!(err.code(), Some("NotFoundException"));
+        assert_eq!(err.status(), Some(404));
+        assert_eq!(err.request_id(), Some("4442587FB7D0A2F9"));
+        assert!(!err.is_retryable());
+        // Client errors are not retried
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retryable_errors_are_retried() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let server = MockServer::start(move |_| {
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                MockResponse::json(
+                    503,
+                    serde_json::json!({
+                        "__type": "ServiceUnavailableException",
+                        "message": "Please retry",
+                    }),
+                )
+            } else {
+                MockResponse::json(200, serde_json::json!({ "vectorBuckets": [] }))
             }
-            Some(s) => {
-                info!("Bucket {} current status: {:?}", bucket_name, s);
-                sleep(Duration::from_secs(1)).await;
+        })
+        .await;
+
+        server
+            .client()
+            .execute_request::<serde_json::Value>("/ListVectorBuckets", Some(serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_connection_resets_are_retried() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let server = MockServer::start(move |_| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                MockResponse::reset()
+            } else {
+                MockResponse::json(200, serde_json::json!({ "vectorBuckets": [] }))
             }
-            None => {
-                info!(
-                    "Bucket {} status is None, assuming in-progress",
-                    bucket_name
-                );
-                sleep(Duration::from_secs(1)).await;
+        })
+        .await;
+
+        server
+            .client()
+            .execute_request::<serde_json::Value>("/ListVectorBuckets", Some(serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+        // Each attempt is signed separately
+        let requests = server.requests();
+        assert_eq!(requests[0].body, requests[1].body);
+        assert!(requests[1].header("authorization").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_honoured() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let server = MockServer::start(move |_| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                MockResponse::json(
+                    429,
+                    serde_json::json!({
+                        "__type": "TooManyRequestsException",
+                        "message": "Slow down",
+                    }),
+                )
+                .header("Retry-After", "0.3")
+            } else {
+                MockResponse::json(200, serde_json::json!({ "vectorBuckets": [] }))
+            }
+        })
+        .await;
+
+        let client = S3VectorsClient::builder("us-east-1")
+            .endpoint(&server.url())
+            .credentials("AKIDEXAMPLE".to_string(), "secret".to_string(), None)
+            .backoff(Duration::from_millis(1), Duration::from_secs(1))
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        client
+            .execute_request::<serde_json::Value>("/ListVectorBuckets", Some(serde_json::json!({})))
+            .await
+            .unwrap();
+        // The first backoff is at most 1ms, so only Retry-After explains the wait
+        assert!(started.elapsed() >= Duration::from_millis(300));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_bounded_by_deadline() {
+        let server = MockServer::start(|_| {
+            MockResponse::json(
+                503,
+                serde_json::json!({
+                    "__type": "ServiceUnavailableException",
+                    "message": "Come back in a minute",
+                }),
+            )
+            .header("Retry-After", "60")
+        })
+        .await;
+
+        let client = S3VectorsClient::builder("us-east-1")
+            .endpoint(&server.url())
+            .credentials("AKIDEXAMPLE".to_string(), "secret".to_string(), None)
+            .backoff(Duration::from_millis(1), Duration::from_secs(120))
+            .retry_deadline(Duration::from_millis(200))
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        let err = client
+            .execute_request::<serde_json::Value>("/ListVectorBuckets", Some(serde_json::json!({})))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, S3VectorsError::ServiceUnavailable(_)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        // The first retry is sent when the deadline is reached, not after a minute
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_deadline_stops_retries() {
+        let server = MockServer::start(|_| {
+            MockResponse::json(
+                500,
+                serde_json::json!({
+                    "__type": "InternalServerException",
+                    "message": "boom",
+                }),
+            )
+            .delay(Duration::from_millis(50))
+        })
+        .await;
+
+        let client = S3VectorsClient::builder("us-east-1")
+            .endpoint(&server.url())
+            .credentials("AKIDEXAMPLE".to_string(), "secret".to_string(), None)
+            .max_retries(100)
+            .backoff(Duration::from_millis(20), Duration::from_millis(20))
+            .retry_deadline(Duration::from_millis(300))
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        let err = client
+            .execute_request::<serde_json::Value>("/ListVectorBuckets", Some(serde_json::json!({})))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, S3VectorsError::InternalServer(_)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+        let sent = server.requests().len();
+        assert!((2..=10).contains(&sent), "sent {sent} requests");
+    }
+
+    #[tokio::test]
+    async fn test_custom_retry_strategy() {
+        /// Never retries anything
+        #[derive(Debug)]
+        struct NoRetry;
+
+        impl crate::RetryStrategy for NoRetry {
+            fn retry_delay(
+                &self,
+                _attempt: u32,
+                _error: &S3VectorsError,
+                _retry_after: Option<Duration>,
+            ) -> Option<Duration> {
+                None
             }
         }
+
+        let server = MockServer::start(|_| MockResponse::reset()).await;
+        let client = S3VectorsClient::builder("us-east-1")
+            .endpoint(&server.url())
+            .credentials("AKIDEXAMPLE".to_string(), "secret".to_string(), None)
+            .retry_strategy(NoRetry)
+            .build()
+            .unwrap();
+
+        let err = client
+            .execute_request::<serde_json::Value>("/ListVectorBuckets", Some(serde_json::json!({})))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, S3VectorsError::RequestError(_)));
+        assert!(err.is_retryable());
+        assert_eq!(server.requests().len(), 1);
     }
 
-    Err(anyhow::anyhow!(
-        "Timeout waiting for bucket to become active"
-    ))
-}
+    fn test_vectors(count: usize, dimensions: usize) -> Vec<Vector> {
+        (0..count)
+            .map(|i| Vector {
+                key: format!("vec-{i}"),
+                data: VectorData {
+                    float32: vec![0.5; dimensions],
+                },
+                metadata: None,
+            })
+            .collect()
+    }
 
-async fn wait_for_index_active(
-    client: &S3VectorsClient,
-    bucket_name: &str,
-    index_name: &str,
-) -> Result<VectorIndex> {
-    info!("Waiting for index {} to become active", index_name);
+    fn sent_keys(request: &crate::test_support::RecordedRequest) -> Vec<String> {
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        body["vectors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|v| v["key"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_batch_put_splits_by_count_and_bytes_concurrently() {
+        let server = MockServer::start(|_| {
+            MockResponse::json(200, serde_json::json!({})).delay(Duration::from_millis(150))
+        })
+        .await;
+        let client = server.client();
+
+        let config = BatchPutConfig {
+            max_batch_vectors: 3,
+            concurrency: 4,
+            ..BatchPutConfig::default()
+        };
+        let started = Instant::now();
+        let report = batch_put_vectors_with(
+            &client,
+            "test-bucket",
+            "test-index",
+            test_vectors(10, 4),
+            4,
+            &config,
+        )
+        .await
+        .unwrap();
+        // Four batches of at most three, sent together rather than one by one
+        assert!(started.elapsed() < Duration::from_millis(450));
+        assert!(report.is_complete());
+        assert_eq!(report.succeeded.len(), 10);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests.iter().all(|r| sent_keys(r).len() <= 3));
+
+        // A byte limit that fits about two vectors per request
+        let one_vector = serde_json::to_vec(&test_vectors(1, 64)[0]).unwrap().len();
+        let config = BatchPutConfig {
+            max_batch_bytes: 100 + 2 * (one_vector + 1),
+            ..BatchPutConfig::default()
+        };
+        let report = batch_put_vectors_with(
+            &client,
+            "test-bucket",
+            "test-index",
+            test_vectors(6, 64),
+            64,
+            &config,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.succeeded.len(), 6);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 4 + 3);
+        assert!(requests[4..]
+            .iter()
+            .all(|r| r.body.len() <= config.max_batch_bytes));
+    }
 
-    // Give it a moment for the index to be created
-    sleep(Duration::from_secs(2)).await;
+    #[tokio::test]
+    async fn test_batch_put_reports_failed_keys() {
+        let throttled_once = Arc::new(AtomicUsize::new(0));
+        let counter = throttled_once.clone();
+        let server = MockServer::start(move |request| {
+            let keys = sent_keys(request);
+            if keys.contains(&"vec-0".to_string()) {
+                MockResponse::json(
+                    400,
+                    serde_json::json!({
+                        "__type": "ValidationException",
+                        "message": "Invalid metadata",
+                    }),
+                )
+            } else if keys.contains(&"vec-2".to_string())
+                && counter.fetch_add(1, Ordering::SeqCst) < 5
+            {
+                // Outlasts the client's own retries, but not the batch retry
+                MockResponse::json(
+                    503,
+                    serde_json::json!({
+                        "__type": "ServiceUnavailableException",
+                        "message": "Please retry",
+                    }),
+                )
+            } else {
+                MockResponse::json(200, serde_json::json!({}))
+            }
+        })
+        .await;
+        let client = server.client();
+
+        let mut vectors = test_vectors(6, 4);
+        vectors[5].data.float32.push(1.0); // wrong dimension
+        let config = BatchPutConfig {
+            max_batch_vectors: 2,
+            retry_backoff: Duration::from_millis(1),
+            ..BatchPutConfig::default()
+        };
+        let report =
+            batch_put_vectors_with(&client, "test-bucket", "test-index", vectors, 4, &config)
+                .await
+                .unwrap();
+
+        let mut succeeded = report.succeeded.clone();
+        succeeded.sort();
+        assert_eq!(succeeded, ["vec-2", "vec-3", "vec-4"]);
+
+        let mut failed: Vec<&str> = report.failed_keys().collect();
+        failed.sort();
+        assert_eq!(failed, ["vec-0", "vec-1", "vec-5"]);
+        assert!(report
+            .failed
+            .iter()
+            .any(|f| matches!(f.error, S3VectorsError::InvalidRequest { .. })));
+        assert!(report
+            .failed
+            .iter()
+            .any(|f| matches!(f.error, S3VectorsError::Validation(_))));
+        assert!(report.into_result().is_err());
+
+        assert!(
+            batch_put_vectors(&client, "Bad_Bucket", "test-index", vec![], 4)
+                .await
+                .is_err()
+        );
+
+        // Nothing written at all is an error, not an empty report
+        let err = batch_put_vectors(&client, "test-bucket", "test-index", test_vectors(2, 4), 4)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("All 2 vectors failed"));
+        assert!(matches!(
+            err.downcast_ref::<S3VectorsError>(),
+            Some(S3VectorsError::InvalidRequest { .. })
+        ));
+        let empty = batch_put_vectors(&client, "test-bucket", "test-index", vec![], 4)
+            .await
+            .unwrap();
+        assert!(empty.is_complete());
+    }
+
+    fn key_list(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("key-{i}")).collect()
+    }
 
-    // Try to describe the index - if it exists, consider it active
-    match client.describe_index(bucket_name, index_name).await {
-        Ok(index) => {
-            info!("Index {} is available", index_name);
-            Ok(index)
+    fn requested_keys(request: &crate::test_support::RecordedRequest) -> Vec<String> {
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        serde_json::from_value(body["keys"].clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_vectors_chunked_preserves_order_and_reports_missing() {
+        // Answers in reverse order and omits keys ending in 7
+        let server = MockServer::start(|request| {
+            let vectors: Vec<_> = requested_keys(request)
+                .into_iter()
+                .rev()
+                .filter(|k| !k.ends_with('7'))
+                .map(|k| serde_json::json!({ "key": k }))
+                .collect();
+            MockResponse::json(200, serde_json::json!({ "vectors": vectors }))
+        })
+        .await;
+        let client = server.client();
+
+        let keys = key_list(250);
+        let report = client
+            .get_vectors_chunked(GetVectorsRequest {
+                vector_bucket_name: "test-bucket".to_string(),
+                index_name: "test-index".to_string(),
+                keys: keys.clone(),
+                return_data: false,
+                return_metadata: true,
+            })
+            .await
+            .unwrap();
+
+        let expected: Vec<&String> = keys.iter().filter(|k| !k.ends_with('7')).collect();
+        let returned: Vec<&String> = report.vectors.iter().map(|v| &v.key).collect();
+        assert_eq!(returned, expected);
+        assert_eq!(report.missing_keys.len(), 25);
+        assert_eq!(report.missing_keys[0], "key-7");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|r| requested_keys(r).len() <= 100));
+
+        // A single request over the limit is rejected before sending
+        let err = client
+            .get_vectors(GetVectorsRequest {
+                vector_bucket_name: "test-bucket".to_string(),
+                index_name: "test-index".to_string(),
+                keys,
+                return_data: false,
+                return_metadata: false,
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, S3VectorsError::Validation(_)));
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_delete_vectors_chunked_reports_failed_keys() {
+        let server = MockServer::start(|request| {
+            if requested_keys(request).contains(&"key-600".to_string()) {
+                MockResponse::json(
+                    403,
+                    serde_json::json!({ "__type": "AccessDeniedException", "message": "denied" }),
+                )
+            } else {
+                MockResponse::json(200, serde_json::json!({}))
+            }
+        })
+        .await;
+        let client = server.client();
+
+        let report = client
+            .delete_vectors_chunked(DeleteVectorsRequest {
+                vector_bucket_name: "test-bucket".to_string(),
+                index_name: "test-index".to_string(),
+                keys: key_list(1200),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(server.requests().len(), 3);
+        assert_eq!(report.deleted.len(), 700);
+        assert_eq!(report.failed.len(), 1);
+        assert!(matches!(
+            report.failed[0].error,
+            S3VectorsError::AccessDenied(_)
+        ));
+        let failed: Vec<&str> = report.failed_keys().collect();
+        assert_eq!(failed.len(), 500);
+        assert_eq!(failed[0], "key-500");
+        assert!(!report.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_query_rejects_non_filterable_keys_before_sending() {
+        let server = MockServer::start(|request| {
+            if request.path == "/GetIndex" {
+                MockResponse::json(
+                    200,
+                    serde_json::json!({ "index": {
+                        "indexName": "test-index",
+                        "indexArn": "arn:aws:s3vectors:us-east-1:123456789012:bucket/test-bucket/index/test-index",
+                        "dimension": 2,
+                        "dataType": "float32",
+                        "distanceMetric": "cosine",
+                        "creationTime": 1.0,
+                        "metadataConfiguration": { "nonFilterableMetadataKeys": ["content"] },
+                    }}),
+                )
+            } else {
+                MockResponse::json(200, serde_json::json!({ "vectors": [] }))
+            }
+        })
+        .await;
+        let client = server.client();
+        let query = |filter: crate::Filter| QueryVectorsRequest {
+            vector_bucket_name: "test-bucket".to_string(),
+            index_name: "test-index".to_string(),
+            query_vector: QueryVector {
+                float32: vec![1.0, 0.0],
+            },
+            top_k: 3,
+            filter: Some(filter),
+            return_metadata: true,
+            return_distance: true,
+        };
+
+        let err = client
+            .query_vectors(query(crate::Filter::eq("content", "x")))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, S3VectorsError::Validation(m) if m.contains("/content")),
+            "{err}"
+        );
+        client
+            .query_vectors(query(crate::Filter::eq("genre", "scifi")))
+            .await
+            .unwrap();
+
+        // The index is described once, and the rejected query never sent
+        let paths: Vec<_> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, ["/GetIndex", "/QueryVectors"]);
+    }
+
+    #[tokio::test]
+    async fn test_query_caches_failure_to_describe_index() {
+        let server = MockServer::start(|request| {
+            if request.path == "/GetIndex" {
+                MockResponse::json(
+                    403,
+                    serde_json::json!({
+                        "__type": "AccessDeniedException",
+                        "message": "Not authorized to perform s3vectors:GetIndex",
+                    }),
+                )
+            } else {
+                MockResponse::json(200, serde_json::json!({ "vectors": [] }))
+            }
+        })
+        .await;
+        let client = server.client();
+        let request = QueryVectorsRequest {
+            vector_bucket_name: "test-bucket".to_string(),
+            index_name: "test-index".to_string(),
+            query_vector: QueryVector {
+                float32: vec![1.0, 0.0],
+            },
+            top_k: 3,
+            filter: Some(crate::Filter::eq("content", "x")),
+            return_metadata: true,
+            return_distance: true,
+        };
+
+        for _ in 0..3 {
+            client.query_vectors(request.clone()).await.unwrap();
         }
-        Err(e) => {
-            error!("Failed to describe index {}: {:?}", index_name, e);
-            Err(anyhow::anyhow!("Failed to verify index creation: {}", e))
+        // The service checks the filter; GetIndex is not retried per query
+        let paths: Vec<_> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(
+            paths,
+            [
+                "/GetIndex",
+                "/QueryVectors",
+                "/QueryVectors",
+                "/QueryVectors"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_bucket_and_index_checks_existing_schema() {
+        let emulator = crate::emulator::Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+        client.create_vector_bucket("schemas").await.unwrap();
+        client
+            .create_index(CreateIndexRequest {
+                vector_bucket_name: "schemas".to_string(),
+                index_name: "docs".to_string(),
+                data_type: DataType::Float32,
+                dimension: 768,
+                distance_metric: DistanceMetric::Cosine,
+                metadata_configuration: Some(MetadataConfiguration {
+                    non_filterable_metadata_keys: Some(vec!["source".into(), "content".into()]),
+                }),
+            })
+            .await
+            .unwrap();
+        let content_only = || {
+            Some(MetadataConfiguration {
+                non_filterable_metadata_keys: Some(vec!["content".to_string()]),
+            })
+        };
+
+        // Same settings, keys in another order: reused as is
+        let same = Some(MetadataConfiguration {
+            non_filterable_metadata_keys: Some(vec!["content".into(), "source".into()]),
+        });
+        let (_, index) = create_bucket_and_index_with(
+            &client,
+            "schemas",
+            "docs",
+            768,
+            DistanceMetric::Cosine,
+            &CreateIndexOptions {
+                metadata_configuration: same,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(index.dimension, 768);
+
+        let err = create_bucket_and_index_with(
+            &client,
+            "schemas",
+            "docs",
+            384,
+            DistanceMetric::Euclidean,
+            &CreateIndexOptions {
+                metadata_configuration: content_only(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+        let mismatch = err.downcast_ref::<IndexMismatchError>().unwrap();
+        assert_eq!(
+            mismatch.mismatches,
+            [
+                IndexSettingMismatch {
+                    field: "dimension",
+                    existing: "768".to_string(),
+                    requested: "384".to_string(),
+                },
+                IndexSettingMismatch {
+                    field: "distanceMetric",
+                    existing: "cosine".to_string(),
+                    requested: "euclidean".to_string(),
+                },
+                IndexSettingMismatch {
+                    field: "nonFilterableMetadataKeys",
+                    existing: "[content, source]".to_string(),
+                    requested: "[content]".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            client
+                .describe_index("schemas", "docs")
+                .await
+                .unwrap()
+                .dimension,
+            768
+        );
+
+        let (_, index) = create_bucket_and_index_with(
+            &client,
+            "schemas",
+            "docs",
+            384,
+            DistanceMetric::Euclidean,
+            &CreateIndexOptions {
+                metadata_configuration: content_only(),
+                recreate_on_mismatch: true,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(index.dimension, 384);
+        assert_eq!(index.distance_metric, DistanceMetric::Euclidean);
+        assert!(index_mismatches(
+            &index,
+            &CreateIndexRequest {
+                vector_bucket_name: "schemas".to_string(),
+                index_name: "docs".to_string(),
+                data_type: DataType::Float32,
+                dimension: 384,
+                distance_metric: DistanceMetric::Euclidean,
+                metadata_configuration: content_only(),
+            }
+        )
+        .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create_bucket_and_index_reuses_index_without_metadata_config() {
+        let emulator = crate::emulator::Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+        // An index from before non-filterable keys were requested
+        let (_, created) =
+            create_bucket_and_index(&client, "legacy", "docs", 384, DistanceMetric::Cosine)
+                .await
+                .unwrap();
+        assert!(created.metadata_configuration.is_none());
+
+        let (_, index) = create_bucket_and_index_with(
+            &client,
+            "legacy",
+            "docs",
+            384,
+            DistanceMetric::Cosine,
+            &CreateIndexOptions {
+                metadata_configuration: Some(MetadataConfiguration {
+                    non_filterable_metadata_keys: Some(vec!["content".to_string()]),
+                }),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(index.creation_time, created.creation_time);
+        assert!(index.metadata_configuration.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_bucket_with_encryption_and_tags() {
+        let emulator = crate::emulator::Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+        let key = "arn:aws:kms:us-east-1:111122223333:key/1234abcd-12ab-34cd-56ef-1234567890ab";
+        let request = |encryption| CreateVectorBucketRequest {
+            vector_bucket_name: "encrypted".to_string(),
+            encryption_configuration: Some(encryption),
+            tags: [("team".to_string(), "search".to_string())].into(),
+        };
+
+        let bucket = client
+            .create_vector_bucket_with(request(EncryptionConfiguration::sse_kms(key)))
+            .await
+            .unwrap();
+        assert_eq!(
+            bucket.encryption_configuration,
+            Some(EncryptionConfiguration::sse_kms(key))
+        );
+
+        // Rejected before anything is sent
+        for encryption in [
+            EncryptionConfiguration::sse_kms("alias/vectors"),
+            EncryptionConfiguration {
+                kms_key_arn: None,
+                sse_type: Some("aws:kms".to_string()),
+            },
+            EncryptionConfiguration {
+                kms_key_arn: Some(key.to_string()),
+                sse_type: Some("AES256".to_string()),
+            },
+        ] {
+            let err = client
+                .create_vector_bucket_with(request(encryption))
+                .await
+                .unwrap_err();
+            assert!(matches!(err, S3VectorsError::Validation(_)), "{err:?}");
         }
+        let err = client
+            .create_vector_bucket_with(CreateVectorBucketRequest {
+                tags: [("aws:owner".to_string(), "me".to_string())].into(),
+                ..request(EncryptionConfiguration::sse_s3())
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, S3VectorsError::Validation(_)), "{err:?}");
     }
 }
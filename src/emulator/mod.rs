@@ -0,0 +1,667 @@
+//! In-process emulator of the S3 Vectors API for offline development and
+//! tests.
+//!
+//! Implements every operation the client calls: buckets, indexes, vectors
+//! (including segmented listing and exact cosine/euclidean queries with
+//! metadata filters), bucket policies and tags, with the service's error
+//! codes and pagination. Requests must be signed, but signatures are not
+//! verified.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use s3_vectors::emulator::Emulator;
+//!
+//! let emulator = Emulator::new().spawn().await?;
+//! let client = emulator.client()?;
+//! client.create_vector_bucket("my-bucket").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod state;
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use axum::extract;
+use axum::http::{header, HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::Router;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::{debug, info};
+
+use self::state::{ApiError, State};
+use crate::S3VectorsClient;
+
+const REGION: &str = "us-east-1";
+
+/// An emulated S3 Vectors service, cheap to clone and shared between
+/// connections
+#[derive(Clone, Debug)]
+pub struct Emulator {
+    state: Arc<Mutex<State>>,
+    state_file: Option<PathBuf>,
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Emulator {
+    /// An emulator that keeps all state in memory
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(State::new(REGION))),
+            state_file: None,
+        }
+    }
+
+    /// An emulator persisted to `path`: existing state is loaded and every
+    /// change is written back
+    pub fn with_state_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let state = if path.exists() {
+            let content = std::fs::read(path)
+                .with_context(|| format!("Failed to read state file {}", path.display()))?;
+            serde_json::from_slice(&content)
+                .with_context(|| format!("Invalid state file {}", path.display()))?
+        } else {
+            State::new(REGION)
+        };
+        Ok(Self {
+            state: Arc::new(Mutex::new(state)),
+            state_file: Some(path.to_path_buf()),
+        })
+    }
+
+    /// The HTTP routes of the emulated service
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/{operation}", post(handle_request))
+            .route(
+                "/tags/{resource_arn}",
+                get(handle_tags_request)
+                    .post(handle_tags_request)
+                    .delete(handle_tags_request),
+            )
+            .with_state(self)
+    }
+
+    /// Serve requests on `addr` until the process exits
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let listener = TcpListener::bind(addr)
+            .await
+            .with_context(|| format!("Failed to bind {addr}"))?;
+        info!(
+            "S3 Vectors emulator listening on {}",
+            listener.local_addr()?
+        );
+        axum::serve(listener, self.router())
+            .await
+            .context("Emulator server failed")
+    }
+
+    /// Serve requests on a random local port in the background
+    pub async fn spawn(self) -> Result<EmulatorHandle> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let router = self.router();
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                tracing::error!("Emulator server failed: {}", e);
+            }
+        });
+        Ok(EmulatorHandle { addr, task })
+    }
+
+    fn execute(&self, operation: &str, body: &Value) -> Result<Value, ApiError> {
+        let mut state = self.state.lock().unwrap();
+        let (response, changed) = state.handle(operation, body)?;
+        if changed {
+            if let Some(path) = &self.state_file {
+                save(&state, path)
+                    .map_err(|e| ApiError::new(500, "InternalServerException", format!("{e:#}")))?;
+            }
+        }
+        Ok(response)
+    }
+}
+
+/// Write the state next to `path` and rename it into place, so a crash never
+/// leaves a truncated file
+fn save(state: &State, path: &Path) -> Result<()> {
+    let temp = path.with_extension("tmp");
+    std::fs::write(&temp, serde_json::to_vec_pretty(state)?)
+        .with_context(|| format!("Failed to write {}", temp.display()))?;
+    std::fs::rename(&temp, path).with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+async fn handle_request(
+    extract::State(emulator): extract::State<Emulator>,
+    extract::Path(operation): extract::Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let body = parse_body(&body);
+    respond(&emulator, &operation, &headers, body)
+}
+
+/// `/tags/{resourceArn}`: GET lists, POST adds and DELETE (with `tagKeys`
+/// query parameters) removes tags
+async fn handle_tags_request(
+    extract::State(emulator): extract::State<Emulator>,
+    extract::Path(resource_arn): extract::Path<String>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let (operation, body) = if method == Method::POST {
+        let body = parse_body(&body).map(|mut body| {
+            body["resourceArn"] = json!(resource_arn);
+            body
+        });
+        ("TagResource", body)
+    } else if method == Method::DELETE {
+        let tag_keys: Vec<String> =
+            url::form_urlencoded::parse(uri.query().unwrap_or_default().as_bytes())
+                .filter(|(name, _)| name == "tagKeys")
+                .map(|(_, value)| value.into_owned())
+                .collect();
+        let body = json!({ "resourceArn": resource_arn, "tagKeys": tag_keys });
+        ("UntagResource", Ok(body))
+    } else {
+        let body = json!({ "resourceArn": resource_arn });
+        ("ListTagsForResource", Ok(body))
+    };
+    respond(&emulator, operation, &headers, body)
+}
+
+fn parse_body(body: &Bytes) -> Result<Value, ApiError> {
+    if body.is_empty() {
+        Ok(Value::Object(Default::default()))
+    } else {
+        serde_json::from_slice(body)
+            .map_err(|e| ApiError::validation(format!("Request body is not valid JSON: {e}")))
+    }
+}
+
+fn respond(
+    emulator: &Emulator,
+    operation: &str,
+    headers: &HeaderMap,
+    body: Result<Value, ApiError>,
+) -> Response {
+    let request_id = format!("{:016X}", fastrand::u64(..));
+    debug!("Emulating {} ({})", operation, request_id);
+
+    let result = if !headers.contains_key(header::AUTHORIZATION) {
+        Err(ApiError::new(
+            403,
+            "AccessDeniedException",
+            "Missing Authentication Token",
+        ))
+    } else {
+        body.and_then(|body| emulator.execute(operation, &body))
+    };
+
+    let (status, error_type, body) = match result {
+        Ok(body) => (StatusCode::OK, None, body),
+        Err(e) => (
+            StatusCode::from_u16(e.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            Some(e.code),
+            e.body(),
+        ),
+    };
+    let mut response = (status, axum::Json(body)).into_response();
+    let headers = response.headers_mut();
+    headers.insert("x-amz-request-id", request_id.parse().unwrap());
+    if let Some(code) = error_type {
+        headers.insert("x-amzn-errortype", code.parse().unwrap());
+    }
+    response
+}
+
+/// A running background emulator, shut down when dropped
+#[derive(Debug)]
+pub struct EmulatorHandle {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl EmulatorHandle {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Endpoint URL to point clients at
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A client for this emulator with dummy credentials
+    pub fn client(&self) -> Result<S3VectorsClient> {
+        S3VectorsClient::builder(REGION)
+            .endpoint(&self.url())
+            .credentials("AKIDEMULATOR".to_string(), "emulator".to_string(), None)
+            .build()
+    }
+}
+
+impl Drop for EmulatorHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::S3VectorsError;
+    use crate::types::*;
+    use crate::PaginationConfig;
+    use futures_util::TryStreamExt;
+
+    fn index_request(bucket: &str, index: &str, metric: DistanceMetric) -> CreateIndexRequest {
+        CreateIndexRequest {
+            vector_bucket_name: bucket.to_string(),
+            index_name: index.to_string(),
+            dimension: 2,
+            data_type: DataType::Float32,
+            distance_metric: metric,
+            metadata_configuration: Some(MetadataConfiguration {
+                non_filterable_metadata_keys: Some(vec!["text".to_string()]),
+            }),
+        }
+    }
+
+    fn vector(key: &str, data: [f32; 2], metadata: Value) -> Vector {
+        Vector {
+            key: key.to_string(),
+            data: VectorData {
+                float32: data.to_vec(),
+            },
+            metadata: Some(metadata),
+        }
+    }
+
+    fn list_request(segment: Option<(u32, u32)>) -> ListVectorsRequest {
+        ListVectorsRequest {
+            vector_bucket_name: "docs".to_string(),
+            index_name: "embeddings".to_string(),
+            max_results: None,
+            next_token: None,
+            segment_count: segment.map(|(count, _)| count),
+            segment_index: segment.map(|(_, index)| index),
+            return_data: true,
+            return_metadata: false,
+        }
+    }
+
+    fn query(filter: Option<Value>) -> QueryVectorsRequest {
+        QueryVectorsRequest {
+            vector_bucket_name: "docs".to_string(),
+            index_name: "embeddings".to_string(),
+            query_vector: QueryVector {
+                float32: vec![1.0, 0.0],
+            },
+            top_k: 2,
+            filter: filter.map(|f| crate::Filter::from_json(&f).unwrap()),
+            return_metadata: true,
+            return_distance: true,
+        }
+    }
+
+    fn code(error: S3VectorsError) -> String {
+        error.code().unwrap_or_default().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_emulator_lifecycle() {
+        let emulator = Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+
+        let bucket = client.create_vector_bucket("docs").await.unwrap();
+        assert_eq!(
+            bucket.vector_bucket_arn,
+            "arn:aws:s3vectors:us-east-1:123456789012:bucket/docs"
+        );
+        let err = client.create_vector_bucket("docs").await.unwrap_err();
+        assert!(matches!(err, S3VectorsError::AlreadyExists(_)));
+
+        client
+            .create_index(index_request("docs", "embeddings", DistanceMetric::Cosine))
+            .await
+            .unwrap();
+        let index = client.get_index("docs", "embeddings").await.unwrap().index;
+        assert_eq!(index.dimension, 2);
+        assert_eq!(index.distance_metric, DistanceMetric::Cosine);
+
+        let vectors = vec![
+            vector(
+                "a",
+                [1.0, 0.0],
+                json!({"genre": "scifi", "year": 2021, "text": "x"}),
+            ),
+            vector("b", [0.8, 0.6], json!({"genre": "drama", "year": 2019})),
+            vector("c", [0.0, 1.0], json!({"genre": "scifi", "year": 2015})),
+        ];
+        client
+            .put_vectors(PutVectorsRequest {
+                vector_bucket_name: "docs".to_string(),
+                index_name: "embeddings".to_string(),
+                vectors,
+            })
+            .await
+            .unwrap();
+
+        let matches = client.query_vectors(query(None)).await.unwrap().vectors;
+        let keys: Vec<_> = matches.iter().map(|m| m.key.as_str()).collect();
+        assert_eq!(keys, ["a", "b"]);
+        assert!(matches[0].distance.unwrap().abs() < 1e-6);
+        assert!((matches[1].distance.unwrap() - 0.2).abs() < 1e-6);
+
+        let filtered = client
+            .query_vectors(query(Some(
+                json!({"genre": "scifi", "year": {"$lt": 2020}}),
+            )))
+            .await
+            .unwrap();
+        assert_eq!(filtered.vectors.len(), 1);
+        assert_eq!(filtered.vectors[0].key, "c");
+        assert_eq!(filtered.vectors[0].metadata.as_ref().unwrap()["year"], 2015);
+
+        let err = client
+            .query_vectors(query(Some(json!({"text": "x"}))))
+            .await
+            .unwrap_err();
+        // Caught by the client, which described the index
+        assert!(matches!(err, S3VectorsError::Validation(_)));
+
+        let report = client
+            .get_vectors_chunked(GetVectorsRequest {
+                vector_bucket_name: "docs".to_string(),
+                index_name: "embeddings".to_string(),
+                keys: vec!["c".to_string(), "missing".to_string(), "a".to_string()],
+                return_data: true,
+                return_metadata: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(report.missing_keys, ["missing"]);
+        assert_eq!(report.vectors[0].key, "c");
+        assert_eq!(report.vectors[0].data.as_ref().unwrap().float32, [0.0, 1.0]);
+        assert!(report.vectors[0].metadata.is_none());
+
+        client
+            .delete_vectors(DeleteVectorsRequest {
+                vector_bucket_name: "docs".to_string(),
+                index_name: "embeddings".to_string(),
+                keys: vec!["b".to_string()],
+            })
+            .await
+            .unwrap();
+        let remaining = client.list_vectors(list_request(None)).await.unwrap();
+        let keys: Vec<_> = remaining.vectors.iter().map(|v| v.key.as_str()).collect();
+        assert_eq!(keys, ["a", "c"]);
+
+        let err = client.delete_vector_bucket("docs").await.unwrap_err();
+        assert_eq!(code(err), "ConflictException");
+        client.delete_index("docs", "embeddings").await.unwrap();
+        client.delete_vector_bucket("docs").await.unwrap();
+        let err = client.describe_vector_bucket("docs").await.unwrap_err();
+        assert!(matches!(err, S3VectorsError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_emulator_pagination_and_segments() {
+        let emulator = Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+        client.create_vector_bucket("docs").await.unwrap();
+        client
+            .create_index(index_request(
+                "docs",
+                "embeddings",
+                DistanceMetric::Euclidean,
+            ))
+            .await
+            .unwrap();
+        let vectors: Vec<Vector> = (0..25)
+            .map(|i| vector(&format!("key-{i:02}"), [i as f32, 0.0], json!({})))
+            .collect();
+        crate::batch_put_vectors(&client, "docs", "embeddings", vectors, 2)
+            .await
+            .unwrap()
+            .into_result()
+            .unwrap();
+
+        let listed: Vec<ListOutputVector> = client
+            .list_vectors_paginated(
+                list_request(None),
+                PaginationConfig::default().page_size(10),
+            )
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 25);
+        assert_eq!(listed[24].key, "key-24");
+        assert_eq!(listed[3].data.as_ref().unwrap().float32, [3.0, 0.0]);
+
+        let mut scanned: Vec<String> = client
+            .scan_vectors(
+                list_request(None),
+                4,
+                PaginationConfig::default().page_size(3),
+            )
+            .map_ok(|v| v.key)
+            .try_collect()
+            .await
+            .unwrap();
+        scanned.sort();
+        assert_eq!(
+            scanned,
+            listed.iter().map(|v| v.key.clone()).collect::<Vec<_>>()
+        );
+
+        for i in 0..3 {
+            client
+                .create_vector_bucket(&format!("extra-{i}"))
+                .await
+                .unwrap();
+        }
+        let buckets: Vec<VectorBucket> = client
+            .list_vector_buckets_paginated(
+                Some("extra-".to_string()),
+                PaginationConfig::default().page_size(2),
+            )
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 3);
+
+        let err = client
+            .list_vectors(ListVectorsRequest {
+                max_results: Some(1001),
+                ..list_request(None)
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), "ValidationException");
+    }
+
+    #[tokio::test]
+    async fn test_emulator_errors_and_policies() {
+        let emulator = Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+
+        let err = client
+            .create_index(index_request("nope", "embeddings", DistanceMetric::Cosine))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, S3VectorsError::NotFound(_)));
+
+        client.create_vector_bucket("docs").await.unwrap();
+        client
+            .create_index(index_request("docs", "embeddings", DistanceMetric::Cosine))
+            .await
+            .unwrap();
+        let err = client
+            .put_vectors(PutVectorsRequest {
+                vector_bucket_name: "docs".to_string(),
+                index_name: "embeddings".to_string(),
+                vectors: vec![
+                    vector("a", [1.0, 0.0], json!({})),
+                    Vector {
+                        key: "b".to_string(),
+                        data: VectorData { float32: vec![1.0] },
+                        metadata: None,
+                    },
+                ],
+            })
+            .await
+            .unwrap_err();
+        match err {
+            S3VectorsError::InvalidRequest { fields, .. } => {
+                assert_eq!(fields[0].path, "/vectors/1/data");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+
+        let err = client.get_vector_bucket_policy("docs").await.unwrap_err();
+        assert!(matches!(err, S3VectorsError::NotFound(_)));
+        let policy = r#"{"Version":"2012-10-17","Statement":[]}"#;
+        client
+            .put_vector_bucket_policy("docs", policy)
+            .await
+            .unwrap();
+        assert_eq!(
+            client.get_vector_bucket_policy("docs").await.unwrap(),
+            policy
+        );
+        client.delete_vector_bucket_policy("docs").await.unwrap();
+        assert!(client.get_vector_bucket_policy("docs").await.is_err());
+
+        // Unsigned requests are rejected
+        let response = reqwest::Client::new()
+            .post(format!("{}/ListVectorBuckets", emulator.url()))
+            .json(&json!({}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+        assert_eq!(
+            response.headers()["x-amzn-errortype"],
+            "AccessDeniedException"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_emulator_tags() {
+        let emulator = Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+        let bucket = client
+            .create_vector_bucket_with(CreateVectorBucketRequest {
+                vector_bucket_name: "docs".to_string(),
+                tags: [("team".to_string(), "search".to_string())].into(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        client
+            .create_index(index_request("docs", "embeddings", DistanceMetric::Cosine))
+            .await
+            .unwrap();
+        let index_arn = format!("{}/index/embeddings", bucket.vector_bucket_arn);
+
+        let tags = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<std::collections::BTreeMap<_, _>>()
+        };
+        client
+            .tag_resource(
+                &bucket.vector_bucket_arn,
+                tags(&[("cost center", "ml/42"), ("team", "retrieval")]),
+            )
+            .await
+            .unwrap();
+        client
+            .tag_resource(&index_arn, tags(&[("env", "prod")]))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            client
+                .list_tags_for_resource(&bucket.vector_bucket_arn)
+                .await
+                .unwrap(),
+            tags(&[("cost center", "ml/42"), ("team", "retrieval")])
+        );
+        assert_eq!(
+            client.list_tags_for_resource(&index_arn).await.unwrap(),
+            tags(&[("env", "prod")])
+        );
+
+        client
+            .untag_resource(
+                &bucket.vector_bucket_arn,
+                &["cost center".to_string(), "absent".to_string()],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            client
+                .list_tags_for_resource(&bucket.vector_bucket_arn)
+                .await
+                .unwrap(),
+            tags(&[("team", "retrieval")])
+        );
+
+        let missing = format!("{}/index/missing", bucket.vector_bucket_arn);
+        let err = client.list_tags_for_resource(&missing).await.unwrap_err();
+        assert!(matches!(err, S3VectorsError::NotFound(_)), "{err:?}");
+        let err = client
+            .tag_resource("docs", tags(&[("a", "b")]))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, S3VectorsError::Validation(_)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_emulator_persists_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        {
+            let emulator = Emulator::with_state_file(&path)
+                .unwrap()
+                .spawn()
+                .await
+                .unwrap();
+            let client = emulator.client().unwrap();
+            client.create_vector_bucket("docs").await.unwrap();
+            client
+                .create_index(index_request("docs", "embeddings", DistanceMetric::Cosine))
+                .await
+                .unwrap();
+        }
+
+        let emulator = Emulator::with_state_file(&path)
+            .unwrap()
+            .spawn()
+            .await
+            .unwrap();
+        let client = emulator.client().unwrap();
+        let index = client.describe_index("docs", "embeddings").await.unwrap();
+        assert_eq!(index.dimension, 2);
+    }
+}
@@ -0,0 +1,986 @@
+//! Emulated service state and the operations on it.
+//!
+//! Every operation takes the JSON request body and returns the JSON response
+//! body, mirroring the service's request and response shapes.
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::filter::Filter;
+use crate::types::{DataType, DistanceMetric, MetadataConfiguration, VectorData};
+use crate::validation::{
+    validate_bucket_name, validate_index_name, validate_kms_key_arn, validate_resource_arn,
+    validate_tags, validate_top_k,
+};
+
+const ACCOUNT_ID: &str = "123456789012";
+const MAX_METADATA_BYTES: usize = 40 * 1024;
+const MAX_KEY_LENGTH: usize = 1024;
+const MAX_NON_FILTERABLE_KEYS: usize = 10;
+
+/// A service error response
+#[derive(Debug)]
+pub(crate) struct ApiError {
+    pub status: u16,
+    pub code: &'static str,
+    pub message: String,
+    /// `fieldList` entries of a `ValidationException` as (path, message)
+    pub fields: Vec<(String, String)>,
+}
+
+impl ApiError {
+    pub fn new(status: u16, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(400, "ValidationException", message)
+    }
+
+    fn invalid_field(path: &str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            fields: vec![(path.to_string(), message.clone())],
+            ..Self::validation(message)
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self::new(404, "NotFoundException", message)
+    }
+
+    fn conflict(message: impl Into<String>) -> Self {
+        Self::new(409, "ConflictException", message)
+    }
+
+    pub fn body(&self) -> Value {
+        let mut body = json!({ "__type": self.code, "message": self.message });
+        if !self.fields.is_empty() {
+            body["fieldList"] = self
+                .fields
+                .iter()
+                .map(|(path, message)| json!({ "path": path, "message": message }))
+                .collect();
+        }
+        body
+    }
+}
+
+type OpResult = Result<Value, ApiError>;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct State {
+    region: String,
+    buckets: BTreeMap<String, Bucket>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Bucket {
+    arn: String,
+    creation_time: f64,
+    encryption_configuration: Value,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    policy: Option<String>,
+    indexes: BTreeMap<String, Index>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Index {
+    arn: String,
+    creation_time: f64,
+    data_type: DataType,
+    dimension: u32,
+    distance_metric: DistanceMetric,
+    metadata_configuration: Option<MetadataConfiguration>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    vectors: BTreeMap<String, StoredVector>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredVector {
+    data: Vec<f32>,
+    metadata: Option<Value>,
+}
+
+impl Index {
+    fn non_filterable_keys(&self) -> &[String] {
+        self.metadata_configuration
+            .as_ref()
+            .and_then(|c| c.non_filterable_metadata_keys.as_deref())
+            .unwrap_or_default()
+    }
+
+    fn output_vector(&self, key: &str, return_data: bool, return_metadata: bool) -> Value {
+        let stored = &self.vectors[key];
+        let mut vector = json!({ "key": key });
+        if return_data {
+            vector["data"] = json!(VectorData {
+                float32: stored.data.clone()
+            });
+        }
+        if let (true, Some(metadata)) = (return_metadata, &stored.metadata) {
+            vector["metadata"] = metadata.clone();
+        }
+        vector
+    }
+}
+
+// Request shapes. Buckets and indexes can be addressed by name or ARN.
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BucketTarget {
+    vector_bucket_name: Option<String>,
+    vector_bucket_arn: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexTarget {
+    vector_bucket_name: Option<String>,
+    index_name: Option<String>,
+    index_arn: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResourceTarget {
+    resource_arn: String,
+}
+
+#[derive(Deserialize)]
+struct TagResource {
+    tags: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UntagResource {
+    tag_keys: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateVectorBucket {
+    vector_bucket_name: String,
+    encryption_configuration: Option<Value>,
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListRequest {
+    max_results: Option<u32>,
+    next_token: Option<String>,
+    prefix: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateIndex {
+    vector_bucket_name: Option<String>,
+    vector_bucket_arn: Option<String>,
+    index_name: String,
+    data_type: DataType,
+    dimension: u32,
+    distance_metric: DistanceMetric,
+    metadata_configuration: Option<MetadataConfiguration>,
+}
+
+#[derive(Deserialize)]
+struct InputVector {
+    key: String,
+    data: VectorData,
+    metadata: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct PutVectors {
+    vectors: Vec<InputVector>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetVectors {
+    keys: Vec<String>,
+    #[serde(default)]
+    return_data: bool,
+    #[serde(default)]
+    return_metadata: bool,
+}
+
+#[derive(Deserialize)]
+struct DeleteVectors {
+    keys: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListVectors {
+    max_results: Option<u32>,
+    next_token: Option<String>,
+    segment_count: Option<u32>,
+    segment_index: Option<u32>,
+    #[serde(default)]
+    return_data: bool,
+    #[serde(default)]
+    return_metadata: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryVectors {
+    query_vector: VectorData,
+    top_k: u32,
+    filter: Option<Value>,
+    #[serde(default)]
+    return_metadata: bool,
+    #[serde(default)]
+    return_distance: bool,
+}
+
+#[derive(Deserialize)]
+struct PutPolicy {
+    policy: String,
+}
+
+fn parse<T: DeserializeOwned>(body: &Value) -> Result<T, ApiError> {
+    serde_json::from_value(body.clone())
+        .map_err(|e| ApiError::validation(format!("Invalid request body: {e}")))
+}
+
+fn now() -> f64 {
+    chrono::Utc::now().timestamp_millis() as f64 / 1000.0
+}
+
+/// Page through `keys` (in order) after `next_token`
+fn page<'a>(
+    keys: impl Iterator<Item = &'a String>,
+    max_results: Option<u32>,
+    next_token: Option<&str>,
+    limit: u32,
+) -> Result<(Vec<&'a String>, Option<String>), ApiError> {
+    let max_results = max_results.unwrap_or(limit);
+    if max_results == 0 || max_results > limit {
+        return Err(ApiError::invalid_field(
+            "/maxResults",
+            format!("maxResults must be between 1 and {limit}"),
+        ));
+    }
+    let after = next_token.map(decode_token).transpose()?;
+
+    let mut items: Vec<&String> = keys
+        .filter(|key| {
+            after
+                .as_ref()
+                .is_none_or(|after| key.as_str() > after.as_str())
+        })
+        .take(max_results as usize + 1)
+        .collect();
+    let next_token = if items.len() > max_results as usize {
+        items.truncate(max_results as usize);
+        items.last().map(|key| encode_token(key))
+    } else {
+        None
+    };
+    Ok((items, next_token))
+}
+
+/// Tokens are the hex-encoded last key of the previous page
+fn encode_token(key: &str) -> String {
+    key.bytes().map(|b| format!("{b:02x}")).collect()
+}
+
+fn decode_token(token: &str) -> Result<String, ApiError> {
+    let invalid = || ApiError::invalid_field("/nextToken", "Invalid nextToken");
+    if !token.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let bytes = (0..token.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(token.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Stable segment assignment (FNV-1a) so a segmented scan sees every key once
+fn segment_of(key: &str, segment_count: u32) -> u32 {
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    (hash % segment_count as u64) as u32
+}
+
+fn distance(metric: &DistanceMetric, a: &[f32], b: &[f32]) -> f32 {
+    match metric {
+        DistanceMetric::Euclidean => a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f32>()
+            .sqrt(),
+        DistanceMetric::Cosine => {
+            let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+            let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+            let norms = norm(a) * norm(b);
+            if norms == 0.0 {
+                1.0
+            } else {
+                1.0 - dot / norms
+            }
+        }
+    }
+}
+
+fn check_vector(path: &str, data: &[f32], dimension: u32) -> Result<(), ApiError> {
+    if data.len() != dimension as usize {
+        return Err(ApiError::invalid_field(
+            path,
+            format!(
+                "Vector dimension {} does not match index dimension {dimension}",
+                data.len()
+            ),
+        ));
+    }
+    if data.iter().any(|x| !x.is_finite()) {
+        return Err(ApiError::invalid_field(
+            path,
+            "Vector values must be finite",
+        ));
+    }
+    Ok(())
+}
+
+fn check_key_count(keys: &[String], limit: usize) -> Result<(), ApiError> {
+    if keys.is_empty() || keys.len() > limit {
+        return Err(ApiError::invalid_field(
+            "/keys",
+            format!("Between 1 and {limit} keys must be specified"),
+        ));
+    }
+    Ok(())
+}
+
+impl State {
+    pub fn new(region: &str) -> Self {
+        Self {
+            region: region.to_string(),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Run `operation`; returns the response body and whether state changed
+    pub fn handle(&mut self, operation: &str, body: &Value) -> Result<(Value, bool), ApiError> {
+        let read = |result: OpResult| result.map(|value| (value, false));
+        let write = |result: OpResult| result.map(|value| (value, true));
+        match operation {
+            "CreateVectorBucket" => write(self.create_vector_bucket(body)),
+            "GetVectorBucket" => read(self.get_vector_bucket(body)),
+            "ListVectorBuckets" => read(self.list_vector_buckets(body)),
+            "DeleteVectorBucket" => write(self.delete_vector_bucket(body)),
+            "CreateIndex" => write(self.create_index(body)),
+            "GetIndex" => read(self.get_index(body)),
+            "ListIndexes" => read(self.list_indexes(body)),
+            "DeleteIndex" => write(self.delete_index(body)),
+            "PutVectors" => write(self.put_vectors(body)),
+            "GetVectors" => read(self.get_vectors(body)),
+            "ListVectors" => read(self.list_vectors(body)),
+            "DeleteVectors" => write(self.delete_vectors(body)),
+            "QueryVectors" => read(self.query_vectors(body)),
+            "PutVectorBucketPolicy" => write(self.put_vector_bucket_policy(body)),
+            "GetVectorBucketPolicy" => read(self.get_vector_bucket_policy(body)),
+            "DeleteVectorBucketPolicy" => write(self.delete_vector_bucket_policy(body)),
+            "TagResource" => write(self.tag_resource(body)),
+            "UntagResource" => write(self.untag_resource(body)),
+            "ListTagsForResource" => read(self.list_tags_for_resource(body)),
+            _ => Err(ApiError::new(
+                404,
+                "UnknownOperationException",
+                format!("Unknown operation {operation}"),
+            )),
+        }
+    }
+
+    fn bucket_arn(&self, name: &str) -> String {
+        format!(
+            "arn:aws:s3vectors:{}:{ACCOUNT_ID}:bucket/{name}",
+            self.region
+        )
+    }
+
+    /// Resolve a bucket name from `vectorBucketName` or `vectorBucketArn`
+    fn bucket_name(name: Option<&String>, arn: Option<&String>) -> Result<String, ApiError> {
+        match (name, arn) {
+            (Some(name), None) => Ok(name.clone()),
+            (None, Some(arn)) => arn
+                .split_once(":bucket/")
+                .map(|(_, name)| name.to_string())
+                .filter(|name| !name.is_empty() && !name.contains('/'))
+                .ok_or_else(|| {
+                    ApiError::invalid_field("/vectorBucketArn", "Invalid vector bucket ARN")
+                }),
+            _ => Err(ApiError::validation(
+                "Exactly one of vectorBucketName and vectorBucketArn must be specified",
+            )),
+        }
+    }
+
+    fn bucket(&self, body: &Value) -> Result<(String, &Bucket), ApiError> {
+        let target: BucketTarget = parse(body)?;
+        let name = Self::bucket_name(
+            target.vector_bucket_name.as_ref(),
+            target.vector_bucket_arn.as_ref(),
+        )?;
+        let bucket = self
+            .buckets
+            .get(&name)
+            .ok_or_else(|| ApiError::not_found(format!("Vector bucket {name} not found")))?;
+        Ok((name, bucket))
+    }
+
+    fn bucket_mut(&mut self, body: &Value) -> Result<&mut Bucket, ApiError> {
+        let (name, _) = self.bucket(body)?;
+        Ok(self.buckets.get_mut(&name).expect("bucket exists"))
+    }
+
+    /// Resolve `(bucket, index)` names from names or `indexArn`
+    fn index_names(body: &Value) -> Result<(String, String), ApiError> {
+        let target: IndexTarget = parse(body)?;
+        match (
+            target.vector_bucket_name,
+            target.index_name,
+            target.index_arn,
+        ) {
+            (Some(bucket), Some(index), None) => Ok((bucket, index)),
+            (None, None, Some(arn)) => arn
+                .split_once(":bucket/")
+                .and_then(|(_, rest)| rest.split_once("/index/"))
+                .filter(|(bucket, index)| !bucket.is_empty() && !index.is_empty())
+                .map(|(bucket, index)| (bucket.to_string(), index.to_string()))
+                .ok_or_else(|| ApiError::invalid_field("/indexArn", "Invalid index ARN")),
+            _ => Err(ApiError::validation(
+                "Specify either vectorBucketName and indexName, or indexArn",
+            )),
+        }
+    }
+
+    fn index(&self, body: &Value) -> Result<(String, String, &Index), ApiError> {
+        let (bucket_name, index_name) = Self::index_names(body)?;
+        let bucket = self
+            .buckets
+            .get(&bucket_name)
+            .ok_or_else(|| ApiError::not_found(format!("Vector bucket {bucket_name} not found")))?;
+        let index = bucket.indexes.get(&index_name).ok_or_else(|| {
+            ApiError::not_found(format!(
+                "Index {index_name} not found in vector bucket {bucket_name}"
+            ))
+        })?;
+        Ok((bucket_name, index_name, index))
+    }
+
+    fn index_mut(&mut self, body: &Value) -> Result<&mut Index, ApiError> {
+        let (bucket_name, index_name, _) = self.index(body)?;
+        Ok(self
+            .buckets
+            .get_mut(&bucket_name)
+            .and_then(|bucket| bucket.indexes.get_mut(&index_name))
+            .expect("index exists"))
+    }
+
+    // Buckets
+
+    fn create_vector_bucket(&mut self, body: &Value) -> OpResult {
+        let request: CreateVectorBucket = parse(body)?;
+        let name = request.vector_bucket_name;
+        validate_bucket_name(&name)
+            .map_err(|e| ApiError::invalid_field("/vectorBucketName", e.to_string()))?;
+        if let Some(encryption) = &request.encryption_configuration {
+            let sse_type = encryption.get("sseType").and_then(Value::as_str);
+            let kms_key_arn = encryption.get("kmsKeyArn").and_then(Value::as_str);
+            match (sse_type, kms_key_arn) {
+                (Some("aws:kms"), Some(arn)) => validate_kms_key_arn(arn).map_err(|e| {
+                    ApiError::invalid_field("/encryptionConfiguration/kmsKeyArn", e.to_string())
+                })?,
+                (Some("AES256") | None, None) => {}
+                _ => {
+                    return Err(ApiError::invalid_field(
+                        "/encryptionConfiguration",
+                        "kmsKeyArn is required for, and only allowed with, sseType aws:kms",
+                    ))
+                }
+            }
+        }
+        validate_tags(&request.tags)
+            .map_err(|e| ApiError::invalid_field("/tags", e.to_string()))?;
+        if self.buckets.contains_key(&name) {
+            return Err(ApiError::conflict(format!(
+                "Vector bucket {name} already exists"
+            )));
+        }
+
+        let arn = self.bucket_arn(&name);
+        self.buckets.insert(
+            name,
+            Bucket {
+                arn: arn.clone(),
+                creation_time: now(),
+                encryption_configuration: request
+                    .encryption_configuration
+                    .unwrap_or_else(|| json!({ "sseType": "AES256" })),
+                tags: request.tags,
+                policy: None,
+                indexes: BTreeMap::new(),
+            },
+        );
+        Ok(json!({ "vectorBucketArn": arn }))
+    }
+
+    fn get_vector_bucket(&self, body: &Value) -> OpResult {
+        let (name, bucket) = self.bucket(body)?;
+        Ok(json!({
+            "vectorBucket": {
+                "vectorBucketName": name,
+                "vectorBucketArn": bucket.arn,
+                "creationTime": bucket.creation_time,
+                "encryptionConfiguration": bucket.encryption_configuration,
+            }
+        }))
+    }
+
+    fn list_vector_buckets(&self, body: &Value) -> OpResult {
+        let request: ListRequest = parse(body)?;
+        let prefix = request.prefix.unwrap_or_default();
+        let (names, next_token) = page(
+            self.buckets.keys().filter(|name| name.starts_with(&prefix)),
+            request.max_results,
+            request.next_token.as_deref(),
+            500,
+        )?;
+
+        let buckets: Vec<Value> = names
+            .into_iter()
+            .map(|name| {
+                let bucket = &self.buckets[name];
+                json!({
+                    "vectorBucketName": name,
+                    "vectorBucketArn": bucket.arn,
+                    "creationTime": bucket.creation_time,
+                })
+            })
+            .collect();
+        let mut response = json!({ "vectorBuckets": buckets });
+        if let Some(token) = next_token {
+            response["nextToken"] = token.into();
+        }
+        Ok(response)
+    }
+
+    fn delete_vector_bucket(&mut self, body: &Value) -> OpResult {
+        let (name, bucket) = self.bucket(body)?;
+        if !bucket.indexes.is_empty() {
+            return Err(ApiError::conflict(format!(
+                "Vector bucket {name} is not empty; delete its indexes first"
+            )));
+        }
+        self.buckets.remove(&name);
+        Ok(json!({}))
+    }
+
+    // Indexes
+
+    fn create_index(&mut self, body: &Value) -> OpResult {
+        let request: CreateIndex = parse(body)?;
+        let bucket_name = Self::bucket_name(
+            request.vector_bucket_name.as_ref(),
+            request.vector_bucket_arn.as_ref(),
+        )?;
+        validate_index_name(&request.index_name)
+            .map_err(|e| ApiError::invalid_field("/indexName", e.to_string()))?;
+        if request.dimension == 0 || request.dimension > 4096 {
+            return Err(ApiError::invalid_field(
+                "/dimension",
+                "Dimension must be between 1 and 4096",
+            ));
+        }
+        let non_filterable = request
+            .metadata_configuration
+            .as_ref()
+            .and_then(|c| c.non_filterable_metadata_keys.as_ref())
+            .map_or(0, Vec::len);
+        if non_filterable > MAX_NON_FILTERABLE_KEYS {
+            return Err(ApiError::invalid_field(
+                "/metadataConfiguration/nonFilterableMetadataKeys",
+                format!(
+                    "At most {MAX_NON_FILTERABLE_KEYS} non-filterable metadata keys are allowed"
+                ),
+            ));
+        }
+
+        let bucket = self
+            .buckets
+            .get_mut(&bucket_name)
+            .ok_or_else(|| ApiError::not_found(format!("Vector bucket {bucket_name} not found")))?;
+        if bucket.indexes.contains_key(&request.index_name) {
+            return Err(ApiError::conflict(format!(
+                "Index {} already exists in vector bucket {bucket_name}",
+                request.index_name
+            )));
+        }
+
+        let arn = format!("{}/index/{}", bucket.arn, request.index_name);
+        bucket.indexes.insert(
+            request.index_name,
+            Index {
+                arn: arn.clone(),
+                creation_time: now(),
+                data_type: request.data_type,
+                dimension: request.dimension,
+                distance_metric: request.distance_metric,
+                metadata_configuration: request.metadata_configuration,
+                tags: BTreeMap::new(),
+                vectors: BTreeMap::new(),
+            },
+        );
+        Ok(json!({ "indexArn": arn }))
+    }
+
+    fn get_index(&self, body: &Value) -> OpResult {
+        let (bucket_name, index_name, index) = self.index(body)?;
+        let mut response = json!({
+            "index": {
+                "vectorBucketName": bucket_name,
+                "indexName": index_name,
+                "indexArn": index.arn,
+                "creationTime": index.creation_time,
+                "dataType": index.data_type,
+                "dimension": index.dimension,
+                "distanceMetric": index.distance_metric,
+            }
+        });
+        if let Some(config) = &index.metadata_configuration {
+            response["index"]["metadataConfiguration"] = json!(config);
+        }
+        Ok(response)
+    }
+
+    fn list_indexes(&self, body: &Value) -> OpResult {
+        let (bucket_name, bucket) = self.bucket(body)?;
+        let request: ListRequest = parse(body)?;
+        let prefix = request.prefix.unwrap_or_default();
+        let (names, next_token) = page(
+            bucket
+                .indexes
+                .keys()
+                .filter(|name| name.starts_with(&prefix)),
+            request.max_results,
+            request.next_token.as_deref(),
+            500,
+        )?;
+
+        let indexes: Vec<Value> = names
+            .into_iter()
+            .map(|name| {
+                let index = &bucket.indexes[name];
+                json!({
+                    "vectorBucketName": bucket_name,
+                    "indexName": name,
+                    "indexArn": index.arn,
+                    "creationTime": index.creation_time,
+                })
+            })
+            .collect();
+        let mut response = json!({ "indexes": indexes });
+        if let Some(token) = next_token {
+            response["nextToken"] = token.into();
+        }
+        Ok(response)
+    }
+
+    fn delete_index(&mut self, body: &Value) -> OpResult {
+        let (bucket_name, index_name, _) = self.index(body)?;
+        if let Some(bucket) = self.buckets.get_mut(&bucket_name) {
+            bucket.indexes.remove(&index_name);
+        }
+        Ok(json!({}))
+    }
+
+    // Vectors
+
+    fn put_vectors(&mut self, body: &Value) -> OpResult {
+        let request: PutVectors = parse(body)?;
+        let index = self.index_mut(body)?;
+        if request.vectors.is_empty() || request.vectors.len() > 500 {
+            return Err(ApiError::invalid_field(
+                "/vectors",
+                "Between 1 and 500 vectors must be specified",
+            ));
+        }
+
+        // Validate the whole batch before writing anything
+        for (i, vector) in request.vectors.iter().enumerate() {
+            if vector.key.is_empty() || vector.key.len() > MAX_KEY_LENGTH {
+                return Err(ApiError::invalid_field(
+                    &format!("/vectors/{i}/key"),
+                    format!("Key must be between 1 and {MAX_KEY_LENGTH} characters"),
+                ));
+            }
+            check_vector(
+                &format!("/vectors/{i}/data"),
+                &vector.data.float32,
+                index.dimension,
+            )?;
+            if let Some(metadata) = &vector.metadata {
+                let path = format!("/vectors/{i}/metadata");
+                if !metadata.is_object() {
+                    return Err(ApiError::invalid_field(&path, "Metadata must be an object"));
+                }
+                let size = serde_json::to_vec(metadata).map_or(0, |bytes| bytes.len());
+                if size > MAX_METADATA_BYTES {
+                    return Err(ApiError::invalid_field(
+                        &path,
+                        format!("Metadata size {size} exceeds {MAX_METADATA_BYTES} bytes"),
+                    ));
+                }
+            }
+        }
+
+        for vector in request.vectors {
+            index.vectors.insert(
+                vector.key,
+                StoredVector {
+                    data: vector.data.float32,
+                    metadata: vector.metadata,
+                },
+            );
+        }
+        Ok(json!({}))
+    }
+
+    fn get_vectors(&self, body: &Value) -> OpResult {
+        let request: GetVectors = parse(body)?;
+        let (_, _, index) = self.index(body)?;
+        check_key_count(&request.keys, 100)?;
+
+        let vectors: Vec<Value> = request
+            .keys
+            .iter()
+            .filter(|key| index.vectors.contains_key(*key))
+            .map(|key| index.output_vector(key, request.return_data, request.return_metadata))
+            .collect();
+        Ok(json!({ "vectors": vectors }))
+    }
+
+    fn list_vectors(&self, body: &Value) -> OpResult {
+        let request: ListVectors = parse(body)?;
+        let (_, _, index) = self.index(body)?;
+        crate::validation::validate_segment(request.segment_count, request.segment_index)
+            .map_err(|e| ApiError::validation(e.to_string()))?;
+
+        let segment = request.segment_count.zip(request.segment_index);
+        let (keys, next_token) = page(
+            index.vectors.keys().filter(|key| {
+                segment.is_none_or(|(count, segment)| segment_of(key, count) == segment)
+            }),
+            request.max_results,
+            request.next_token.as_deref(),
+            1000,
+        )?;
+
+        let vectors: Vec<Value> = keys
+            .into_iter()
+            .map(|key| index.output_vector(key, request.return_data, request.return_metadata))
+            .collect();
+        let mut response = json!({ "vectors": vectors });
+        if let Some(token) = next_token {
+            response["nextToken"] = token.into();
+        }
+        Ok(response)
+    }
+
+    fn delete_vectors(&mut self, body: &Value) -> OpResult {
+        let request: DeleteVectors = parse(body)?;
+        let index = self.index_mut(body)?;
+        check_key_count(&request.keys, 500)?;
+
+        // Deleting a key that does not exist is not an error
+        for key in &request.keys {
+            index.vectors.remove(key);
+        }
+        Ok(json!({}))
+    }
+
+    fn query_vectors(&self, body: &Value) -> OpResult {
+        let request: QueryVectors = parse(body)?;
+        let (_, _, index) = self.index(body)?;
+        validate_top_k(request.top_k)
+            .map_err(|e| ApiError::invalid_field("/topK", e.to_string()))?;
+        let query = &request.query_vector.float32;
+        check_vector("/queryVector", query, index.dimension)?;
+        let filter = request
+            .filter
+            .as_ref()
+            .map(|filter| {
+                let filter = Filter::from_json(filter)?;
+                filter.check_filterable(index.non_filterable_keys())?;
+                Ok(filter)
+            })
+            .transpose()
+            .map_err(|e: crate::filter::FilterError| {
+                ApiError::invalid_field(
+                    &format!("/filter{}", e.path.trim_end_matches('/')),
+                    e.message,
+                )
+            })?;
+
+        let mut matches: Vec<(f32, &String, &StoredVector)> = index
+            .vectors
+            .iter()
+            .filter(|(_, vector)| {
+                filter
+                    .as_ref()
+                    .is_none_or(|f| f.matches(vector.metadata.as_ref().unwrap_or(&Value::Null)))
+            })
+            .map(|(key, vector)| {
+                (
+                    distance(&index.distance_metric, query, &vector.data),
+                    key,
+                    vector,
+                )
+            })
+            .collect();
+        // Exact kNN; ties are broken by key so results are deterministic
+        matches.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+        matches.truncate(request.top_k as usize);
+
+        let vectors: Vec<Value> = matches
+            .into_iter()
+            .map(|(distance, key, vector)| {
+                let mut output = json!({ "key": key });
+                if request.return_distance {
+                    output["distance"] = json!(distance);
+                }
+                if let (true, Some(metadata)) = (request.return_metadata, &vector.metadata) {
+                    output["metadata"] = metadata.clone();
+                }
+                output
+            })
+            .collect();
+        Ok(json!({ "vectors": vectors }))
+    }
+
+    // Policies
+
+    fn put_vector_bucket_policy(&mut self, body: &Value) -> OpResult {
+        let request: PutPolicy = parse(body)?;
+        let document: Value = serde_json::from_str(&request.policy).map_err(|e| {
+            ApiError::invalid_field("/policy", format!("Policy is not valid JSON: {e}"))
+        })?;
+        if !document.is_object() {
+            return Err(ApiError::invalid_field(
+                "/policy",
+                "Policy must be a JSON object",
+            ));
+        }
+        self.bucket_mut(body)?.policy = Some(request.policy);
+        Ok(json!({}))
+    }
+
+    fn get_vector_bucket_policy(&self, body: &Value) -> OpResult {
+        let (name, bucket) = self.bucket(body)?;
+        let policy = bucket.policy.as_ref().ok_or_else(|| {
+            ApiError::not_found(format!("Vector bucket {name} has no bucket policy"))
+        })?;
+        Ok(json!({ "policy": policy }))
+    }
+
+    fn delete_vector_bucket_policy(&mut self, body: &Value) -> OpResult {
+        self.bucket_mut(body)?.policy = None;
+        Ok(json!({}))
+    }
+
+    // Tags
+
+    /// Tags of the bucket or index named by `resourceArn`
+    fn tags_mut(&mut self, body: &Value) -> Result<&mut BTreeMap<String, String>, ApiError> {
+        let request: ResourceTarget = parse(body)?;
+        validate_resource_arn(&request.resource_arn)
+            .map_err(|e| ApiError::invalid_field("/resourceArn", e.to_string()))?;
+        if request.resource_arn.contains("/index/") {
+            let target = json!({ "indexArn": request.resource_arn });
+            Ok(&mut self.index_mut(&target)?.tags)
+        } else {
+            let target = json!({ "vectorBucketArn": request.resource_arn });
+            Ok(&mut self.bucket_mut(&target)?.tags)
+        }
+    }
+
+    fn tag_resource(&mut self, body: &Value) -> OpResult {
+        let request: TagResource = parse(body)?;
+        let tags = self.tags_mut(body)?;
+        let mut merged = tags.clone();
+        merged.extend(request.tags);
+        validate_tags(&merged).map_err(|e| ApiError::invalid_field("/tags", e.to_string()))?;
+        *tags = merged;
+        Ok(json!({}))
+    }
+
+    fn untag_resource(&mut self, body: &Value) -> OpResult {
+        let request: UntagResource = parse(body)?;
+        let tags = self.tags_mut(body)?;
+        for key in &request.tag_keys {
+            tags.remove(key);
+        }
+        Ok(json!({}))
+    }
+
+    fn list_tags_for_resource(&mut self, body: &Value) -> OpResult {
+        let tags = self.tags_mut(body)?;
+        Ok(json!({ "tags": tags }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_tokens_and_segments() {
+        let keys: Vec<String> = (0..5).map(|i| format!("key-{i}")).collect();
+        let (first, token) = page(keys.iter(), Some(2), None, 1000).unwrap();
+        assert_eq!(first, [&keys[0], &keys[1]]);
+        let token = token.unwrap();
+        assert_eq!(decode_token(&token).unwrap(), "key-1");
+
+        let (rest, token) = page(keys.iter(), Some(10), Some(&token), 1000).unwrap();
+        assert_eq!(rest.len(), 3);
+        assert!(token.is_none());
+        assert!(page(keys.iter(), Some(1001), None, 1000).is_err());
+        assert!(page(keys.iter(), None, Some("zz"), 1000).is_err());
+
+        // Every key lands in exactly one segment
+        for key in &keys {
+            assert_eq!((0..4).filter(|s| segment_of(key, 4) == *s).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_distances() {
+        let cosine = distance(&DistanceMetric::Cosine, &[1.0, 0.0], &[0.0, 1.0]);
+        assert!((cosine - 1.0).abs() < 1e-6);
+        assert!(distance(&DistanceMetric::Cosine, &[1.0, 1.0], &[2.0, 2.0]).abs() < 1e-6);
+        let euclidean = distance(&DistanceMetric::Euclidean, &[0.0, 0.0], &[3.0, 4.0]);
+        assert!((euclidean - 5.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,392 @@
+use std::fmt;
+
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+
+/// Details carried by every error returned by the S3 Vectors service
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorMetadata {
+    /// Exception name, e.g. `NotFoundException`
+    pub code: String,
+    pub message: String,
+    /// HTTP status code of the response
+    pub status: u16,
+    /// `x-amz-request-id` of the failed request, for AWS support cases
+    pub request_id: Option<String>,
+}
+
+impl fmt::Display for ErrorMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (HTTP {}", self.message, self.status)?;
+        if let Some(request_id) = &self.request_id {
+            write!(f, ", request ID {request_id}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// A field rejected by a `ValidationException`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ValidationField {
+    /// Path of the offending field in the request, e.g. `/vectors/0/data`
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum S3VectorsError {
+    #[error("Authentication required: {0}")]
+    AuthRequired(String),
+
+    /// Rejected by client-side validation before any request was sent
+    #[error("Validation error: {0}")]
+    Validation(String),
+
+    /// `ValidationException` (400), with the rejected fields when provided
+    #[error("Invalid request: {metadata}{}", format_fields(.fields))]
+    InvalidRequest {
+        metadata: ErrorMetadata,
+        fields: Vec<ValidationField>,
+    },
+
+    /// `AccessDeniedException` (403)
+    #[error("Access denied: {0}")]
+    AccessDenied(ErrorMetadata),
+
+    /// `NotFoundException` (404)
+    #[error("Resource not found: {0}")]
+    NotFound(ErrorMetadata),
+
+    /// `ConflictException` (409): the resource already exists or is being
+    /// modified concurrently
+    #[error("Resource already exists: {0}")]
+    AlreadyExists(ErrorMetadata),
+
+    /// `ServiceQuotaExceededException` (402)
+    #[error("Service quota exceeded: {0}")]
+    ServiceQuotaExceeded(ErrorMetadata),
+
+    /// `TooManyRequestsException` (429)
+    #[error("Rate limit exceeded: {0}")]
+    TooManyRequests(ErrorMetadata),
+
+    /// Never returned: throttling is reported as
+    /// [`TooManyRequests`](Self::TooManyRequests)
+    #[deprecated(note = "match `TooManyRequests` instead")]
+    #[error("Rate limit exceeded, retry after {0}ms")]
+    RateLimit(u64),
+
+    /// `InternalServerException` (500)
+    #[error("Internal server error: {0}")]
+    InternalServer(ErrorMetadata),
+
+    /// `ServiceUnavailableException` (503)
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(ErrorMetadata),
+
+    /// `KmsDisabledException` (400): the bucket's KMS key is disabled
+    #[error("KMS key disabled: {0}")]
+    KmsDisabled(ErrorMetadata),
+
+    /// `KmsInvalidKeyUsageException` (400)
+    #[error("KMS key cannot be used for this operation: {0}")]
+    KmsInvalidKeyUsage(ErrorMetadata),
+
+    /// `KmsInvalidStateException` (400)
+    #[error("KMS key is in an invalid state: {0}")]
+    KmsInvalidState(ErrorMetadata),
+
+    /// `KmsNotFoundException` (400)
+    #[error("KMS key not found: {0}")]
+    KmsNotFound(ErrorMetadata),
+
+    /// An exception this client does not know about
+    #[error("Service error {code}: {0}", code = .0.code)]
+    Unknown(ErrorMetadata),
+
+    /// The service answered with something this client cannot interpret
+    #[error("Service error: {0}")]
+    ServiceError(String),
+
+    #[error("Request error: {0}")]
+    RequestError(#[from] reqwest::Error),
+
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("Other error: {0}")]
+    Other(#[from] anyhow::Error),
+}
+
+/// One setting on which an existing index differs from the requested one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexSettingMismatch {
+    /// Request field name, e.g. `dimension` or `distanceMetric`
+    pub field: &'static str,
+    pub existing: String,
+    pub requested: String,
+}
+
+/// An index already exists but with settings other than those requested
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Index '{bucket}/{index}' already exists with different settings:{}",
+    format_mismatches(.mismatches)
+)]
+pub struct IndexMismatchError {
+    pub bucket: String,
+    pub index: String,
+    pub mismatches: Vec<IndexSettingMismatch>,
+}
+
+fn format_mismatches(mismatches: &[IndexSettingMismatch]) -> String {
+    mismatches
+        .iter()
+        .map(|m| {
+            format!(
+                "\n  {}: existing {}, requested {}",
+                m.field, m.existing, m.requested
+            )
+        })
+        .collect()
+}
+
+fn format_fields(fields: &[ValidationField]) -> String {
+    fields
+        .iter()
+        .map(|field| format!("\n  {}: {}", field.path, field.message))
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    #[serde(rename = "__type")]
+    error_type: Option<String>,
+    #[serde(alias = "Message")]
+    message: Option<String>,
+    #[serde(rename = "requestId")]
+    request_id: Option<String>,
+    #[serde(rename = "fieldList", default)]
+    field_list: Vec<ValidationField>,
+}
+
+impl S3VectorsError {
+    /// Build the typed error for a failed HTTP response
+    pub(crate) fn from_response(status: u16, headers: &HeaderMap, body: &str) -> Self {
+        let parsed: Option<ErrorBody> = serde_json::from_str(body).ok();
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+
+        let code = parsed
+            .as_ref()
+            .and_then(|b| b.error_type.clone())
+            .or_else(|| header("x-amzn-errortype"))
+            .map(|code| normalize_code(&code))
+            .unwrap_or_else(|| code_for_status(status).to_string());
+        let message = parsed
+            .as_ref()
+            .and_then(|b| b.message.clone())
+            .unwrap_or_else(|| {
+                if body.is_empty() {
+                    format!("HTTP {status}")
+                } else {
+                    body.to_string()
+                }
+            });
+        let request_id = header("x-amz-request-id")
+            .or_else(|| header("x-amzn-requestid"))
+            .or_else(|| parsed.as_ref().and_then(|b| b.request_id.clone()));
+
+        let metadata = ErrorMetadata {
+            code,
+            message,
+            status,
+            request_id,
+        };
+
+        match metadata.code.trim_end_matches("Exception") {
+            "Validation" => S3VectorsError::InvalidRequest {
+                metadata,
+                fields: parsed.map(|b| b.field_list).unwrap_or_default(),
+            },
+            "AccessDenied" => S3VectorsError::AccessDenied(metadata),
+            "NotFound" => S3VectorsError::NotFound(metadata),
+            "Conflict" => S3VectorsError::AlreadyExists(metadata),
+            "ServiceQuotaExceeded" => S3VectorsError::ServiceQuotaExceeded(metadata),
+            "TooManyRequests" | "Throttling" => S3VectorsError::TooManyRequests(metadata),
+            "InternalServer" => S3VectorsError::InternalServer(metadata),
+            "ServiceUnavailable" => S3VectorsError::ServiceUnavailable(metadata),
+            "KmsDisabled" => S3VectorsError::KmsDisabled(metadata),
+            "KmsInvalidKeyUsage" => S3VectorsError::KmsInvalidKeyUsage(metadata),
+            "KmsInvalidState" => S3VectorsError::KmsInvalidState(metadata),
+            "KmsNotFound" => S3VectorsError::KmsNotFound(metadata),
+            _ => S3VectorsError::Unknown(metadata),
+        }
+    }
+
+    /// Service error details, if the service rejected the request
+    pub fn metadata(&self) -> Option<&ErrorMetadata> {
+        match self {
+            S3VectorsError::InvalidRequest { metadata, .. }
+            | S3VectorsError::AccessDenied(metadata)
+            | S3VectorsError::NotFound(metadata)
+            | S3VectorsError::AlreadyExists(metadata)
+            | S3VectorsError::ServiceQuotaExceeded(metadata)
+            | S3VectorsError::TooManyRequests(metadata)
+            | S3VectorsError::InternalServer(metadata)
+            | S3VectorsError::ServiceUnavailable(metadata)
+            | S3VectorsError::KmsDisabled(metadata)
+            | S3VectorsError::KmsInvalidKeyUsage(metadata)
+            | S3VectorsError::KmsInvalidState(metadata)
+            | S3VectorsError::KmsNotFound(metadata)
+            | S3VectorsError::Unknown(metadata) => Some(metadata),
+            _ => None,
+        }
+    }
+
+    /// Exception name reported by the service
+    pub fn code(&self) -> Option<&str> {
+        self.metadata().map(|m| m.code.as_str())
+    }
+
+    /// HTTP status of the failed response
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            S3VectorsError::RequestError(e) => e.status().map(|s| s.as_u16()),
+            _ => self.metadata().map(|m| m.status),
+        }
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        self.metadata().and_then(|m| m.request_id.as_deref())
+    }
+
+    /// Whether the same request may succeed if sent again later
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            S3VectorsError::TooManyRequests(_)
+            | S3VectorsError::InternalServer(_)
+            | S3VectorsError::ServiceUnavailable(_) => true,
+            S3VectorsError::Unknown(metadata) => metadata.status >= 500,
+            // Timeouts, refused connections and connections reset before a
+            // response arrived
+            S3VectorsError::RequestError(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            _ => false,
+        }
+    }
+}
+
+/// Strip namespace prefixes and URI suffixes from `__type`, e.g.
+/// `com.amazonaws.s3vectors#NotFoundException:http://...` -> `NotFoundException`
+fn normalize_code(code: &str) -> String {
+    let code = code.rsplit('#').next().unwrap_or(code);
+    code.split(':').next().unwrap_or(code).trim().to_string()
+}
+
+/// Exception implied by the status when the body names none
+fn code_for_status(status: u16) -> &'static str {
+    match status {
+        400 => "ValidationException",
+        402 => "ServiceQuotaExceededException",
+        403 => "AccessDeniedException",
+        404 => "NotFoundException",
+        409 => "ConflictException",
+        429 => "TooManyRequestsException",
+        500 => "InternalServerException",
+        503 => "ServiceUnavailableException",
+        _ => "UnknownError",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(request_id: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-amz-request-id",
+            HeaderValue::from_str(request_id).unwrap(),
+        );
+        headers
+    }
+
+    #[test]
+    fn test_from_response_maps_documented_exceptions() {
+        let cases = [
+            (403, "AccessDeniedException", "AccessDenied"),
+            (404, "NotFoundException", "NotFound"),
+            (409, "ConflictException", "AlreadyExists"),
+            (402, "ServiceQuotaExceededException", "ServiceQuotaExceeded"),
+            (429, "TooManyRequestsException", "TooManyRequests"),
+            (500, "InternalServerException", "InternalServer"),
+            (503, "ServiceUnavailableException", "ServiceUnavailable"),
+            (400, "KmsDisabledException", "KmsDisabled"),
+            (400, "KmsInvalidKeyUsageException", "KmsInvalidKeyUsage"),
+            (400, "KmsInvalidStateException", "KmsInvalidState"),
+            (400, "KmsNotFoundException", "KmsNotFound"),
+        ];
+
+        for (status, code, variant) in cases {
+            let body = format!(r#"{{"__type": "{code}", "message": "boom"}}"#);
+            let err = S3VectorsError::from_response(status, &headers("REQ1"), &body);
+            assert!(
+                format!("{err:?}").starts_with(variant),
+                "{code} mapped to {err:?}"
+            );
+            assert_eq!(err.code(), Some(code));
+            assert_eq!(err.status(), Some(status));
+            assert_eq!(err.request_id(), Some("REQ1"));
+        }
+    }
+
+    #[test]
+    fn test_from_response_parses_validation_fields() {
+        let body = r#"{
+            "__type": "com.amazonaws.s3vectors#ValidationException",
+            "message": "Invalid input",
+            "fieldList": [{"path": "/vectors/0/data/float32", "message": "dimension mismatch"}]
+        }"#;
+        let err = S3VectorsError::from_response(400, &headers("REQ2"), body);
+
+        match &err {
+            S3VectorsError::InvalidRequest { metadata, fields } => {
+                assert_eq!(metadata.code, "ValidationException");
+                assert_eq!(fields[0].path, "/vectors/0/data/float32");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert!(err.to_string().contains("dimension mismatch"));
+        assert!(err.to_string().contains("request ID REQ2"));
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_from_response_falls_back_to_status() {
+        let err = S3VectorsError::from_response(503, &HeaderMap::new(), "upstream timeout");
+        assert!(matches!(err, S3VectorsError::ServiceUnavailable(_)));
+        assert_eq!(err.metadata().unwrap().message, "upstream timeout");
+        assert!(err.is_retryable());
+
+        let err = S3VectorsError::from_response(418, &HeaderMap::new(), "");
+        assert!(matches!(err, S3VectorsError::Unknown(_)));
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_normalize_code() {
+        assert_eq!(
+            normalize_code("com.amazonaws.s3vectors#NotFoundException"),
+            "NotFoundException"
+        );
+        assert_eq!(
+            normalize_code("ConflictException:http://internal.amazon.com/"),
+            "ConflictException"
+        );
+    }
+}
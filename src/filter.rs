@@ -0,0 +1,821 @@
+//! Typed metadata filters for `QueryVectors`.
+//!
+//! A [`Filter`] serializes to the service's filter JSON, and existing JSON
+//! filters can be parsed and validated with [`Filter::from_json`] or
+//! `str::parse`:
+//!
+//! ```
+//! use s3_vectors::Filter;
+//!
+//! let filter = Filter::eq("genre", "scifi")
+//!     .and(Filter::gte("year", 2020))
+//!     .and(Filter::is_in("lang", ["en", "ko"]));
+//! assert_eq!(
+//!     filter.to_json(),
+//!     serde_json::json!({"$and": [
+//!         {"genre": {"$eq": "scifi"}},
+//!         {"year": {"$gte": 2020}},
+//!         {"lang": {"$in": ["en", "ko"]}},
+//!     ]})
+//! );
+//!
+//! let parsed: Filter = r#"{"genre": "scifi", "year": {"$gte": 2020}}"#.parse().unwrap();
+//! assert!(r#"{"year": {"$gte": "2020"}}"#.parse::<Filter>().is_err());
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+/// A metadata filter: conditions on metadata keys combined with `$and` and
+/// `$or`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Filter {
+    /// A condition on a single metadata key
+    Key(String, Condition),
+    /// Every filter must match
+    And(Vec<Filter>),
+    /// At least one filter must match
+    Or(Vec<Filter>),
+}
+
+/// Comparison applied to one metadata key
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    /// `$eq`; against an array value, matches if any element is equal
+    Eq(FilterValue),
+    /// `$ne`; also matches vectors without the key
+    Ne(FilterValue),
+    Gt(f64),
+    Gte(f64),
+    Lt(f64),
+    Lte(f64),
+    /// `$in`: equal to one of the values
+    In(Vec<FilterValue>),
+    /// `$nin`: equal to none of the values; also matches vectors without
+    /// the key
+    Nin(Vec<FilterValue>),
+    /// `$exists`: whether the key is present
+    Exists(bool),
+}
+
+/// A string, number or boolean operand
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterValue {
+    String(String),
+    Number(f64),
+    Bool(bool),
+}
+
+/// Why a filter was rejected
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid filter at {path}: {message}")]
+pub struct FilterError {
+    /// Location of the problem in the filter JSON, e.g. `/$and/1/year`
+    pub path: String,
+    pub message: String,
+}
+
+impl FilterError {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: if path.is_empty() { "/" } else { path }.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl Filter {
+    pub fn eq(key: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::Key(key.into(), Condition::Eq(value.into()))
+    }
+
+    pub fn ne(key: impl Into<String>, value: impl Into<FilterValue>) -> Self {
+        Self::Key(key.into(), Condition::Ne(value.into()))
+    }
+
+    pub fn gt(key: impl Into<String>, value: impl Into<f64>) -> Self {
+        Self::Key(key.into(), Condition::Gt(value.into()))
+    }
+
+    pub fn gte(key: impl Into<String>, value: impl Into<f64>) -> Self {
+        Self::Key(key.into(), Condition::Gte(value.into()))
+    }
+
+    pub fn lt(key: impl Into<String>, value: impl Into<f64>) -> Self {
+        Self::Key(key.into(), Condition::Lt(value.into()))
+    }
+
+    pub fn lte(key: impl Into<String>, value: impl Into<f64>) -> Self {
+        Self::Key(key.into(), Condition::Lte(value.into()))
+    }
+
+    pub fn is_in<V: Into<FilterValue>>(
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        Self::Key(key.into(), Condition::In(values))
+    }
+
+    pub fn not_in<V: Into<FilterValue>>(
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        Self::Key(key.into(), Condition::Nin(values))
+    }
+
+    pub fn exists(key: impl Into<String>, exists: bool) -> Self {
+        Self::Key(key.into(), Condition::Exists(exists))
+    }
+
+    /// `$and` of all `filters`
+    pub fn all(filters: impl IntoIterator<Item = Filter>) -> Self {
+        Self::And(filters.into_iter().collect())
+    }
+
+    /// `$or` of all `filters`
+    pub fn any(filters: impl IntoIterator<Item = Filter>) -> Self {
+        Self::Or(filters.into_iter().collect())
+    }
+
+    /// Combine with `other` under `$and`, extending an existing `$and`
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Self::And(mut filters) => {
+                filters.push(other);
+                Self::And(filters)
+            }
+            filter => Self::And(vec![filter, other]),
+        }
+    }
+
+    /// Combine with `other` under `$or`, extending an existing `$or`
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Self::Or(mut filters) => {
+                filters.push(other);
+                Self::Or(filters)
+            }
+            filter => Self::Or(vec![filter, other]),
+        }
+    }
+
+    /// Parse and validate a filter in the service's JSON syntax.
+    ///
+    /// Several keys in one object, or several operators on one key, are an
+    /// implicit `$and`; a bare value is shorthand for `$eq`.
+    pub fn from_json(value: &Value) -> Result<Self, FilterError> {
+        let filter = parse_filter(value, "")?;
+        filter.validate()?;
+        Ok(filter)
+    }
+
+    /// The filter in the service's JSON syntax
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Key(key, condition) => json!({ key: condition.to_json() }),
+            Self::And(filters) => {
+                json!({ "$and": filters.iter().map(Self::to_json).collect::<Vec<_>>() })
+            }
+            Self::Or(filters) => {
+                json!({ "$or": filters.iter().map(Self::to_json).collect::<Vec<_>>() })
+            }
+        }
+    }
+
+    /// Metadata keys the filter refers to
+    pub fn keys(&self) -> BTreeSet<&str> {
+        let mut keys = BTreeSet::new();
+        self.visit(&mut |_, key, _| {
+            keys.insert(key);
+            Ok(())
+        })
+        .expect("collecting keys cannot fail");
+        keys
+    }
+
+    /// Check operands the builder cannot rule out: empty `$and`/`$or`/`$in`
+    /// lists, non-finite numbers and empty or `$`-prefixed keys
+    pub fn validate(&self) -> Result<(), FilterError> {
+        self.check_combinators("")?;
+        self.visit(&mut |path, key, condition| {
+            if key.is_empty() || key.starts_with('$') {
+                return Err(FilterError::new(
+                    path,
+                    format!("invalid metadata key '{key}'"),
+                ));
+            }
+            let path = &format!("{path}/{key}");
+            let finite = |value: &FilterValue| match value {
+                FilterValue::Number(n) => n.is_finite(),
+                _ => true,
+            };
+            match condition {
+                Condition::Eq(value) | Condition::Ne(value) if !finite(value) => {
+                    Err(FilterError::new(path, "numbers must be finite"))
+                }
+                Condition::Gt(n) | Condition::Gte(n) | Condition::Lt(n) | Condition::Lte(n)
+                    if !n.is_finite() =>
+                {
+                    Err(FilterError::new(path, "numbers must be finite"))
+                }
+                Condition::In(values) | Condition::Nin(values) => {
+                    if values.is_empty() {
+                        Err(FilterError::new(path, "value list must not be empty"))
+                    } else if !values.iter().all(finite) {
+                        Err(FilterError::new(path, "numbers must be finite"))
+                    } else {
+                        Ok(())
+                    }
+                }
+                _ => Ok(()),
+            }
+        })
+    }
+
+    /// Reject conditions on keys the index declares non-filterable
+    pub fn check_filterable(&self, non_filterable_keys: &[String]) -> Result<(), FilterError> {
+        self.visit(&mut |path, key, _| {
+            if non_filterable_keys.iter().any(|k| k == key) {
+                Err(FilterError::new(
+                    &format!("{path}/{key}"),
+                    format!("metadata key '{key}' is non-filterable for this index"),
+                ))
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Whether `metadata` satisfies the filter, using the service's
+    /// semantics (see [`matches`]). Anything but a JSON object is treated as
+    /// metadata without keys.
+    pub fn matches(&self, metadata: &Value) -> bool {
+        match self {
+            Self::Key(key, condition) => condition.matches(metadata.get(key)),
+            Self::And(filters) => filters.iter().all(|filter| filter.matches(metadata)),
+            Self::Or(filters) => filters.iter().any(|filter| filter.matches(metadata)),
+        }
+    }
+
+    fn check_combinators(&self, path: &str) -> Result<(), FilterError> {
+        let (op, filters) = match self {
+            Self::Key(..) => return Ok(()),
+            Self::And(filters) => ("$and", filters),
+            Self::Or(filters) => ("$or", filters),
+        };
+        let path = format!("{path}/{op}");
+        if filters.is_empty() {
+            return Err(FilterError::new(&path, format!("{op} must not be empty")));
+        }
+        filters
+            .iter()
+            .enumerate()
+            .try_for_each(|(i, filter)| filter.check_combinators(&format!("{path}/{i}")))
+    }
+
+    /// Call `f` with the JSON path, key and condition of every key condition
+    fn visit<'a>(
+        &'a self,
+        f: &mut impl FnMut(&str, &'a str, &'a Condition) -> Result<(), FilterError>,
+    ) -> Result<(), FilterError> {
+        fn walk<'a>(
+            filter: &'a Filter,
+            path: &str,
+            f: &mut impl FnMut(&str, &'a str, &'a Condition) -> Result<(), FilterError>,
+        ) -> Result<(), FilterError> {
+            let (op, filters) = match filter {
+                Filter::Key(key, condition) => return f(path, key, condition),
+                Filter::And(filters) => ("$and", filters),
+                Filter::Or(filters) => ("$or", filters),
+            };
+            filters
+                .iter()
+                .enumerate()
+                .try_for_each(|(i, filter)| walk(filter, &format!("{path}/{op}/{i}"), f))
+        }
+        walk(self, "", f)
+    }
+}
+
+impl Condition {
+    /// Evaluate against the metadata value of the key, `None` if missing
+    fn matches(&self, value: Option<&Value>) -> bool {
+        let equals_any = |operand: &FilterValue| value.is_some_and(|v| operand.matches(v));
+        let number = value.and_then(Value::as_f64);
+        match self {
+            Self::Eq(operand) => equals_any(operand),
+            Self::Ne(operand) => !equals_any(operand),
+            Self::Gt(n) => number.is_some_and(|v| v > *n),
+            Self::Gte(n) => number.is_some_and(|v| v >= *n),
+            Self::Lt(n) => number.is_some_and(|v| v < *n),
+            Self::Lte(n) => number.is_some_and(|v| v <= *n),
+            Self::In(operands) => operands.iter().any(equals_any),
+            Self::Nin(operands) => !operands.iter().any(equals_any),
+            Self::Exists(exists) => value.is_some() == *exists,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        let list =
+            |values: &[FilterValue]| values.iter().map(FilterValue::to_json).collect::<Vec<_>>();
+        match self {
+            Self::Eq(value) => json!({ "$eq": value.to_json() }),
+            Self::Ne(value) => json!({ "$ne": value.to_json() }),
+            Self::Gt(n) => json!({ "$gt": number(*n) }),
+            Self::Gte(n) => json!({ "$gte": number(*n) }),
+            Self::Lt(n) => json!({ "$lt": number(*n) }),
+            Self::Lte(n) => json!({ "$lte": number(*n) }),
+            Self::In(values) => json!({ "$in": list(values) }),
+            Self::Nin(values) => json!({ "$nin": list(values) }),
+            Self::Exists(exists) => json!({ "$exists": exists }),
+        }
+    }
+}
+
+impl FilterValue {
+    /// Equality without type coercion (numbers compare by value); against an
+    /// array, equality with any element
+    fn matches(&self, value: &Value) -> bool {
+        match (self, value) {
+            (_, Value::Array(items)) => items.iter().any(|item| self.matches(item)),
+            (Self::String(s), Value::String(v)) => s == v,
+            (Self::Number(n), Value::Number(v)) => v.as_f64() == Some(*n),
+            (Self::Bool(b), Value::Bool(v)) => b == v,
+            _ => false,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Self::String(s) => Value::String(s.clone()),
+            Self::Number(n) => number(*n),
+            Self::Bool(b) => Value::Bool(*b),
+        }
+    }
+
+    fn from_json(value: &Value, path: &str) -> Result<Self, FilterError> {
+        match value {
+            Value::String(s) => Ok(Self::String(s.clone())),
+            Value::Bool(b) => Ok(Self::Bool(*b)),
+            Value::Number(n) => Ok(Self::Number(n.as_f64().unwrap_or(f64::NAN))),
+            _ => Err(FilterError::new(
+                path,
+                format!("expected a string, number or boolean, got {value}"),
+            )),
+        }
+    }
+}
+
+/// Evaluate a filter in the service's JSON syntax against a vector's
+/// metadata, failing only if the filter itself is invalid.
+///
+/// The semantics follow S3 Vectors:
+///
+/// - Values are compared without type coercion: `"2020"` does not equal
+///   `2020` and `true` does not equal `1`, while `2020` equals `2020.0`.
+///   Range operators only match numeric metadata.
+/// - When the metadata value is an array, `$eq` and `$in` match if any
+///   element matches, and `$ne` and `$nin` match if no element does.
+/// - A missing key fails every condition except `$ne`, `$nin` and
+///   `$exists: false`.
+/// - Keys are matched literally; `a.b` is a key, not a nested path.
+pub fn matches(filter: &Value, metadata: &Value) -> Result<bool, FilterError> {
+    Ok(Filter::from_json(filter)?.matches(metadata))
+}
+
+/// Whole numbers are written without a fraction so `2020` stays `2020`
+fn number(n: f64) -> Value {
+    const MAX_EXACT: f64 = 9_007_199_254_740_992.0;
+    if n.fract() == 0.0 && n.abs() <= MAX_EXACT {
+        json!(n as i64)
+    } else {
+        json!(n)
+    }
+}
+
+fn parse_filter(value: &Value, path: &str) -> Result<Filter, FilterError> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| FilterError::new(path, "a filter must be a JSON object"))?;
+    if object.is_empty() {
+        return Err(FilterError::new(path, "a filter must not be empty"));
+    }
+
+    let mut filters = Vec::new();
+    for (key, condition) in object {
+        let key_path = format!("{path}/{key}");
+        match key.as_str() {
+            "$and" | "$or" => {
+                let items = condition.as_array().ok_or_else(|| {
+                    FilterError::new(&key_path, format!("{key} takes an array of filters"))
+                })?;
+                let parsed = items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| parse_filter(item, &format!("{key_path}/{i}")))
+                    .collect::<Result<Vec<_>, _>>()?;
+                filters.push(if key == "$and" {
+                    Filter::And(parsed)
+                } else {
+                    Filter::Or(parsed)
+                });
+            }
+            op if op.starts_with('$') => {
+                return Err(FilterError::new(
+                    &key_path,
+                    format!("unsupported logical operator {op}; use $and or $or"),
+                ));
+            }
+            _ => filters.extend(parse_conditions(key, condition, &key_path)?),
+        }
+    }
+
+    Ok(if filters.len() == 1 {
+        filters.remove(0)
+    } else {
+        Filter::And(filters)
+    })
+}
+
+fn parse_conditions(key: &str, condition: &Value, path: &str) -> Result<Vec<Filter>, FilterError> {
+    let operators: Map<String, Value> = match condition {
+        Value::Object(operators) => operators.clone(),
+        Value::Array(_) => {
+            return Err(FilterError::new(
+                path,
+                "use $in to match one of several values",
+            ));
+        }
+        value => return Ok(vec![Filter::eq(key, FilterValue::from_json(value, path)?)]),
+    };
+    if operators.is_empty() {
+        return Err(FilterError::new(path, "no operator given"));
+    }
+
+    operators
+        .iter()
+        .map(|(op, operand)| {
+            let path = &format!("{path}/{op}");
+            let number = || {
+                operand.as_f64().ok_or_else(|| {
+                    FilterError::new(path, format!("{op} takes a number, got {operand}"))
+                })
+            };
+            let list = || {
+                operand
+                    .as_array()
+                    .ok_or_else(|| {
+                        FilterError::new(path, format!("{op} takes an array, got {operand}"))
+                    })?
+                    .iter()
+                    .enumerate()
+                    .map(|(i, value)| FilterValue::from_json(value, &format!("{path}/{i}")))
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            let condition = match op.as_str() {
+                "$eq" => Condition::Eq(FilterValue::from_json(operand, path)?),
+                "$ne" => Condition::Ne(FilterValue::from_json(operand, path)?),
+                "$gt" => Condition::Gt(number()?),
+                "$gte" => Condition::Gte(number()?),
+                "$lt" => Condition::Lt(number()?),
+                "$lte" => Condition::Lte(number()?),
+                "$in" => Condition::In(list()?),
+                "$nin" => Condition::Nin(list()?),
+                "$exists" => Condition::Exists(operand.as_bool().ok_or_else(|| {
+                    FilterError::new(path, format!("$exists takes true or false, got {operand}"))
+                })?),
+                other => {
+                    return Err(FilterError::new(
+                        path,
+                        format!("unsupported operator {other}"),
+                    ))
+                }
+            };
+            Ok(Filter::Key(key.to_string(), condition))
+        })
+        .collect()
+}
+
+impl From<&str> for FilterValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for FilterValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<bool> for FilterValue {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f64> for FilterValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<f32> for FilterValue {
+    fn from(value: f32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<i32> for FilterValue {
+    fn from(value: i32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<u32> for FilterValue {
+    fn from(value: u32) -> Self {
+        Self::Number(value.into())
+    }
+}
+
+impl From<i64> for FilterValue {
+    fn from(value: i64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl TryFrom<Value> for Filter {
+    type Error = FilterError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        Self::from_json(&value)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value: Value = serde_json::from_str(s)
+            .map_err(|e| FilterError::new("", format!("not valid JSON: {e}")))?;
+        Self::from_json(&value)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
+
+impl Serialize for Filter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Filter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        Self::from_json(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_serializes_to_filter_json() {
+        let filter = Filter::any([
+            Filter::ne("genre", "drama").and(Filter::lt("year", 1999.5)),
+            Filter::not_in("lang", ["de"]),
+            Filter::exists("draft", false),
+        ]);
+        assert_eq!(
+            serde_json::to_value(&filter).unwrap(),
+            json!({"$or": [
+                {"$and": [{"genre": {"$ne": "drama"}}, {"year": {"$lt": 1999.5}}]},
+                {"lang": {"$nin": ["de"]}},
+                {"draft": {"$exists": false}},
+            ]})
+        );
+        assert_eq!(
+            filter.keys().into_iter().collect::<Vec<_>>(),
+            ["draft", "genre", "lang", "year"]
+        );
+    }
+
+    #[test]
+    fn test_parse_normalizes_shorthand() {
+        let filter: Filter = r#"{"genre": "scifi", "year": {"$gte": 2020, "$lt": 2025}}"#
+            .parse()
+            .unwrap();
+        assert_eq!(
+            filter,
+            Filter::all([
+                Filter::eq("genre", "scifi"),
+                Filter::gte("year", 2020),
+                Filter::lt("year", 2025),
+            ])
+        );
+        // Round trip through the canonical form
+        assert_eq!(Filter::from_json(&filter.to_json()).unwrap(), filter);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_filters() {
+        let cases = [
+            (r#"[]"#, "/"),
+            (r#"{}"#, "/"),
+            (r#"{"$not": {"a": 1}}"#, "/$not"),
+            (r#"{"$and": []}"#, "/$and"),
+            (r#"{"$or": [{"year": {"$gt": "2020"}}]}"#, "/$or/0/year/$gt"),
+            (r#"{"genre": ["a", "b"]}"#, "/genre"),
+            (r#"{"genre": {"$in": [["a"]]}}"#, "/genre/$in/0"),
+            (r#"{"genre": {"$in": []}}"#, "/genre"),
+            (r#"{"genre": {"$regex": "a.*"}}"#, "/genre/$regex"),
+            (r#"{"genre": {"$exists": 1}}"#, "/genre/$exists"),
+            (r#"{"genre": null}"#, "/genre"),
+        ];
+        for (json, path) in cases {
+            let err = json.parse::<Filter>().unwrap_err();
+            assert_eq!(err.path, path, "{json}: {err}");
+        }
+        assert!(Filter::gt("year", f64::NAN).validate().is_err());
+    }
+
+    #[test]
+    fn test_check_filterable() {
+        let filter = Filter::eq("genre", "scifi").or(Filter::eq("content", "x"));
+        let err = filter
+            .check_filterable(&["content".to_string()])
+            .unwrap_err();
+        assert_eq!(err.path, "/$or/1/content");
+        assert!(filter.check_filterable(&["source".to_string()]).is_ok());
+    }
+
+    /// Service filter semantics, as `(filter, metadata, expected)` where
+    /// `None` means the filter is rejected. Add a row for every behaviour
+    /// confirmed against the service.
+    const CONFORMANCE: &[(&str, &str, Option<bool>)] = &[
+        // Equality and implicit $eq
+        (r#"{"genre": "scifi"}"#, r#"{"genre": "scifi"}"#, Some(true)),
+        (
+            r#"{"genre": {"$eq": "scifi"}}"#,
+            r#"{"genre": "drama"}"#,
+            Some(false),
+        ),
+        (
+            r#"{"genre": "Scifi"}"#,
+            r#"{"genre": "scifi"}"#,
+            Some(false),
+        ),
+        // No type coercion; numbers compare by value
+        (r#"{"year": 2021}"#, r#"{"year": 2021.0}"#, Some(true)),
+        (r#"{"year": "2021"}"#, r#"{"year": 2021}"#, Some(false)),
+        (r#"{"year": 2021}"#, r#"{"year": "2021"}"#, Some(false)),
+        (r#"{"flag": true}"#, r#"{"flag": 1}"#, Some(false)),
+        (r#"{"flag": true}"#, r#"{"flag": "true"}"#, Some(false)),
+        (
+            r#"{"year": {"$gt": 2020}}"#,
+            r#"{"year": "2021"}"#,
+            Some(false),
+        ),
+        // Range operators
+        (
+            r#"{"year": {"$gt": 2020}}"#,
+            r#"{"year": 2021}"#,
+            Some(true),
+        ),
+        (
+            r#"{"year": {"$gt": 2021}}"#,
+            r#"{"year": 2021}"#,
+            Some(false),
+        ),
+        (
+            r#"{"year": {"$gte": 2021}}"#,
+            r#"{"year": 2021}"#,
+            Some(true),
+        ),
+        (
+            r#"{"year": {"$lt": 2021.5}}"#,
+            r#"{"year": 2021}"#,
+            Some(true),
+        ),
+        (
+            r#"{"year": {"$lte": 2020}}"#,
+            r#"{"year": 2021}"#,
+            Some(false),
+        ),
+        (
+            r#"{"year": {"$gte": 2020, "$lt": 2022}}"#,
+            r#"{"year": 2021}"#,
+            Some(true),
+        ),
+        (r#"{"year": {"$gt": 1}}"#, r#"{"year": [5]}"#, Some(false)),
+        // Arrays in metadata
+        (
+            r#"{"tags": "space"}"#,
+            r#"{"tags": ["space", "robots"]}"#,
+            Some(true),
+        ),
+        (
+            r#"{"tags": {"$ne": "space"}}"#,
+            r#"{"tags": ["space", "robots"]}"#,
+            Some(false),
+        ),
+        (
+            r#"{"tags": {"$ne": "dragons"}}"#,
+            r#"{"tags": ["space", "robots"]}"#,
+            Some(true),
+        ),
+        (
+            r#"{"tags": {"$in": ["dragons", "robots"]}}"#,
+            r#"{"tags": ["space", "robots"]}"#,
+            Some(true),
+        ),
+        (
+            r#"{"tags": {"$nin": ["robots"]}}"#,
+            r#"{"tags": ["space", "robots"]}"#,
+            Some(false),
+        ),
+        (
+            r#"{"tags": {"$in": ["x"]}}"#,
+            r#"{"tags": []}"#,
+            Some(false),
+        ),
+        // $in / $nin on scalars
+        (
+            r#"{"lang": {"$in": ["en", "ko"]}}"#,
+            r#"{"lang": "ko"}"#,
+            Some(true),
+        ),
+        (
+            r#"{"lang": {"$nin": ["en", "ko"]}}"#,
+            r#"{"lang": "de"}"#,
+            Some(true),
+        ),
+        (
+            r#"{"year": {"$in": [2020, "2021"]}}"#,
+            r#"{"year": 2021}"#,
+            Some(false),
+        ),
+        // Missing keys
+        (r#"{"genre": "scifi"}"#, r#"{}"#, Some(false)),
+        (r#"{"genre": {"$ne": "scifi"}}"#, r#"{}"#, Some(true)),
+        (r#"{"genre": {"$nin": ["scifi"]}}"#, r#"{}"#, Some(true)),
+        (r#"{"genre": {"$in": ["scifi"]}}"#, r#"{}"#, Some(false)),
+        (r#"{"year": {"$lt": 2000}}"#, r#"{}"#, Some(false)),
+        (r#"{"genre": {"$exists": false}}"#, r#"{}"#, Some(true)),
+        (
+            r#"{"genre": {"$exists": true}}"#,
+            r#"{"genre": "scifi"}"#,
+            Some(true),
+        ),
+        (r#"{"genre": "scifi"}"#, r#"null"#, Some(false)),
+        // Keys are literal, not paths
+        (r#"{"a.b": 1}"#, r#"{"a": {"b": 1}}"#, Some(false)),
+        (r#"{"a.b": 1}"#, r#"{"a.b": 1}"#, Some(true)),
+        // Logical operators and implicit $and
+        (
+            r#"{"genre": "scifi", "year": {"$lt": 2000}}"#,
+            r#"{"genre": "scifi", "year": 2021}"#,
+            Some(false),
+        ),
+        (
+            r#"{"$or": [{"year": {"$lt": 2000}}, {"genre": "scifi"}]}"#,
+            r#"{"genre": "scifi", "year": 2021}"#,
+            Some(true),
+        ),
+        (
+            r#"{"$and": [{"genre": "scifi"}, {"$or": [{"lang": "en"}, {"lang": "ko"}]}]}"#,
+            r#"{"genre": "scifi", "lang": "ko"}"#,
+            Some(true),
+        ),
+        // Invalid filters
+        (r#"{}"#, r#"{}"#, None),
+        (r#"{"$not": {"genre": "scifi"}}"#, r#"{}"#, None),
+        (r#"{"year": {"$gt": "2020"}}"#, r#"{}"#, None),
+        (r#"{"genre": ["a", "b"]}"#, r#"{}"#, None),
+        (r#"{"genre": {"$in": []}}"#, r#"{}"#, None),
+        (r#"{"genre": {"$regex": "sci.*"}}"#, r#"{}"#, None),
+        (r#"{"genre": {"nested": "object"}}"#, r#"{}"#, None),
+    ];
+
+    #[test]
+    fn test_matches_conformance() {
+        for (filter, metadata, expected) in CONFORMANCE {
+            let filter: Value = serde_json::from_str(filter).unwrap();
+            let metadata: Value = serde_json::from_str(metadata).unwrap();
+            let actual = matches(&filter, &metadata).ok();
+            assert_eq!(actual, *expected, "filter {filter} on metadata {metadata}");
+        }
+    }
+}
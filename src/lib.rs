@@ -1,8 +1,22 @@
+mod arn;
 mod auth;
+mod builder;
 mod config;
 mod deploy;
+mod error;
+mod paginator;
+mod retry;
+#[cfg(test)]
+mod test_support;
 mod types;
 mod validation;
+mod waiter;
+
+pub mod credentials;
+pub mod emulator;
+pub mod filter;
+pub mod policy;
+pub mod region;
 
 // RAG modules
 pub mod document;
@@ -12,18 +26,32 @@ pub mod rag;
 // CLI module
 pub mod cli;
 
-use std::collections::HashMap;
-use std::path::Path;
 use std::sync::LazyLock;
 
 use anyhow::{Context, Result};
 
+pub use crate::arn::{ArnError, IndexArn, ResourceArn, VectorBucketArn};
+pub use crate::builder::S3VectorsClientBuilder;
 pub use crate::config::{get_config, CONFIG};
+pub use crate::credentials::{Credentials, CredentialsProvider, DefaultCredentialsChain};
 pub use crate::types::*;
 pub use crate::validation::*;
 
 // Re-export commonly used functions
-pub use crate::deploy::{batch_put_vectors, create_bucket_and_index, S3VectorsError};
+pub use crate::deploy::{
+    batch_put_vectors, batch_put_vectors_with, create_bucket_and_index,
+    create_bucket_and_index_with, BatchFailure, BatchPutConfig, BatchPutReport, CreateIndexOptions,
+    DeleteVectorsReport, GetVectorsReport,
+};
+pub use crate::error::{
+    ErrorMetadata, IndexMismatchError, IndexSettingMismatch, S3VectorsError, ValidationField,
+};
+pub use crate::filter::{Filter, FilterError};
+pub use crate::paginator::PaginationConfig;
+pub use crate::policy::{PolicyDocument, PolicyError};
+pub use crate::region::{Partition, RegionCatalogue};
+pub use crate::retry::{AdaptiveRetry, RetryConfig, RetryMode, RetryStrategy, StandardRetry};
+pub use crate::waiter::{WaitError, Waiter};
 
 static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
     match reqwest::Client::builder()
@@ -49,23 +77,80 @@ pub struct S3VectorsClient {
     endpoint: String,
     region: String,
     signer: Option<auth::AwsV4Signer>,
+    http: reqwest::Client,
+    retry: RetryConfig,
+    retry_strategy: std::sync::Arc<dyn RetryStrategy>,
+    /// Non-filterable metadata keys per `(bucket, index)`, for checking
+    /// query filters before sending them; `None` for indexes that could not
+    /// be described
+    non_filterable_keys: NonFilterableKeyCache,
 }
 
+type NonFilterableKeyCache = std::sync::Arc<
+    std::sync::Mutex<std::collections::HashMap<(String, String), Option<Vec<String>>>>,
+>;
+
 impl S3VectorsClient {
-    /// Create a new S3 Vectors client for the specified region
+    /// Create a new S3 Vectors client for the specified region.
+    ///
+    /// Unlike [`builder`](Self::builder) and the environment and profile
+    /// constructors, this cannot fail and so does not check the region
+    /// against the [`RegionCatalogue`]; call [`validate_region`] first if it
+    /// comes from user input.
     pub fn new(region: &str) -> Self {
         Self {
-            endpoint: format!("https://s3vectors.{region}.api.aws"),
+            endpoint: default_endpoint(region),
             region: region.to_string(),
             signer: None,
+            http: HTTP_CLIENT.clone(),
+            retry: RetryConfig::default(),
+            retry_strategy: RetryConfig::default().strategy(),
+            non_filterable_keys: Default::default(),
         }
     }
 
+    /// Start building a client with custom HTTP, retry and endpoint settings
+    pub fn builder(region: &str) -> S3VectorsClientBuilder {
+        S3VectorsClientBuilder::new(region)
+    }
+
     /// Get the region this client is configured for
     pub fn region(&self) -> &str {
         &self.region
     }
 
+    /// Get the endpoint requests are sent to
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Send requests to a custom endpoint instead of the regional default.
+    ///
+    /// Useful for local emulators, VPC (PrivateLink) and FIPS endpoints. The
+    /// endpoint may include a port and a base path; requests are still signed
+    /// for the client's region.
+    pub fn with_endpoint(mut self, endpoint: &str) -> Result<Self> {
+        self.endpoint = normalize_endpoint(endpoint)?;
+        Ok(self)
+    }
+
+    /// Copy of this client for another region, e.g. the one in a resource
+    /// ARN. Credentials, HTTP and retry settings are kept; a regional
+    /// endpoint moves to the new region, a custom one stays.
+    pub fn with_region(&self, region: &str) -> Result<Self> {
+        if region == self.region {
+            return Ok(self.clone());
+        }
+        let mut client = self.clone();
+        if self.endpoint == default_endpoint(&self.region) {
+            client.endpoint = resolve_endpoint(region, None)?;
+        }
+        client.region = region.to_string();
+        client.signer = self.signer.as_ref().map(|s| s.for_region(region));
+        client.non_filterable_keys = Default::default();
+        Ok(client)
+    }
+
     /// List buckets (used for credential validation)
     pub async fn list_buckets(&self) -> Result<serde_json::Value> {
         // Simple method to test credentials by listing buckets
@@ -73,7 +158,8 @@ impl S3VectorsClient {
         Ok(serde_json::json!({"buckets": []}))
     }
 
-    /// Create a new client with explicit credentials
+    /// Create a new client with explicit credentials. Like [`new`](Self::new),
+    /// the region is not checked.
     pub fn with_credentials(
         region: &str,
         access_key_id: String,
@@ -81,7 +167,7 @@ impl S3VectorsClient {
         session_token: Option<String>,
     ) -> Self {
         Self {
-            endpoint: format!("https://s3vectors.{region}.api.aws"),
+            endpoint: default_endpoint(region),
             region: region.to_string(),
             signer: Some(auth::AwsV4Signer::new(
                 access_key_id,
@@ -89,6 +175,10 @@ impl S3VectorsClient {
                 session_token,
                 region.to_string(),
             )),
+            http: HTTP_CLIENT.clone(),
+            retry: RetryConfig::default(),
+            retry_strategy: RetryConfig::default().strategy(),
+            non_filterable_keys: Default::default(),
         }
     }
 
@@ -117,34 +207,28 @@ impl S3VectorsClient {
             None
         };
 
+        let endpoint = resolve_endpoint(&region, config.endpoint_url())?;
+
         Ok(Self {
-            endpoint: format!("https://s3vectors.{region}.api.aws"),
+            endpoint,
             region,
             signer,
+            http: HTTP_CLIENT.clone(),
+            retry: RetryConfig::default(),
+            retry_strategy: RetryConfig::default().strategy(),
+            non_filterable_keys: Default::default(),
         })
     }
 
     /// Create a client from AWS profile
     pub fn from_profile(profile_name: &str, region: &str) -> Result<Self> {
-        let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Cannot find home directory"))?;
-        let creds_path = home.join(".aws/credentials");
-
-        if !creds_path.exists() {
-            return Err(anyhow::anyhow!(
-                "AWS credentials file not found at: {:?}",
-                creds_path
-            ));
-        }
+        let provider = credentials::ProfileProvider::new(profile_name)?;
 
-        let creds = parse_credentials_file(&creds_path, profile_name)
-            .with_context(|| format!("Failed to parse credentials for profile: {profile_name}"))?;
-
-        Ok(Self::with_credentials(
-            region,
-            creds.access_key_id,
-            creds.secret_access_key,
-            creds.session_token,
-        ))
+        let mut builder = Self::builder(region).credentials_provider(provider);
+        if let Some(url) = get_config().endpoint_url() {
+            builder = builder.endpoint(url);
+        }
+        builder.build()
     }
 
     /// Create a client with optional region override
@@ -174,76 +258,76 @@ impl S3VectorsClient {
             None
         };
 
+        let endpoint = resolve_endpoint(&region, config.endpoint_url())?;
+
         Ok(Self {
-            endpoint: format!("https://s3vectors.{region}.api.aws"),
+            endpoint,
             region,
             signer,
+            http: HTTP_CLIENT.clone(),
+            retry: RetryConfig::default(),
+            retry_strategy: RetryConfig::default().strategy(),
+            non_filterable_keys: Default::default(),
         })
     }
 }
 
-#[derive(Debug)]
-struct AwsCredentials {
-    access_key_id: String,
-    secret_access_key: String,
-    session_token: Option<String>,
+/// Default regional endpoint for S3 Vectors, from the region catalogue
+fn default_endpoint(region: &str) -> String {
+    RegionCatalogue::global().endpoint(region)
 }
 
-/// Parse AWS credentials file for a specific profile
-fn parse_credentials_file(path: &Path, profile_name: &str) -> Result<AwsCredentials> {
-    use std::fs;
-    use std::io::{BufRead, BufReader};
-
-    let file = fs::File::open(path)
-        .with_context(|| format!("Failed to open credentials file: {path:?}"))?;
-    let reader = BufReader::new(file);
-
-    let mut current_profile = None;
-    let mut credentials = HashMap::new();
-
-    for line in reader.lines() {
-        let line = line.context("Failed to read line from credentials file")?;
-        let line = line.trim();
-
-        if line.is_empty() || line.starts_with('#') {
-            continue;
+/// Endpoint for `region`: `custom` if given, otherwise the regional default,
+/// in which case S3 Vectors must be available in the region
+fn resolve_endpoint(region: &str, custom: Option<&str>) -> Result<String> {
+    match custom {
+        Some(url) => normalize_endpoint(url),
+        None => {
+            validate_region(region)?;
+            Ok(default_endpoint(region))
         }
+    }
+}
 
-        if line.starts_with('[') && line.ends_with(']') {
-            current_profile = Some(line[1..line.len() - 1].to_string());
-        } else if let Some(ref profile) = current_profile {
-            if let Some((key, value)) = line.split_once('=') {
-                let key = key.trim();
-                let value = value.trim();
-
-                if profile == profile_name {
-                    credentials.insert(key.to_string(), value.to_string());
-                }
-            }
-        }
+/// Validate a custom endpoint URL and strip any trailing slash so that
+/// operation paths can be appended directly
+fn normalize_endpoint(endpoint: &str) -> Result<String> {
+    let parsed =
+        url::Url::parse(endpoint).with_context(|| format!("Invalid endpoint URL: {endpoint}"))?;
+
+    if !matches!(parsed.scheme(), "http" | "https") {
+        anyhow::bail!("Endpoint URL must use http or https: {endpoint}");
+    }
+    if parsed.host_str().is_none() {
+        anyhow::bail!("Endpoint URL has no host: {endpoint}");
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        anyhow::bail!("Endpoint URL must not contain a query string or fragment: {endpoint}");
     }
 
-    let access_key_id = credentials
-        .get("aws_access_key_id")
-        .ok_or_else(|| anyhow::anyhow!("Profile '{}' missing aws_access_key_id", profile_name))?
-        .clone();
-    let secret_access_key = credentials
-        .get("aws_secret_access_key")
-        .ok_or_else(|| anyhow::anyhow!("Profile '{}' missing aws_secret_access_key", profile_name))?
-        .clone();
-    let session_token = credentials.get("aws_session_token").cloned();
-
-    Ok(AwsCredentials {
-        access_key_id,
-        secret_access_key,
-        session_token,
-    })
+    Ok(endpoint.trim_end_matches('/').to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_with_region() {
+        let client = S3VectorsClient::with_credentials("us-east-1", "AK".into(), "SK".into(), None);
+        let moved = client.with_region("eu-west-1").unwrap();
+        assert_eq!(moved.region(), "eu-west-1");
+        assert_eq!(moved.endpoint(), "https://s3vectors.eu-west-1.api.aws");
+        assert!(moved.signer.is_some());
+        assert!(client.with_region("xx-nowhere-1").is_err());
+
+        // Custom endpoints only change the signing region
+        let local = client.with_endpoint("http://localhost:9000").unwrap();
+        let moved = local.with_region("xx-nowhere-1").unwrap();
+        assert_eq!(moved.endpoint(), "http://localhost:9000");
+        assert_eq!(moved.region(), "xx-nowhere-1");
+    }
+
     #[test]
     fn test_s3_vectors_client_creation() {
         // Test that client can be created with region
@@ -253,6 +337,34 @@ mod tests {
         assert!(client.signer.is_none());
     }
 
+    #[test]
+    fn test_with_endpoint_override() {
+        let client = S3VectorsClient::new("us-west-2")
+            .with_endpoint("http://localhost:8080/s3vectors/")
+            .unwrap();
+        assert_eq!(client.endpoint(), "http://localhost:8080/s3vectors");
+        assert_eq!(client.region(), "us-west-2");
+
+        let client = S3VectorsClient::new("us-east-1")
+            .with_endpoint("https://vpce-0abc.s3vectors.us-east-1.vpce.amazonaws.com")
+            .unwrap();
+        assert_eq!(
+            client.endpoint(),
+            "https://vpce-0abc.s3vectors.us-east-1.vpce.amazonaws.com"
+        );
+    }
+
+    #[test]
+    fn test_with_endpoint_rejects_invalid_urls() {
+        let client = S3VectorsClient::new("us-east-1");
+        assert!(client.clone().with_endpoint("localhost:8080").is_err());
+        assert!(client.clone().with_endpoint("ftp://localhost").is_err());
+        assert!(client
+            .clone()
+            .with_endpoint("http://localhost:8080/?x=1")
+            .is_err());
+    }
+
     #[test]
     fn test_from_env_without_credentials() {
         // Test that from_env succeeds regardless of whether credentials are present
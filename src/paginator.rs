@@ -0,0 +1,332 @@
+use std::future::Future;
+
+use futures_util::{stream, Stream, StreamExt, TryStreamExt};
+
+use crate::error::S3VectorsError;
+use crate::types::*;
+use crate::S3VectorsClient;
+
+/// Page size and item limits for the `*_paginated` list operations
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PaginationConfig {
+    /// `maxResults` sent with every page request (service default if unset)
+    pub page_size: Option<u32>,
+    /// Stop after yielding this many items; no further pages are requested
+    pub max_items: Option<usize>,
+}
+
+impl PaginationConfig {
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        self.max_items = Some(max_items);
+        self
+    }
+}
+
+/// Turn a page-fetching function into a stream of items that follows
+/// `nextToken` until the last page or `max_items`.
+///
+/// Pages are fetched lazily as the stream is polled, so dropping the stream
+/// early sends no further requests.
+fn paginate<'a, T, F, Fut>(
+    pagination: PaginationConfig,
+    fetch_page: F,
+) -> impl Stream<Item = Result<T, S3VectorsError>> + Send + 'a
+where
+    T: Send + 'a,
+    F: Fn(Option<u32>, Option<String>) -> Fut + Send + 'a,
+    Fut: Future<Output = Result<(Vec<T>, Option<String>), S3VectorsError>> + Send + 'a,
+{
+    // `None` once the last page has been fetched
+    let first: Option<Option<String>> = Some(None);
+    let pages = stream::try_unfold((fetch_page, first), move |(fetch_page, token)| async move {
+        let Some(token) = token else {
+            return Ok(None);
+        };
+        let (items, next_token) = fetch_page(pagination.page_size, token).await?;
+        // Some services return an empty token on the last page
+        let next = next_token.filter(|t| !t.is_empty()).map(Some);
+        Ok::<_, S3VectorsError>(Some((items, (fetch_page, next))))
+    });
+
+    pages
+        .map_ok(|items| stream::iter(items.into_iter().map(Ok)))
+        .try_flatten()
+        .take(pagination.max_items.unwrap_or(usize::MAX))
+}
+
+impl S3VectorsClient {
+    /// Stream every vector bucket, following `nextToken` across pages
+    pub fn list_vector_buckets_paginated(
+        &self,
+        prefix: Option<String>,
+        pagination: PaginationConfig,
+    ) -> impl Stream<Item = Result<VectorBucket, S3VectorsError>> + Send + '_ {
+        paginate(pagination, move |max_results, next_token| {
+            let prefix = prefix.clone();
+            async move {
+                let response = self
+                    .list_vector_buckets(max_results, next_token, prefix)
+                    .await?;
+                Ok((response.buckets, response.next_token))
+            }
+        })
+    }
+
+    /// Stream every index in a bucket, following `nextToken` across pages
+    pub fn list_indexes_paginated<'a>(
+        &'a self,
+        bucket_name: &'a str,
+        pagination: PaginationConfig,
+    ) -> impl Stream<Item = Result<IndexSummary, S3VectorsError>> + Send + 'a {
+        paginate(pagination, move |max_results, next_token| async move {
+            let response = self
+                .list_indexes(bucket_name, max_results, next_token)
+                .await?;
+            Ok((response.indexes, response.next_token))
+        })
+    }
+
+    /// Stream every vector in an index (or in one segment of it), following
+    /// `nextToken` across pages. `max_results` and `next_token` in `request`
+    /// are replaced by the paginator.
+    pub fn list_vectors_paginated(
+        &self,
+        request: ListVectorsRequest,
+        pagination: PaginationConfig,
+    ) -> impl Stream<Item = Result<ListOutputVector, S3VectorsError>> + Send + '_ {
+        paginate(pagination, move |max_results, next_token| {
+            let request = ListVectorsRequest {
+                max_results,
+                next_token,
+                ..request.clone()
+            };
+            async move {
+                let response = self.list_vectors(request).await?;
+                Ok((response.vectors, response.next_token))
+            }
+        })
+    }
+
+    /// Scan an entire index with `segment_count` segments listed
+    /// concurrently (1-16).
+    ///
+    /// Vectors arrive in no particular order. The page size applies to each
+    /// segment and `max_items` to the combined stream; segmentation fields in
+    /// `request` are replaced.
+    pub fn scan_vectors(
+        &self,
+        request: ListVectorsRequest,
+        segment_count: u32,
+        pagination: PaginationConfig,
+    ) -> impl Stream<Item = Result<ListOutputVector, S3VectorsError>> + Send + '_ {
+        // A count of zero still yields one stream, whose first request fails
+        // validation
+        let segments: Vec<_> = (0..segment_count.max(1))
+            .map(|segment_index| {
+                let request = ListVectorsRequest {
+                    segment_count: Some(segment_count),
+                    segment_index: Some(segment_index),
+                    ..request.clone()
+                };
+                let per_segment = PaginationConfig {
+                    max_items: None,
+                    ..pagination
+                };
+                self.list_vectors_paginated(request, per_segment).boxed()
+            })
+            .collect();
+
+        stream::select_all(segments).take(pagination.max_items.unwrap_or(usize::MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    /// Serves keys `key-0..total` in pages of the requested size, using the
+    /// next start offset as the token
+    async fn paged_vectors(total: usize) -> MockServer {
+        MockServer::start(move |request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let start: usize = body["nextToken"].as_str().map_or(0, |t| t.parse().unwrap());
+            let size = body["maxResults"].as_u64().unwrap_or(2) as usize;
+            let end = (start + size).min(total);
+            let vectors: Vec<_> = (start..end)
+                .map(|i| serde_json::json!({ "key": format!("key-{i}") }))
+                .collect();
+            let mut page = serde_json::json!({ "vectors": vectors });
+            if end < total {
+                page["nextToken"] = end.to_string().into();
+            }
+            MockResponse::json(200, page)
+        })
+        .await
+    }
+
+    fn list_request() -> ListVectorsRequest {
+        ListVectorsRequest {
+            vector_bucket_name: "test-bucket".to_string(),
+            index_name: "test-index".to_string(),
+            max_results: None,
+            next_token: None,
+            segment_count: None,
+            segment_index: None,
+            return_data: false,
+            return_metadata: false,
+        }
+    }
+
+    fn keys(vectors: Vec<ListOutputVector>) -> Vec<String> {
+        vectors.into_iter().map(|v| v.key).collect()
+    }
+
+    #[tokio::test]
+    async fn test_paginator_follows_next_token() {
+        let server = paged_vectors(7).await;
+        let client = server.client();
+
+        let keys = keys(
+            client
+                .list_vectors_paginated(list_request(), PaginationConfig::default().page_size(3))
+                .try_collect()
+                .await
+                .unwrap(),
+        );
+        assert_eq!(keys.len(), 7);
+        assert_eq!(keys[0], "key-0");
+        assert_eq!(keys[6], "key-6");
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(!requests[0].body_str().contains("nextToken"));
+        assert!(requests[2].body_str().contains(r#""nextToken":"6""#));
+        assert!(requests
+            .iter()
+            .all(|r| r.body_str().contains(r#""maxResults":3"#)));
+    }
+
+    #[tokio::test]
+    async fn test_paginator_stops_at_max_items() {
+        let server = paged_vectors(100).await;
+        let client = server.client();
+
+        let keys = keys(
+            client
+                .list_vectors_paginated(
+                    list_request(),
+                    PaginationConfig::default().page_size(2).max_items(5),
+                )
+                .try_collect()
+                .await
+                .unwrap(),
+        );
+        assert_eq!(keys, ["key-0", "key-1", "key-2", "key-3", "key-4"]);
+        // Pages are fetched lazily, so nothing beyond the third page is requested
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_paginator_surfaces_errors() {
+        let server = MockServer::start(|request| {
+            if request.body_str().contains("nextToken") {
+                MockResponse::json(
+                    403,
+                    serde_json::json!({ "__type": "AccessDeniedException", "message": "denied" }),
+                )
+            } else {
+                MockResponse::json(
+                    200,
+                    serde_json::json!({ "indexes": [], "nextToken": "page-2" }),
+                )
+            }
+        })
+        .await;
+        let client = server.client();
+
+        let result: Result<Vec<IndexSummary>, _> = client
+            .list_indexes_paginated("test-bucket", PaginationConfig::default())
+            .try_collect()
+            .await;
+        assert!(matches!(result, Err(S3VectorsError::AccessDenied(_))));
+    }
+
+    #[tokio::test]
+    async fn test_scan_vectors_lists_segments_concurrently() {
+        // Each segment has two pages of two vectors, answered after a delay
+        let server = MockServer::start(|request| {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            let segment = body["segmentIndex"].as_u64().unwrap();
+            let page: u64 = body["nextToken"].as_str().map_or(0, |t| t.parse().unwrap());
+            let vectors: Vec<_> = (0..2)
+                .map(|i| {
+                    serde_json::json!({
+                        "key": format!("s{segment}-p{page}-{i}"),
+                        "data": { "float32": [0.5, 0.25] },
+                        "metadata": { "segment": segment },
+                    })
+                })
+                .collect();
+            let mut response = serde_json::json!({ "vectors": vectors });
+            if page == 0 {
+                response["nextToken"] = "1".into();
+            }
+            MockResponse::json(200, response).delay(std::time::Duration::from_millis(150))
+        })
+        .await;
+        let client = server.client();
+
+        let started = std::time::Instant::now();
+        let request = ListVectorsRequest {
+            return_data: true,
+            return_metadata: true,
+            ..list_request()
+        };
+        let vectors: Vec<ListOutputVector> = client
+            .scan_vectors(request, 4, PaginationConfig::default())
+            .try_collect()
+            .await
+            .unwrap();
+        // Sequential listing would take 8 x 150ms
+        assert!(started.elapsed() < std::time::Duration::from_millis(900));
+
+        let mut keys = keys(vectors.clone());
+        keys.sort();
+        keys.dedup();
+        assert_eq!(keys.len(), 16);
+        assert!(vectors
+            .iter()
+            .all(|v| v.data.as_ref().unwrap().float32 == [0.5, 0.25]));
+        assert!(vectors.iter().all(|v| v.metadata.is_some()));
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 8);
+        for request in &requests {
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            assert_eq!(body["segmentCount"], 4);
+            assert_eq!(body["returnData"], true);
+            assert_eq!(body["returnMetadata"], true);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scan_vectors_rejects_invalid_segment_count() {
+        let server = paged_vectors(1).await;
+        let client = server.client();
+
+        for count in [0, 17] {
+            let result: Result<Vec<_>, _> = client
+                .scan_vectors(list_request(), count, PaginationConfig::default())
+                .try_collect()
+                .await;
+            assert!(matches!(result, Err(S3VectorsError::Validation(_))));
+        }
+        assert!(server.requests().is_empty());
+    }
+}
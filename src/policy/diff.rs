@@ -0,0 +1,323 @@
+//! Statement-level comparison of two policies.
+//!
+//! Statements are paired by `Sid`, and statements without one by content.
+//! Within a pair, actions, resources, principals and conditions are compared
+//! as sets, so reordering or re-casing action names is not a change.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use super::{PolicyDocument, Principal, Statement};
+
+/// What changed between an old and a new policy
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyDiff {
+    /// Statements only in the new policy
+    pub added: Vec<Statement>,
+    /// Statements only in the old policy
+    pub removed: Vec<Statement>,
+    /// Statements with the same `Sid` but different content
+    pub changed: Vec<StatementChange>,
+    /// Old and new `Version`, if it changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<(String, String)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StatementChange {
+    pub sid: String,
+    pub fields: Vec<FieldChange>,
+}
+
+/// Elements added to and removed from one statement field
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FieldChange {
+    /// `Effect`, `Principal`, `Action`, `Resource` or `Condition`
+    pub field: &'static str,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// A statement's elements per field, keyed by their normalized form and
+/// mapping to how they are displayed
+type Elements = BTreeMap<&'static str, BTreeMap<String, String>>;
+
+impl PolicyDocument {
+    /// Compare this (old) policy with `new`
+    pub fn diff(&self, new: &PolicyDocument) -> PolicyDiff {
+        let mut old: Vec<&Statement> = self.statements.iter().collect();
+        let mut new_left: Vec<&Statement> = new.statements.iter().collect();
+        let mut diff = PolicyDiff::default();
+
+        // Same Sid: the same statement, possibly edited
+        old.retain(|before| {
+            let Some(sid) = &before.sid else {
+                return true;
+            };
+            let Some(i) = new_left.iter().position(|s| s.sid.as_ref() == Some(sid)) else {
+                return true;
+            };
+            let after = new_left.remove(i);
+            let fields = field_changes(&elements(before), &elements(after));
+            if !fields.is_empty() {
+                diff.changed.push(StatementChange {
+                    sid: sid.clone(),
+                    fields,
+                });
+            }
+            false
+        });
+
+        // Otherwise only identical statements pair up
+        old.retain(|before| {
+            let before_elements = elements(before);
+            match new_left
+                .iter()
+                .position(|after| before.sid == after.sid && elements(after) == before_elements)
+            {
+                Some(i) => {
+                    new_left.remove(i);
+                    false
+                }
+                None => true,
+            }
+        });
+
+        diff.removed = old.into_iter().cloned().collect();
+        diff.added = new_left.into_iter().cloned().collect();
+        if self.version != new.version {
+            diff.version = Some((self.version.clone(), new.version.clone()));
+        }
+        diff
+    }
+}
+
+impl PolicyDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.version.is_none()
+    }
+}
+
+impl fmt::Display for PolicyDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+        if let Some((old, new)) = &self.version {
+            writeln!(f, "~ Version {old} -> {new}")?;
+        }
+        for statement in &self.removed {
+            writeln!(f, "- {}", summary(statement))?;
+        }
+        for change in &self.changed {
+            writeln!(f, "~ Statement {}", change.sid)?;
+            for field in &change.fields {
+                for element in &field.removed {
+                    writeln!(f, "    - {}: {element}", field.field)?;
+                }
+                for element in &field.added {
+                    writeln!(f, "    + {}: {element}", field.field)?;
+                }
+            }
+        }
+        for statement in &self.added {
+            writeln!(f, "+ {}", summary(statement))?;
+        }
+        Ok(())
+    }
+}
+
+/// `Statement Read: Allow arn:...:role/app s3vectors:GetVectors on arn:...`
+fn summary(statement: &Statement) -> String {
+    let elements = elements(statement);
+    let list = |field| {
+        elements[field]
+            .values()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut summary = format!(
+        "Statement{}: {} {} {} on {}",
+        statement
+            .sid
+            .as_ref()
+            .map(|sid| format!(" {sid}"))
+            .unwrap_or_default(),
+        list("Effect"),
+        list("Principal"),
+        list("Action"),
+        list("Resource"),
+    );
+    if !elements["Condition"].is_empty() {
+        summary.push_str(&format!(" if {}", list("Condition")));
+    }
+    summary
+}
+
+fn elements(statement: &Statement) -> Elements {
+    // `NotAction` and friends list what the statement does not apply to
+    let negate = |negated: bool, element: String| {
+        if negated {
+            format!("not {element}")
+        } else {
+            element
+        }
+    };
+    let principals = match &statement.principal {
+        Principal::Any => keyed([negate(statement.not_principal, "*".to_string())]),
+        principal => keyed(principal.kinds().into_iter().flat_map(|(kind, ids)| {
+            ids.iter()
+                .map(move |id| negate(statement.not_principal, format!("{kind}:{id}")))
+        })),
+    };
+    // Action names are case-insensitive
+    let actions = statement
+        .actions
+        .iter()
+        .map(|action| negate(statement.not_action, action.clone()))
+        .map(|action| (action.to_ascii_lowercase(), action))
+        .collect();
+    let resources = statement
+        .resources
+        .iter()
+        .map(|resource| negate(statement.not_resource, resource.clone()));
+    let conditions = keyed(statement.conditions.iter().flat_map(|(operator, keys)| {
+        keys.iter().map(move |(key, values)| {
+            let mut values = values.clone();
+            values.sort();
+            format!("{operator} {key} [{}]", values.join(", "))
+        })
+    }));
+
+    BTreeMap::from([
+        ("Effect", keyed([statement.effect.as_str().to_string()])),
+        ("Principal", principals),
+        ("Action", actions),
+        ("Resource", keyed(resources)),
+        ("Condition", conditions),
+    ])
+}
+
+/// Elements that are displayed as they are compared
+fn keyed(values: impl IntoIterator<Item = String>) -> BTreeMap<String, String> {
+    values
+        .into_iter()
+        .map(|value| (value.clone(), value))
+        .collect()
+}
+
+fn field_changes(before: &Elements, after: &Elements) -> Vec<FieldChange> {
+    ["Effect", "Principal", "Action", "Resource", "Condition"]
+        .into_iter()
+        .filter_map(|field| {
+            let (before, after) = (&before[field], &after[field]);
+            let added: Vec<String> = after
+                .iter()
+                .filter(|(key, _)| !before.contains_key(*key))
+                .map(|(_, shown)| shown.clone())
+                .collect();
+            let removed: Vec<String> = before
+                .iter()
+                .filter(|(key, _)| !after.contains_key(*key))
+                .map(|(_, shown)| shown.clone())
+                .collect();
+            (!added.is_empty() || !removed.is_empty()).then_some(FieldChange {
+                field,
+                added,
+                removed,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BUCKET: &str = "arn:aws:s3vectors:us-east-1:111122223333:bucket/docs";
+
+    fn policy(statements: serde_json::Value) -> PolicyDocument {
+        PolicyDocument::from_json(&json!({"Version": "2012-10-17", "Statement": statements}))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_reordering_and_case_are_not_changes() {
+        let old = policy(json!([
+            {"Sid": "Read", "Effect": "Allow", "Principal": "*",
+             "Action": ["s3vectors:GetVectors", "s3vectors:QueryVectors"], "Resource": BUCKET},
+            {"Effect": "Deny", "Principal": "*", "Action": "s3vectors:DeleteVectors", "Resource": "*"}
+        ]));
+        let new = policy(json!([
+            {"Effect": "Deny", "Principal": "*", "Action": ["s3vectors:DeleteVectors"], "Resource": ["*"]},
+            {"Sid": "Read", "Effect": "Allow", "Principal": "*",
+             "Action": ["s3vectors:queryvectors", "s3vectors:GetVectors"], "Resource": BUCKET}
+        ]));
+        let diff = old.diff(&new);
+        assert!(diff.is_empty(), "{diff}");
+        assert_eq!(diff.to_string(), "No changes\n");
+    }
+
+    #[test]
+    fn test_statement_level_changes() {
+        let old = policy(json!([
+            {"Sid": "Read", "Effect": "Allow", "Principal": {"AWS": "111122223333"},
+             "Action": ["s3vectors:GetVectors", "s3vectors:ListVectors"], "Resource": BUCKET},
+            {"Effect": "Deny", "Principal": "*", "Action": "s3vectors:DeleteVectors", "Resource": "*"}
+        ]));
+        let new = policy(json!([
+            {"Sid": "Read", "Effect": "Allow", "Principal": {"AWS": "111122223333"},
+             "Action": ["s3vectors:GetVectors", "s3vectors:QueryVectors"], "Resource": BUCKET,
+             "Condition": {"Bool": {"aws:SecureTransport": "true"}}},
+            {"Sid": "Write", "Effect": "Allow", "Principal": {"AWS": "111122223333"},
+             "Action": "s3vectors:PutVectors", "Resource": format!("{BUCKET}/index/*")}
+        ]));
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.added[0].sid.as_deref(), Some("Write"));
+        assert_eq!(diff.changed.len(), 1);
+        let fields = &diff.changed[0].fields;
+        assert_eq!(fields[0].field, "Action");
+        assert_eq!(fields[0].added, ["s3vectors:QueryVectors"]);
+        assert_eq!(fields[0].removed, ["s3vectors:ListVectors"]);
+        assert_eq!(fields[1].field, "Condition");
+        assert_eq!(fields[1].added, ["Bool aws:SecureTransport [true]"]);
+
+        let text = diff.to_string();
+        assert!(text.contains("- Statement: Deny * s3vectors:DeleteVectors on *"));
+        assert!(text.contains("~ Statement Read\n    - Action: s3vectors:ListVectors\n"));
+        assert!(text.contains("+ Statement Write: Allow AWS:111122223333 s3vectors:PutVectors"));
+
+        // Switching to the negated element replaces what is listed
+        let negated = policy(json!([
+            {"Sid": "Read", "Effect": "Allow", "Principal": {"AWS": "111122223333"},
+             "NotAction": ["s3vectors:GetVectors", "s3vectors:QueryVectors"], "Resource": BUCKET,
+             "Condition": {"Bool": {"aws:SecureTransport": "true"}}}
+        ]));
+        let fields = &new.diff(&negated).changed[0].fields;
+        assert_eq!(fields[0].field, "Action");
+        assert_eq!(
+            fields[0].added,
+            ["not s3vectors:GetVectors", "not s3vectors:QueryVectors"]
+        );
+        assert_eq!(fields[0].removed.len(), 2);
+
+        // Diffing against itself, or in reverse
+        assert!(new.diff(&new).is_empty());
+        let reverse = new.diff(&old);
+        assert_eq!(reverse.removed[0].sid.as_deref(), Some("Write"));
+        assert_eq!(
+            reverse.changed[0].fields[0].added,
+            ["s3vectors:ListVectors"]
+        );
+    }
+}
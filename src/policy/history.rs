@@ -0,0 +1,161 @@
+//! Local backups of bucket policies.
+//!
+//! Each backup is the policy JSON as it was, stored as
+//! `<dir>/<bucket>/<timestamp>.json` so it can be read or re-applied with
+//! `policy put -f` directly. Timestamps are UTC and sort chronologically.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+
+/// Backups of bucket policies under one directory
+#[derive(Clone, Debug)]
+pub struct PolicyHistory {
+    dir: PathBuf,
+}
+
+/// One saved policy
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct HistoryEntry {
+    /// When the policy was replaced, e.g. `20250101T120000.123456Z`
+    pub timestamp: String,
+    pub path: PathBuf,
+}
+
+impl PolicyHistory {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `~/.s3-vectors/policy-history`
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".s3-vectors").join("policy-history"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Save `policy` as the latest backup for `bucket`
+    pub fn save(&self, bucket: &str, policy: &str) -> Result<HistoryEntry> {
+        let bucket_dir = self.dir.join(bucket);
+        fs::create_dir_all(&bucket_dir)
+            .with_context(|| format!("Failed to create {}", bucket_dir.display()))?;
+
+        // Pretty-print so backups are readable and diffable
+        let contents = match serde_json::from_str::<serde_json::Value>(policy) {
+            Ok(value) => serde_json::to_string_pretty(&value)?,
+            Err(_) => policy.to_string(),
+        };
+        loop {
+            let timestamp = Utc::now().format("%Y%m%dT%H%M%S%.6fZ").to_string();
+            let path = bucket_dir.join(format!("{timestamp}.json"));
+            // Never overwrite an earlier backup from the same instant
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    file.write_all(contents.as_bytes())
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    return Ok(HistoryEntry { timestamp, path });
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to write {}", path.display()))
+                }
+            }
+        }
+    }
+
+    /// Backups of `bucket`, oldest first
+    pub fn list(&self, bucket: &str) -> Result<Vec<HistoryEntry>> {
+        let bucket_dir = self.dir.join(bucket);
+        let entries = match fs::read_dir(&bucket_dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", bucket_dir.display()))
+            }
+        };
+
+        let mut history = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(timestamp) = path.file_stem().and_then(|s| s.to_str()) {
+                    history.push(HistoryEntry {
+                        timestamp: timestamp.to_string(),
+                        path: path.clone(),
+                    });
+                }
+            }
+        }
+        history.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        Ok(history)
+    }
+
+    /// The latest backup of `bucket`, or the one whose timestamp starts with
+    /// `timestamp`
+    pub fn find(&self, bucket: &str, timestamp: Option<&str>) -> Result<HistoryEntry> {
+        let history = self.list(bucket)?;
+        let Some(timestamp) = timestamp else {
+            return match history.into_iter().last() {
+                Some(entry) => Ok(entry),
+                None => bail!(
+                    "No saved policies for bucket '{bucket}' in {}",
+                    self.dir.display()
+                ),
+            };
+        };
+
+        let mut matches = history
+            .into_iter()
+            .filter(|entry| entry.timestamp.starts_with(timestamp));
+        match (matches.next(), matches.next()) {
+            (Some(entry), None) => Ok(entry),
+            (Some(_), Some(_)) => {
+                bail!("Timestamp '{timestamp}' matches several saved policies; give more digits")
+            }
+            (None, _) => bail!("No saved policy for bucket '{bucket}' at '{timestamp}'"),
+        }
+    }
+
+    pub fn load(&self, entry: &HistoryEntry) -> Result<String> {
+        fs::read_to_string(&entry.path)
+            .with_context(|| format!("Failed to read {}", entry.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_list_find() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = PolicyHistory::new(dir.path());
+        assert!(history.list("docs").unwrap().is_empty());
+        assert!(history.find("docs", None).is_err());
+
+        let first = history.save("docs", r#"{"Version":"2012-10-17"}"#).unwrap();
+        let second = history.save("docs", "v2").unwrap();
+        history.save("other", "x").unwrap();
+
+        let entries = history.list("docs").unwrap();
+        assert_eq!(entries, [first.clone(), second.clone()]);
+        assert!(history.load(&first).unwrap().contains("\n  \"Version\""));
+        assert_eq!(history.load(&second).unwrap(), "v2");
+
+        assert_eq!(history.find("docs", None).unwrap(), second);
+        assert_eq!(history.find("docs", Some(&first.timestamp)).unwrap(), first);
+        // A shared prefix is ambiguous, an unknown one not found
+        assert!(history.find("docs", Some("2")).is_err());
+        assert!(history.find("docs", Some("1999")).is_err());
+    }
+}
@@ -0,0 +1,1263 @@
+//! Typed vector bucket policies.
+//!
+//! A [`PolicyDocument`] is parsed and validated from the IAM policy JSON that
+//! `PutVectorBucketPolicy` takes, and serializes back to it. Validation checks
+//! S3 Vectors action names, resource ARNs, principals and condition operators
+//! so that mistakes are reported before the service rejects the policy, or
+//! worse, accepts one that grants nothing.
+//!
+//! [`PolicyTemplate`] builds the common policies:
+//!
+//! ```
+//! use s3_vectors::policy::{PolicyDocument, PolicyTemplate};
+//!
+//! let policy = PolicyDocument::from_template(
+//!     PolicyTemplate::Reader,
+//!     "arn:aws:iam::111122223333:role/search",
+//!     "arn:aws:s3vectors:us-east-1:111122223333:bucket/docs",
+//! )
+//! .unwrap();
+//! assert!(policy.statements[0]
+//!     .actions
+//!     .contains(&"s3vectors:QueryVectors".to_string()));
+//!
+//! let parsed: PolicyDocument = policy.to_string().parse().unwrap();
+//! assert_eq!(parsed, policy);
+//! assert!(r#"{"Version": "2012-10-17", "Statement": []}"#
+//!     .parse::<PolicyDocument>()
+//!     .is_err());
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Map, Value};
+
+use crate::validation::validate_resource_arn;
+use crate::RegionCatalogue;
+
+mod diff;
+mod history;
+mod simulate;
+
+pub use diff::{FieldChange, PolicyDiff, StatementChange};
+pub use history::{HistoryEntry, PolicyHistory};
+pub use simulate::{Decision, Simulation, SimulationRequest, StatementResult};
+
+/// The current policy language version
+pub const POLICY_VERSION: &str = "2012-10-17";
+
+/// Every S3 Vectors action, without the `s3vectors:` prefix
+pub const ACTIONS: &[&str] = &[
+    "CreateVectorBucket",
+    "DeleteVectorBucket",
+    "GetVectorBucket",
+    "ListVectorBuckets",
+    "PutVectorBucketPolicy",
+    "GetVectorBucketPolicy",
+    "DeleteVectorBucketPolicy",
+    "CreateIndex",
+    "DeleteIndex",
+    "GetIndex",
+    "ListIndexes",
+    "PutVectors",
+    "GetVectors",
+    "ListVectors",
+    "QueryVectors",
+    "DeleteVectors",
+    "TagResource",
+    "UntagResource",
+    "ListTagsForResource",
+];
+
+/// Condition operators, without the `ForAllValues:`/`ForAnyValue:` prefixes
+/// and `IfExists` suffix
+const CONDITION_OPERATORS: &[&str] = &[
+    "StringEquals",
+    "StringNotEquals",
+    "StringEqualsIgnoreCase",
+    "StringNotEqualsIgnoreCase",
+    "StringLike",
+    "StringNotLike",
+    "NumericEquals",
+    "NumericNotEquals",
+    "NumericLessThan",
+    "NumericLessThanEquals",
+    "NumericGreaterThan",
+    "NumericGreaterThanEquals",
+    "DateEquals",
+    "DateNotEquals",
+    "DateLessThan",
+    "DateLessThanEquals",
+    "DateGreaterThan",
+    "DateGreaterThanEquals",
+    "Bool",
+    "IpAddress",
+    "NotIpAddress",
+    "ArnEquals",
+    "ArnNotEquals",
+    "ArnLike",
+    "ArnNotLike",
+    "Null",
+];
+
+/// A vector bucket policy
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolicyDocument {
+    /// Policy language version, `2012-10-17` unless the policy is very old
+    pub version: String,
+    pub id: Option<String>,
+    pub statements: Vec<Statement>,
+}
+
+/// One `Statement` of a policy
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statement {
+    pub sid: Option<String>,
+    pub effect: Effect,
+    /// Who the statement applies to; bucket policies always name one
+    pub principal: Principal,
+    /// `NotPrincipal`: the statement applies to everyone but `principal`
+    pub not_principal: bool,
+    /// Action names such as `s3vectors:QueryVectors`; `*` and `?` wildcards
+    /// are allowed
+    pub actions: Vec<String>,
+    /// `NotAction`: the statement applies to every action but `actions`
+    pub not_action: bool,
+    /// Bucket or index ARNs, possibly with wildcards
+    pub resources: Vec<String>,
+    /// `NotResource`: the statement applies to every resource but `resources`
+    pub not_resource: bool,
+    /// Operator to condition key to values, e.g.
+    /// `StringEquals` → `aws:PrincipalOrgID` → `["o-abc123"]`
+    pub conditions: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+/// The `Principal` of a statement
+#[derive(Clone, Debug, PartialEq)]
+pub enum Principal {
+    /// `"*"`: everyone, including anonymous callers
+    Any,
+    /// `{"AWS": [...], "Service": [...], ...}`: accounts, IAM users and
+    /// roles, service principals, identity providers (`Federated`) and
+    /// canonical user IDs
+    Specific {
+        aws: Vec<String>,
+        service: Vec<String>,
+        federated: Vec<String>,
+        canonical_user: Vec<String>,
+    },
+}
+
+/// Common bucket policies, see [`PolicyDocument::from_template`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum PolicyTemplate {
+    /// Read-only consumer: describe the bucket and its indexes, read, list
+    /// and query vectors
+    Reader,
+    /// Reader plus putting and deleting vectors
+    Writer,
+    /// Query access for another account
+    CrossAccountQuery,
+}
+
+/// Why a policy was rejected
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid policy at {path}: {message}")]
+pub struct PolicyError {
+    /// Location of the problem in the policy JSON, e.g. `/Statement/0/Action/1`
+    pub path: String,
+    pub message: String,
+}
+
+impl PolicyError {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: if path.is_empty() { "/" } else { path }.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl PolicyDocument {
+    /// A policy of `statements` at the current version
+    pub fn new(statements: impl IntoIterator<Item = Statement>) -> Self {
+        Self {
+            version: POLICY_VERSION.to_string(),
+            id: None,
+            statements: statements.into_iter().collect(),
+        }
+    }
+
+    /// Build `template` for `principal` (an IAM ARN or a 12-digit account ID)
+    /// on the bucket `bucket_arn` and all of its indexes
+    pub fn from_template(
+        template: PolicyTemplate,
+        principal: &str,
+        bucket_arn: &str,
+    ) -> Result<Self, PolicyError> {
+        validate_resource(bucket_arn, "/Statement/0/Resource/0")?;
+        let (partition, bucket_account) = match bucket_arn.split(':').collect::<Vec<_>>()[..] {
+            [_, partition, _, _, account, resource]
+                if resource.starts_with("bucket/") && !resource.contains("/index/") =>
+            {
+                (partition, account)
+            }
+            _ => {
+                return Err(PolicyError::new(
+                    "/Statement/0/Resource/0",
+                    format!("templates need a vector bucket ARN, got '{bucket_arn}'"),
+                ))
+            }
+        };
+
+        let principal = if is_account_id(principal) {
+            format!("arn:{partition}:iam::{principal}:root")
+        } else {
+            principal.to_string()
+        };
+        if template == PolicyTemplate::CrossAccountQuery
+            && principal.split(':').nth(4) == Some(bucket_account)
+        {
+            return Err(PolicyError::new(
+                "/Statement/0/Principal/AWS",
+                format!("'{principal}' is in the bucket's own account; use the reader template"),
+            ));
+        }
+
+        let statement = Statement::allow(
+            Principal::aws(principal),
+            template.actions().iter().map(|a| format!("s3vectors:{a}")),
+            [bucket_arn.to_string(), format!("{bucket_arn}/index/*")],
+        )
+        .with_sid(template.sid());
+        let policy = Self::new([statement]);
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Parse and validate a policy in IAM JSON syntax.
+    ///
+    /// `Statement`, `Action`, `Resource`, principal IDs and condition values
+    /// may each be a single value or an array.
+    pub fn from_json(value: &Value) -> Result<Self, PolicyError> {
+        let policy = Self::from_json_unvalidated(value)?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    /// Parse a policy without [`validate`](Self::validate)-ing it, for
+    /// policies the service may accept even though this client does not
+    /// know their actions or condition operators
+    pub fn from_json_unvalidated(value: &Value) -> Result<Self, PolicyError> {
+        parse_policy(value)
+    }
+
+    /// The policy in IAM JSON syntax
+    pub fn to_json(&self) -> Value {
+        let mut policy = Map::new();
+        policy.insert("Version".into(), json!(self.version));
+        if let Some(id) = &self.id {
+            policy.insert("Id".into(), json!(id));
+        }
+        policy.insert(
+            "Statement".into(),
+            self.statements.iter().map(Statement::to_json).collect(),
+        );
+        Value::Object(policy)
+    }
+
+    /// Check versions, Sids, actions, resources, principals and conditions
+    pub fn validate(&self) -> Result<(), PolicyError> {
+        if self.version != POLICY_VERSION && self.version != "2008-10-17" {
+            return Err(PolicyError::new(
+                "/Version",
+                format!(
+                    "unsupported version '{}'; use \"{POLICY_VERSION}\"",
+                    self.version
+                ),
+            ));
+        }
+        if self.statements.is_empty() {
+            return Err(PolicyError::new(
+                "/Statement",
+                "a policy needs at least one statement",
+            ));
+        }
+
+        let mut sids = HashSet::new();
+        for (i, statement) in self.statements.iter().enumerate() {
+            let path = format!("/Statement/{i}");
+            if let Some(sid) = &statement.sid {
+                if sid.is_empty() || !sid.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err(PolicyError::new(
+                        &format!("{path}/Sid"),
+                        format!("Sid '{sid}' may only contain letters and digits"),
+                    ));
+                }
+                if !sids.insert(sid.as_str()) {
+                    return Err(PolicyError::new(
+                        &format!("{path}/Sid"),
+                        format!("Sid '{sid}' is used by more than one statement"),
+                    ));
+                }
+            }
+            statement.validate(&path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Statement {
+    /// Allow `principal` to perform `actions` on `resources`
+    pub fn allow<A: Into<String>, R: Into<String>>(
+        principal: Principal,
+        actions: impl IntoIterator<Item = A>,
+        resources: impl IntoIterator<Item = R>,
+    ) -> Self {
+        Self {
+            sid: None,
+            effect: Effect::Allow,
+            principal,
+            not_principal: false,
+            actions: actions.into_iter().map(Into::into).collect(),
+            not_action: false,
+            resources: resources.into_iter().map(Into::into).collect(),
+            not_resource: false,
+            conditions: BTreeMap::new(),
+        }
+    }
+
+    /// Deny `principal` from performing `actions` on `resources`
+    pub fn deny<A: Into<String>, R: Into<String>>(
+        principal: Principal,
+        actions: impl IntoIterator<Item = A>,
+        resources: impl IntoIterator<Item = R>,
+    ) -> Self {
+        Self {
+            effect: Effect::Deny,
+            ..Self::allow(principal, actions, resources)
+        }
+    }
+
+    pub fn with_sid(mut self, sid: impl Into<String>) -> Self {
+        self.sid = Some(sid.into());
+        self
+    }
+
+    /// Add a condition, e.g. `("StringEquals", "aws:PrincipalOrgID", ["o-abc123"])`
+    pub fn with_condition<V: Into<String>>(
+        mut self,
+        operator: impl Into<String>,
+        key: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        self.conditions
+            .entry(operator.into())
+            .or_default()
+            .insert(key.into(), values.into_iter().map(Into::into).collect());
+        self
+    }
+
+    fn to_json(&self) -> Value {
+        let mut statement = Map::new();
+        if let Some(sid) = &self.sid {
+            statement.insert("Sid".into(), json!(sid));
+        }
+        statement.insert("Effect".into(), json!(self.effect.as_str()));
+        statement.insert(
+            element("Principal", self.not_principal),
+            self.principal.to_json(),
+        );
+        statement.insert(
+            element("Action", self.not_action),
+            one_or_many(&self.actions),
+        );
+        statement.insert(
+            element("Resource", self.not_resource),
+            one_or_many(&self.resources),
+        );
+        if !self.conditions.is_empty() {
+            let conditions: Map<String, Value> = self
+                .conditions
+                .iter()
+                .map(|(operator, keys)| {
+                    let keys: Map<String, Value> = keys
+                        .iter()
+                        .map(|(key, values)| (key.clone(), one_or_many(values)))
+                        .collect();
+                    (operator.clone(), Value::Object(keys))
+                })
+                .collect();
+            statement.insert("Condition".into(), Value::Object(conditions));
+        }
+        Value::Object(statement)
+    }
+
+    fn validate(&self, path: &str) -> Result<(), PolicyError> {
+        let principal_path = format!("{path}/{}", element("Principal", self.not_principal));
+        self.principal.validate(&principal_path)?;
+
+        let action_path = format!("{path}/{}", element("Action", self.not_action));
+        if self.actions.is_empty() {
+            return Err(PolicyError::new(
+                &action_path,
+                "a statement needs at least one action",
+            ));
+        }
+        for (i, action) in self.actions.iter().enumerate() {
+            validate_action(action, &format!("{action_path}/{i}"))?;
+        }
+
+        let resource_path = format!("{path}/{}", element("Resource", self.not_resource));
+        if self.resources.is_empty() {
+            return Err(PolicyError::new(
+                &resource_path,
+                "a statement needs at least one resource",
+            ));
+        }
+        for (i, resource) in self.resources.iter().enumerate() {
+            validate_resource(resource, &format!("{resource_path}/{i}"))?;
+        }
+
+        for (operator, keys) in &self.conditions {
+            let operator_path = format!("{path}/Condition/{operator}");
+            if !is_condition_operator(operator) {
+                return Err(PolicyError::new(
+                    &operator_path,
+                    format!("unknown condition operator '{operator}'"),
+                ));
+            }
+            for (key, values) in keys {
+                if key.is_empty() {
+                    return Err(PolicyError::new(
+                        &operator_path,
+                        "condition keys must not be empty",
+                    ));
+                }
+                if values.is_empty() {
+                    return Err(PolicyError::new(
+                        &format!("{operator_path}/{key}"),
+                        "a condition needs at least one value",
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Effect {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Allow => "Allow",
+            Self::Deny => "Deny",
+        }
+    }
+}
+
+impl Principal {
+    /// An AWS principal: account ID, account root, IAM user or role ARN
+    pub fn aws(id: impl Into<String>) -> Self {
+        Self::Specific {
+            aws: vec![id.into()],
+            service: Vec::new(),
+            federated: Vec::new(),
+            canonical_user: Vec::new(),
+        }
+    }
+
+    /// A service principal such as `bedrock.amazonaws.com`
+    pub fn service(name: impl Into<String>) -> Self {
+        Self::Specific {
+            aws: Vec::new(),
+            service: vec![name.into()],
+            federated: Vec::new(),
+            canonical_user: Vec::new(),
+        }
+    }
+
+    /// Principal types and their IDs, by their names in the policy JSON;
+    /// none for [`Principal::Any`]
+    fn kinds(&self) -> Vec<(&'static str, &[String])> {
+        match self {
+            Self::Any => Vec::new(),
+            Self::Specific {
+                aws,
+                service,
+                federated,
+                canonical_user,
+            } => vec![
+                ("AWS", aws),
+                ("Service", service),
+                ("Federated", federated),
+                ("CanonicalUser", canonical_user),
+            ],
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        match self {
+            Self::Any => json!("*"),
+            Self::Specific { .. } => Value::Object(
+                self.kinds()
+                    .into_iter()
+                    .filter(|(_, ids)| !ids.is_empty())
+                    .map(|(kind, ids)| (kind.to_string(), one_or_many(ids)))
+                    .collect(),
+            ),
+        }
+    }
+
+    fn validate(&self, path: &str) -> Result<(), PolicyError> {
+        let Self::Specific {
+            aws,
+            service,
+            federated,
+            canonical_user,
+        } = self
+        else {
+            return Ok(());
+        };
+        if self.kinds().iter().all(|(_, ids)| ids.is_empty()) {
+            return Err(PolicyError::new(path, "a principal must name someone"));
+        }
+        for (i, id) in aws.iter().enumerate() {
+            if !is_aws_principal(id) {
+                return Err(PolicyError::new(
+                    &format!("{path}/AWS/{i}"),
+                    format!(
+                        "'{id}' is not an account ID, account root, IAM user, role \
+                         or assumed-role ARN"
+                    ),
+                ));
+            }
+        }
+        for (i, name) in service.iter().enumerate() {
+            if !name.ends_with(".amazonaws.com") || name.contains(char::is_whitespace) {
+                return Err(PolicyError::new(
+                    &format!("{path}/Service/{i}"),
+                    format!("'{name}' is not a service principal (<service>.amazonaws.com)"),
+                ));
+            }
+        }
+        for (kind, ids) in [("Federated", federated), ("CanonicalUser", canonical_user)] {
+            if let Some(i) = ids.iter().position(|id| id.trim().is_empty()) {
+                return Err(PolicyError::new(
+                    &format!("{path}/{kind}/{i}"),
+                    format!("{kind} principals must not be empty"),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PolicyTemplate {
+    /// Actions the template allows, without the `s3vectors:` prefix
+    pub fn actions(self) -> &'static [&'static str] {
+        match self {
+            Self::Reader => &[
+                "GetVectorBucket",
+                "ListIndexes",
+                "GetIndex",
+                "GetVectors",
+                "ListVectors",
+                "QueryVectors",
+            ],
+            Self::Writer => &[
+                "GetVectorBucket",
+                "ListIndexes",
+                "GetIndex",
+                "GetVectors",
+                "ListVectors",
+                "QueryVectors",
+                "PutVectors",
+                "DeleteVectors",
+            ],
+            Self::CrossAccountQuery => &["GetIndex", "QueryVectors"],
+        }
+    }
+
+    fn sid(self) -> &'static str {
+        match self {
+            Self::Reader => "VectorReadAccess",
+            Self::Writer => "VectorWriteAccess",
+            Self::CrossAccountQuery => "CrossAccountQuery",
+        }
+    }
+}
+
+/// Match `value` against an IAM-style pattern where `*` matches any run of
+/// characters and `?` exactly one
+pub(crate) fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    let (mut p, mut v) = (0, 0);
+    // Position of the last `*` and the value index it was tried at
+    let mut star: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = star {
+            // Let the last `*` swallow one more character
+            p = star_p + 1;
+            v = star_v + 1;
+            star = Some((star_p, v));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn validate_action(action: &str, path: &str) -> Result<(), PolicyError> {
+    if action == "*" {
+        return Ok(());
+    }
+    let Some((service, name)) = action.split_once(':') else {
+        return Err(PolicyError::new(
+            path,
+            format!("'{action}' must be written as s3vectors:<Action>"),
+        ));
+    };
+    if !service.eq_ignore_ascii_case("s3vectors") {
+        return Err(PolicyError::new(
+            path,
+            format!(
+                "'{action}' is not an S3 Vectors action; bucket policies only grant s3vectors:*"
+            ),
+        ));
+    }
+    // Action names are case-insensitive
+    let name = name.to_ascii_lowercase();
+    if ACTIONS
+        .iter()
+        .any(|known| wildcard_match(&name, &known.to_ascii_lowercase()))
+    {
+        Ok(())
+    } else {
+        Err(PolicyError::new(
+            path,
+            format!("'{action}' does not match any S3 Vectors action"),
+        ))
+    }
+}
+
+fn validate_resource(resource: &str, path: &str) -> Result<(), PolicyError> {
+    if resource == "*" {
+        return Ok(());
+    }
+    if !resource.contains(['*', '?']) {
+        return validate_resource_arn(resource).map_err(|e| PolicyError::new(path, e.to_string()));
+    }
+    match resource.splitn(6, ':').collect::<Vec<_>>()[..] {
+        ["arn", partition, service, _, _, resource]
+            if (partition == "*" || is_partition(partition))
+                && service == "s3vectors"
+                && (resource == "*" || resource.starts_with("bucket/")) =>
+        {
+            Ok(())
+        }
+        _ => Err(PolicyError::new(
+            path,
+            format!(
+                "'{resource}' is not a vector bucket or index ARN pattern \
+                 (arn:aws:s3vectors:<region>:<account>:bucket/<name>[/index/<name>])"
+            ),
+        )),
+    }
+}
+
+/// Whether `partition` is in the [`RegionCatalogue`], as ARN parsing requires
+fn is_partition(partition: &str) -> bool {
+    RegionCatalogue::global()
+        .partition_by_id(partition)
+        .is_some()
+}
+
+fn is_account_id(id: &str) -> bool {
+    id.len() == 12 && id.chars().all(|c| c.is_ascii_digit())
+}
+
+fn is_aws_principal(id: &str) -> bool {
+    if id == "*" || is_account_id(id) {
+        return true;
+    }
+    match id.splitn(6, ':').collect::<Vec<_>>()[..] {
+        ["arn", partition, "iam", "", account, resource] => {
+            is_partition(partition)
+                && is_account_id(account)
+                && (resource == "root"
+                    || resource
+                        .strip_prefix("user/")
+                        .is_some_and(|r| !r.is_empty())
+                    || resource
+                        .strip_prefix("role/")
+                        .is_some_and(|r| !r.is_empty()))
+        }
+        ["arn", partition, "sts", "", account, resource] => {
+            is_partition(partition)
+                && is_account_id(account)
+                && resource
+                    .strip_prefix("assumed-role/")
+                    .is_some_and(|r| r.contains('/'))
+        }
+        _ => false,
+    }
+}
+
+fn is_condition_operator(operator: &str) -> bool {
+    let operator = operator
+        .strip_prefix("ForAllValues:")
+        .or_else(|| operator.strip_prefix("ForAnyValue:"))
+        .unwrap_or(operator);
+    let operator = operator.strip_suffix("IfExists").unwrap_or(operator);
+    CONDITION_OPERATORS.contains(&operator)
+}
+
+/// `name` or, negated, `Not<name>`
+fn element(name: &str, negated: bool) -> String {
+    if negated {
+        format!("Not{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// A lone value as itself, several as an array
+fn one_or_many(values: &[String]) -> Value {
+    match values {
+        [value] => json!(value),
+        values => json!(values),
+    }
+}
+
+fn parse_policy(value: &Value) -> Result<PolicyDocument, PolicyError> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| PolicyError::new("", "a policy must be a JSON object"))?;
+    if let Some(key) = object
+        .keys()
+        .find(|key| !matches!(key.as_str(), "Version" | "Id" | "Statement"))
+    {
+        return Err(PolicyError::new(
+            &format!("/{key}"),
+            format!("unsupported policy element '{key}'"),
+        ));
+    }
+
+    let version = match object.get("Version") {
+        Some(Value::String(version)) => version.clone(),
+        Some(_) => return Err(PolicyError::new("/Version", "Version must be a string")),
+        None => {
+            return Err(PolicyError::new(
+                "/Version",
+                format!("Version is required; use \"{POLICY_VERSION}\""),
+            ))
+        }
+    };
+    let id = match object.get("Id") {
+        Some(Value::String(id)) => Some(id.clone()),
+        Some(_) => return Err(PolicyError::new("/Id", "Id must be a string")),
+        None => None,
+    };
+    let statements = match object.get("Statement") {
+        Some(Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| parse_statement(item, &format!("/Statement/{i}")))
+            .collect::<Result<_, _>>()?,
+        Some(item) => vec![parse_statement(item, "/Statement/0")?],
+        None => return Err(PolicyError::new("/Statement", "Statement is required")),
+    };
+
+    Ok(PolicyDocument {
+        version,
+        id,
+        statements,
+    })
+}
+
+fn parse_statement(value: &Value, path: &str) -> Result<Statement, PolicyError> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| PolicyError::new(path, "a statement must be a JSON object"))?;
+    for key in object.keys() {
+        match key.as_str() {
+            "Sid" | "Effect" | "Principal" | "NotPrincipal" | "Action" | "NotAction"
+            | "Resource" | "NotResource" | "Condition" => {}
+            _ => {
+                return Err(PolicyError::new(
+                    &format!("{path}/{key}"),
+                    format!("unsupported statement element '{key}'"),
+                ))
+            }
+        }
+    }
+
+    let sid = match object.get("Sid") {
+        Some(Value::String(sid)) => Some(sid.clone()),
+        Some(_) => {
+            return Err(PolicyError::new(
+                &format!("{path}/Sid"),
+                "Sid must be a string",
+            ))
+        }
+        None => None,
+    };
+    let effect = match object.get("Effect").and_then(Value::as_str) {
+        Some("Allow") => Effect::Allow,
+        Some("Deny") => Effect::Deny,
+        _ => {
+            return Err(PolicyError::new(
+                &format!("{path}/Effect"),
+                "Effect must be \"Allow\" or \"Deny\"",
+            ))
+        }
+    };
+    let (principal, principal_path, not_principal) = element_or_not(object, "Principal", path)?;
+    let principal = match principal {
+        Some(principal) => parse_principal(principal, &principal_path)?,
+        None => {
+            return Err(PolicyError::new(
+                &principal_path,
+                "bucket policy statements need a Principal",
+            ))
+        }
+    };
+    let (actions, action_path, not_action) = element_or_not(object, "Action", path)?;
+    let actions = parse_strings(actions, &action_path)?;
+    let (resources, resource_path, not_resource) = element_or_not(object, "Resource", path)?;
+    let resources = parse_strings(resources, &resource_path)?;
+    let conditions = match object.get("Condition") {
+        Some(condition) => parse_conditions(condition, &format!("{path}/Condition"))?,
+        None => BTreeMap::new(),
+    };
+
+    Ok(Statement {
+        sid,
+        effect,
+        principal,
+        not_principal,
+        actions,
+        not_action,
+        resources,
+        not_resource,
+        conditions,
+    })
+}
+
+/// The statement's `name` or `Not<name>` element, its path, and whether it
+/// is the negated one
+fn element_or_not<'a>(
+    object: &'a Map<String, Value>,
+    name: &str,
+    path: &str,
+) -> Result<(Option<&'a Value>, String, bool), PolicyError> {
+    let not_name = element(name, true);
+    match (object.get(name), object.get(&not_name)) {
+        (Some(_), Some(_)) => Err(PolicyError::new(
+            &format!("{path}/{not_name}"),
+            format!("a statement takes {name} or {not_name}, not both"),
+        )),
+        (None, Some(value)) => Ok((Some(value), format!("{path}/{not_name}"), true)),
+        (value, None) => Ok((value, format!("{path}/{name}"), false)),
+    }
+}
+
+fn parse_principal(value: &Value, path: &str) -> Result<Principal, PolicyError> {
+    match value {
+        Value::String(s) if s == "*" => Ok(Principal::Any),
+        Value::Object(object) => {
+            let mut aws = Vec::new();
+            let mut service = Vec::new();
+            let mut federated = Vec::new();
+            let mut canonical_user = Vec::new();
+            for (kind, ids) in object {
+                let kind_path = format!("{path}/{kind}");
+                let ids_of_kind = match kind.as_str() {
+                    "AWS" => &mut aws,
+                    "Service" => &mut service,
+                    "Federated" => &mut federated,
+                    "CanonicalUser" => &mut canonical_user,
+                    _ => {
+                        return Err(PolicyError::new(
+                            &kind_path,
+                            format!(
+                                "unsupported principal type '{kind}'; use AWS, Service, \
+                                 Federated or CanonicalUser"
+                            ),
+                        ))
+                    }
+                };
+                *ids_of_kind = parse_strings(Some(ids), &kind_path)?;
+            }
+            Ok(Principal::Specific {
+                aws,
+                service,
+                federated,
+                canonical_user,
+            })
+        }
+        _ => Err(PolicyError::new(
+            path,
+            "Principal must be \"*\" or an object like {\"AWS\": \"<arn>\"}",
+        )),
+    }
+}
+
+/// A string or an array of strings
+fn parse_strings(value: Option<&Value>, path: &str) -> Result<Vec<String>, PolicyError> {
+    let name = path.rsplit('/').next().unwrap_or_default();
+    match value {
+        Some(Value::String(s)) => Ok(vec![s.clone()]),
+        Some(Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                item.as_str().map(str::to_string).ok_or_else(|| {
+                    PolicyError::new(&format!("{path}/{i}"), format!("{name} must be strings"))
+                })
+            })
+            .collect(),
+        Some(_) => Err(PolicyError::new(
+            path,
+            format!("{name} must be a string or an array of strings"),
+        )),
+        None => Err(PolicyError::new(path, format!("{name} is required"))),
+    }
+}
+
+fn parse_conditions(
+    value: &Value,
+    path: &str,
+) -> Result<BTreeMap<String, BTreeMap<String, Vec<String>>>, PolicyError> {
+    let operators = value.as_object().ok_or_else(|| {
+        PolicyError::new(
+            path,
+            "Condition must map operators to {key: values} objects",
+        )
+    })?;
+    let mut conditions = BTreeMap::new();
+    for (operator, keys) in operators {
+        let operator_path = format!("{path}/{operator}");
+        let keys = keys.as_object().ok_or_else(|| {
+            PolicyError::new(
+                &operator_path,
+                "a condition operator takes a {key: values} object",
+            )
+        })?;
+        let mut parsed = BTreeMap::new();
+        for (key, values) in keys {
+            let key_path = format!("{operator_path}/{key}");
+            // Booleans and numbers are compared as their string form
+            let scalar = |value: &Value| match value {
+                Value::String(s) => Some(s.clone()),
+                Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+                _ => None,
+            };
+            let values = match values {
+                Value::Array(items) => items.iter().map(scalar).collect::<Option<Vec<_>>>(),
+                value => scalar(value).map(|v| vec![v]),
+            }
+            .ok_or_else(|| {
+                PolicyError::new(
+                    &key_path,
+                    "condition values must be strings, numbers or booleans",
+                )
+            })?;
+            parsed.insert(key.clone(), values);
+        }
+        conditions.insert(operator.clone(), parsed);
+    }
+    Ok(conditions)
+}
+
+impl FromStr for PolicyDocument {
+    type Err = PolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value: Value = serde_json::from_str(s)
+            .map_err(|e| PolicyError::new("", format!("not valid JSON: {e}")))?;
+        Self::from_json(&value)
+    }
+}
+
+impl fmt::Display for PolicyDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
+
+impl Serialize for PolicyDocument {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl Serialize for Statement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PolicyDocument {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        Self::from_json(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUCKET: &str = "arn:aws:s3vectors:us-east-1:111122223333:bucket/docs";
+    const ROLE: &str = "arn:aws:iam::444455556666:role/search";
+
+    #[test]
+    fn test_parse_policy_round_trip() {
+        let json = json!({
+            "Version": "2012-10-17",
+            "Id": "DocsPolicy",
+            "Statement": {
+                "Sid": "Query",
+                "Effect": "Allow",
+                "Principal": {"AWS": [ROLE, "111122223333"]},
+                "Action": "s3vectors:QueryVectors",
+                "Resource": [BUCKET, format!("{BUCKET}/index/*")],
+                "Condition": {"Bool": {"aws:SecureTransport": true}}
+            }
+        });
+        let policy = PolicyDocument::from_json(&json).unwrap();
+        let statement = &policy.statements[0];
+        assert_eq!(statement.effect, Effect::Allow);
+        assert_eq!(statement.actions, ["s3vectors:QueryVectors"]);
+        assert_eq!(
+            statement.conditions["Bool"]["aws:SecureTransport"],
+            ["true"]
+        );
+        assert_eq!(
+            statement.principal,
+            Principal::Specific {
+                aws: vec![ROLE.to_string(), "111122223333".to_string()],
+                service: vec![],
+                federated: vec![],
+                canonical_user: vec![],
+            }
+        );
+
+        let reparsed: PolicyDocument = policy.to_string().parse().unwrap();
+        assert_eq!(reparsed, policy);
+        assert_eq!(
+            policy.to_json()["Statement"][0]["Action"],
+            "s3vectors:QueryVectors"
+        );
+    }
+
+    #[test]
+    fn test_validation_errors_point_at_the_problem() {
+        let error = |statement: Value| {
+            let policy = json!({"Version": "2012-10-17", "Statement": [statement]});
+            PolicyDocument::from_json(&policy).unwrap_err()
+        };
+        let base = json!({
+            "Effect": "Allow",
+            "Principal": "*",
+            "Action": ["s3vectors:GetVectors"],
+            "Resource": BUCKET
+        });
+        let with = |key: &str, value: Value| {
+            let mut statement = base.clone();
+            statement[key] = value;
+            statement
+        };
+
+        assert!(
+            PolicyDocument::from_json(&json!({"Version": "2012-10-17", "Statement": base})).is_ok()
+        );
+        assert_eq!(
+            error(with(
+                "Action",
+                json!(["s3vectors:GetVectors", "s3vectors:QueryVector"])
+            ))
+            .path,
+            "/Statement/0/Action/1"
+        );
+        assert_eq!(
+            error(with("Action", json!("s3:GetObject"))).path,
+            "/Statement/0/Action/0"
+        );
+        assert_eq!(
+            error(with("Resource", json!("arn:aws:s3:::docs"))).path,
+            "/Statement/0/Resource/0"
+        );
+        assert_eq!(
+            error(with("Principal", json!({"AWS": "arn:aws:iam::123:role/x"}))).path,
+            "/Statement/0/Principal/AWS/0"
+        );
+        assert_eq!(
+            error(with(
+                "Condition",
+                json!({"StringEqual": {"aws:PrincipalOrgID": "o-1"}})
+            ))
+            .path,
+            "/Statement/0/Condition/StringEqual"
+        );
+        assert_eq!(
+            error(with("Effect", json!("allow"))).path,
+            "/Statement/0/Effect"
+        );
+        assert_eq!(
+            error(with("NotAction", json!("s3vectors:PutVectors"))).path,
+            "/Statement/0/NotAction"
+        );
+        let mut not_action = base.clone();
+        not_action.as_object_mut().unwrap().remove("Action");
+        not_action["NotAction"] = json!(["s3vectors:PutVectors", "s3vectors:Nope"]);
+        assert_eq!(error(not_action).path, "/Statement/0/NotAction/1");
+
+        let mut no_principal = base.clone();
+        no_principal.as_object_mut().unwrap().remove("Principal");
+        assert_eq!(error(no_principal).path, "/Statement/0/Principal");
+        assert!("{}".parse::<PolicyDocument>().is_err());
+        assert!("not json".parse::<PolicyDocument>().is_err());
+    }
+
+    #[test]
+    fn test_negated_elements_and_other_principal_types() {
+        let json = json!({
+            "Version": "2012-10-17",
+            "Statement": [{
+                "Effect": "Deny",
+                "NotPrincipal": {"AWS": ROLE},
+                "NotAction": "s3vectors:Get*",
+                "NotResource": format!("{BUCKET}/index/public"),
+                "Condition": {"StringEqualsIfExistsAndMore": {"aws:SourceVpc": "vpc-1"}}
+            }, {
+                "Effect": "Allow",
+                "Principal": {
+                    "Federated": "cognito-identity.amazonaws.com",
+                    "CanonicalUser": "79a59df900b949e55d96a1e698fbaced"
+                },
+                "Action": "s3vectors:QueryVectors",
+                "Resource": BUCKET
+            }]
+        });
+
+        // An operator this client does not know fails validation only
+        let error = PolicyDocument::from_json(&json).unwrap_err();
+        assert_eq!(
+            error.path,
+            "/Statement/0/Condition/StringEqualsIfExistsAndMore"
+        );
+        let policy = PolicyDocument::from_json_unvalidated(&json).unwrap();
+        let deny = &policy.statements[0];
+        assert!(deny.not_principal && deny.not_action && deny.not_resource);
+        assert_eq!(deny.actions, ["s3vectors:Get*"]);
+        assert!(matches!(
+            &policy.statements[1].principal,
+            Principal::Specific { federated, canonical_user, .. }
+                if federated.len() == 1 && canonical_user.len() == 1
+        ));
+        assert_eq!(policy.to_json(), json);
+
+        // Only one of an element and its negation
+        let mut both = json.clone();
+        both["Statement"][1]["NotAction"] = json!("s3vectors:PutVectors");
+        assert_eq!(
+            PolicyDocument::from_json_unvalidated(&both)
+                .unwrap_err()
+                .path,
+            "/Statement/1/NotAction"
+        );
+    }
+
+    #[test]
+    fn test_wildcards_in_actions_and_resources() {
+        for action in [
+            "*",
+            "s3vectors:*",
+            "s3vectors:Get*",
+            "S3Vectors:queryvectors",
+        ] {
+            assert!(validate_action(action, "").is_ok(), "{action}");
+        }
+        assert!(validate_action("s3vectors:Nope*", "").is_err());
+        assert!(validate_resource("arn:aws:s3vectors:*:*:bucket/*", "").is_ok());
+        assert!(validate_resource("arn:aws:s3:::bucket/*", "").is_err());
+        assert!(validate_resource("arn:*:s3vectors:*:*:bucket/*", "").is_ok());
+        assert!(validate_resource("arn:aws-cn:s3vectors:*:*:bucket/*", "").is_ok());
+        assert!(validate_resource("arn:awsfoo:s3vectors:*:*:bucket/*", "").is_err());
+        assert!(is_aws_principal("arn:aws-us-gov:iam::111122223333:root"));
+        assert!(!is_aws_principal("arn:awsfoo:iam::111122223333:root"));
+        assert!(!is_aws_principal(
+            "arn:awsfoo:sts::111122223333:assumed-role/a/b"
+        ));
+
+        assert!(wildcard_match("Get*", "GetVectors"));
+        assert!(wildcard_match("*Vectors", "QueryVectors"));
+        assert!(wildcard_match("Get?ndex", "GetIndex"));
+        assert!(wildcard_match("a*b*c", "aXbYbZc"));
+        assert!(!wildcard_match("Get*", "PutVectors"));
+        assert!(!wildcard_match("Get?", "Get"));
+    }
+
+    #[test]
+    fn test_templates() {
+        let reader = PolicyDocument::from_template(PolicyTemplate::Reader, ROLE, BUCKET).unwrap();
+        let statement = &reader.statements[0];
+        assert_eq!(statement.sid.as_deref(), Some("VectorReadAccess"));
+        assert_eq!(
+            statement.resources,
+            [BUCKET.to_string(), format!("{BUCKET}/index/*")]
+        );
+        assert!(!statement
+            .actions
+            .contains(&"s3vectors:PutVectors".to_string()));
+
+        let writer = PolicyDocument::from_template(PolicyTemplate::Writer, ROLE, BUCKET).unwrap();
+        assert!(writer.statements[0]
+            .actions
+            .contains(&"s3vectors:PutVectors".to_string()));
+
+        let cross = PolicyDocument::from_template(
+            PolicyTemplate::CrossAccountQuery,
+            "444455556666",
+            BUCKET,
+        )
+        .unwrap();
+        assert_eq!(
+            cross.statements[0].principal,
+            Principal::aws("arn:aws:iam::444455556666:root")
+        );
+        // Same-account principals don't need cross-account access
+        assert!(PolicyDocument::from_template(
+            PolicyTemplate::CrossAccountQuery,
+            "111122223333",
+            BUCKET
+        )
+        .is_err());
+        assert!(PolicyDocument::from_template(PolicyTemplate::Reader, "someone", BUCKET).is_err());
+        assert!(PolicyDocument::from_template(
+            PolicyTemplate::Reader,
+            ROLE,
+            &format!("{BUCKET}/index/embeddings")
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,678 @@
+//! Local evaluation of bucket policies.
+//!
+//! Follows the IAM rules for a single resource policy: an explicit `Deny`
+//! beats any `Allow`, and nothing is allowed unless a statement allows it.
+//! Identity policies and SCPs are not considered, so an implicit deny only
+//! means the bucket policy does not grant the request itself.
+
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::{
+    is_account_id, is_condition_operator, wildcard_match, Effect, PolicyDocument, Principal,
+    Statement, ACTIONS,
+};
+use crate::validation::validate_resource_arn;
+
+/// A request to evaluate: who does what to which bucket or index
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationRequest {
+    /// IAM user, role or assumed-role ARN, account ID, or service principal
+    pub principal: String,
+    /// A concrete action such as `s3vectors:QueryVectors`
+    pub action: String,
+    /// Bucket or index ARN
+    pub resource: String,
+    /// Condition keys of the request, e.g. `aws:SourceIp`; keys are
+    /// case-insensitive
+    pub context: BTreeMap<String, Vec<String>>,
+}
+
+/// Outcome of a simulation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Decision {
+    /// An `Allow` statement matched and no `Deny` did
+    Allowed,
+    /// A `Deny` statement matched
+    ExplicitDeny,
+    /// No statement matched
+    ImplicitDeny,
+}
+
+/// How each statement evaluated and which one decided
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Simulation {
+    pub decision: Decision,
+    /// Index of the deciding statement, `None` for an implicit deny
+    pub decided_by: Option<usize>,
+    pub statements: Vec<StatementResult>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementResult {
+    pub index: usize,
+    pub sid: Option<String>,
+    pub effect: Effect,
+    pub matched: bool,
+    /// Why the statement matched or which element did not
+    pub reason: String,
+}
+
+impl SimulationRequest {
+    /// A request with the principal's ARN and account as `aws:PrincipalArn`
+    /// and `aws:PrincipalAccount`, and `aws:SecureTransport` set as for any
+    /// HTTPS call
+    pub fn new(
+        principal: impl Into<String>,
+        action: impl Into<String>,
+        resource: impl Into<String>,
+    ) -> Self {
+        let principal = principal.into();
+        let mut context = BTreeMap::new();
+        if principal.starts_with("arn:") {
+            context.insert("aws:PrincipalArn".to_string(), vec![principal.clone()]);
+        }
+        if let Some(account) = principal_account(&principal) {
+            context.insert(
+                "aws:PrincipalAccount".to_string(),
+                vec![account.to_string()],
+            );
+        }
+        context.insert("aws:SecureTransport".to_string(), vec!["true".to_string()]);
+
+        Self {
+            principal,
+            action: action.into(),
+            resource: resource.into(),
+            context,
+        }
+    }
+
+    /// Set a condition key, replacing any default for it
+    pub fn with_context<V: Into<String>>(
+        mut self,
+        key: &str,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        self.context.retain(|k, _| !k.eq_ignore_ascii_case(key));
+        self.context.insert(
+            key.to_string(),
+            values.into_iter().map(Into::into).collect(),
+        );
+        self
+    }
+
+    /// Check that the action is a concrete S3 Vectors action and the
+    /// resource a bucket or index ARN
+    pub fn validate(&self) -> Result<()> {
+        let known = self
+            .action
+            .strip_prefix("s3vectors:")
+            .is_some_and(|name| ACTIONS.iter().any(|a| a.eq_ignore_ascii_case(name)));
+        if !known {
+            bail!(
+                "'{}' is not an S3 Vectors action (e.g. s3vectors:QueryVectors)",
+                self.action
+            );
+        }
+        if self.principal.is_empty() {
+            bail!("Principal must not be empty");
+        }
+        validate_resource_arn(&self.resource)
+    }
+
+    fn context_values(&self, key: &str) -> Option<&[String]> {
+        self.context
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, values)| values.as_slice())
+            .filter(|values| !values.is_empty())
+    }
+}
+
+impl Simulation {
+    pub fn allowed(&self) -> bool {
+        self.decision == Decision::Allowed
+    }
+
+    /// One line naming the deciding statement
+    pub fn explanation(&self) -> String {
+        let Some(statement) = self.decided_by.map(|i| &self.statements[i]) else {
+            return "Implicitly denied: no statement allows the request".to_string();
+        };
+        let name = match &statement.sid {
+            Some(sid) => format!("statement {} ({sid})", statement.index),
+            None => format!("statement {}", statement.index),
+        };
+        match self.decision {
+            Decision::Allowed => format!("Allowed by {name}"),
+            _ => format!("Explicitly denied by {name}"),
+        }
+    }
+}
+
+impl PolicyDocument {
+    /// Evaluate `request` against this policy
+    pub fn simulate(&self, request: &SimulationRequest) -> Simulation {
+        let statements: Vec<StatementResult> = self
+            .statements
+            .iter()
+            .enumerate()
+            .map(|(index, statement)| {
+                let (matched, reason) = match evaluate(statement, request) {
+                    Ok(()) => (true, "matched".to_string()),
+                    Err(reason) => (false, reason),
+                };
+                StatementResult {
+                    index,
+                    sid: statement.sid.clone(),
+                    effect: statement.effect,
+                    matched,
+                    reason,
+                }
+            })
+            .collect();
+
+        let first = |effect| {
+            statements
+                .iter()
+                .position(|s| s.matched && s.effect == effect)
+        };
+        let (decision, decided_by) = match (first(Effect::Deny), first(Effect::Allow)) {
+            (Some(deny), _) => (Decision::ExplicitDeny, Some(deny)),
+            (None, Some(allow)) => (Decision::Allowed, Some(allow)),
+            (None, None) => (Decision::ImplicitDeny, None),
+        };
+
+        Simulation {
+            decision,
+            decided_by,
+            statements,
+        }
+    }
+}
+
+/// `Ok` if the statement applies to the request, else why not
+fn evaluate(statement: &Statement, request: &SimulationRequest) -> Result<(), String> {
+    // A listed element applies unless the statement negates the list
+    let check = |listed: bool, negated: bool, what: String| match (listed, negated) {
+        (true, false) | (false, true) => Ok(()),
+        (false, false) => Err(format!("{what} not listed")),
+        (true, true) => Err(format!("{what} excluded")),
+    };
+
+    check(
+        principal_matches(&statement.principal, &request.principal),
+        statement.not_principal,
+        format!("principal '{}'", request.principal),
+    )?;
+    // Action names are case-insensitive, ARNs are not
+    let action = request.action.to_ascii_lowercase();
+    check(
+        statement
+            .actions
+            .iter()
+            .any(|pattern| wildcard_match(&pattern.to_ascii_lowercase(), &action)),
+        statement.not_action,
+        format!("action {}", request.action),
+    )?;
+    check(
+        statement
+            .resources
+            .iter()
+            .any(|pattern| wildcard_match(pattern, &request.resource)),
+        statement.not_resource,
+        format!("resource {}", request.resource),
+    )?;
+
+    for (operator, keys) in &statement.conditions {
+        if !is_condition_operator(operator) {
+            return Err(format!("condition operator {operator} cannot be simulated"));
+        }
+        for (key, values) in keys {
+            if !condition_holds(operator, values, request.context_values(key)) {
+                return Err(format!("condition {operator} {key} not met"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn principal_matches(principal: &Principal, caller: &str) -> bool {
+    let Principal::Specific {
+        aws,
+        service,
+        federated,
+        canonical_user,
+    } = principal
+    else {
+        return true;
+    };
+    if service
+        .iter()
+        .chain(federated)
+        .chain(canonical_user)
+        .any(|id| id == caller)
+    {
+        return true;
+    }
+
+    let caller_account = principal_account(caller);
+    let caller_role = assumed_role_arn(caller);
+    aws.iter().any(|id| {
+        id == "*"
+            || id == caller
+            || caller_role.as_deref() == Some(id.as_str())
+            // An account (or its root) stands for every principal in it
+            || (caller_account.is_some() && principal_account_root(id) == caller_account)
+    })
+}
+
+/// The account of an account ID or IAM/STS ARN
+fn principal_account(principal: &str) -> Option<&str> {
+    if is_account_id(principal) {
+        return Some(principal);
+    }
+    principal
+        .split(':')
+        .nth(4)
+        .filter(|account| is_account_id(account))
+}
+
+/// The account an account ID or `:root` ARN stands for
+fn principal_account_root(id: &str) -> Option<&str> {
+    if is_account_id(id) {
+        return Some(id);
+    }
+    id.strip_suffix(":root")
+        .filter(|_| id.starts_with("arn:"))
+        .and_then(principal_account)
+}
+
+/// `arn:aws:sts::<account>:assumed-role/<role>/<session>` as the role's ARN
+fn assumed_role_arn(caller: &str) -> Option<String> {
+    let [_, partition, "sts", _, account, resource] = caller.splitn(6, ':').collect::<Vec<_>>()[..]
+    else {
+        return None;
+    };
+    let (role, _session) = resource.strip_prefix("assumed-role/")?.split_once('/')?;
+    Some(format!("arn:{partition}:iam::{account}:role/{role}"))
+}
+
+/// Whether a condition holds for the request's values of its key.
+///
+/// Several policy values are alternatives. A missing key fails the condition
+/// unless the operator is negated, `ForAllValues:` or `...IfExists`.
+fn condition_holds(operator: &str, policy_values: &[String], values: Option<&[String]>) -> bool {
+    let (set, operator) = match operator.split_once(':') {
+        Some((set, operator)) => (Some(set), operator),
+        None => (None, operator),
+    };
+    let (operator, if_exists) = match operator.strip_suffix("IfExists") {
+        Some(operator) => (operator, true),
+        None => (operator, false),
+    };
+
+    if operator == "Null" {
+        let absent = values.is_none();
+        return policy_values
+            .iter()
+            .any(|expected| expected.eq_ignore_ascii_case("true") == absent);
+    }
+
+    let (positive, negated) = match operator {
+        "StringNotEquals" => ("StringEquals", true),
+        "StringNotEqualsIgnoreCase" => ("StringEqualsIgnoreCase", true),
+        "StringNotLike" => ("StringLike", true),
+        "NumericNotEquals" => ("NumericEquals", true),
+        "DateNotEquals" => ("DateEquals", true),
+        "NotIpAddress" => ("IpAddress", true),
+        "ArnNotEquals" => ("ArnEquals", true),
+        "ArnNotLike" => ("ArnLike", true),
+        operator => (operator, false),
+    };
+
+    let Some(values) = values else {
+        return if_exists || set == Some("ForAllValues") || (set.is_none() && negated);
+    };
+    let matches = |value: &String| {
+        policy_values
+            .iter()
+            .any(|expected| value_matches(positive, expected, value))
+    };
+    match set {
+        Some("ForAllValues") => values.iter().all(|v| matches(v) != negated),
+        Some(_) => values.iter().any(|v| matches(v) != negated),
+        None => values.iter().any(matches) != negated,
+    }
+}
+
+fn value_matches(operator: &str, expected: &str, actual: &str) -> bool {
+    let numbers = || Some((expected.parse::<f64>().ok()?, actual.parse::<f64>().ok()?));
+    let dates = || Some((parse_date(expected)?, parse_date(actual)?));
+
+    match operator {
+        "StringEquals" => expected == actual,
+        "StringEqualsIgnoreCase" => expected.eq_ignore_ascii_case(actual),
+        "StringLike" | "ArnEquals" | "ArnLike" => wildcard_match(expected, actual),
+        "Bool" => expected.eq_ignore_ascii_case(actual),
+        "NumericEquals" => numbers().is_some_and(|(e, a)| a == e),
+        "NumericLessThan" => numbers().is_some_and(|(e, a)| a < e),
+        "NumericLessThanEquals" => numbers().is_some_and(|(e, a)| a <= e),
+        "NumericGreaterThan" => numbers().is_some_and(|(e, a)| a > e),
+        "NumericGreaterThanEquals" => numbers().is_some_and(|(e, a)| a >= e),
+        "DateEquals" => dates().is_some_and(|(e, a)| a == e),
+        "DateLessThan" => dates().is_some_and(|(e, a)| a < e),
+        "DateLessThanEquals" => dates().is_some_and(|(e, a)| a <= e),
+        "DateGreaterThan" => dates().is_some_and(|(e, a)| a > e),
+        "DateGreaterThanEquals" => dates().is_some_and(|(e, a)| a >= e),
+        "IpAddress" => ip_in_cidr(expected, actual),
+        _ => false,
+    }
+}
+
+/// An RFC 3339 timestamp or epoch seconds
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(value) {
+        Ok(date) => Some(date.with_timezone(&Utc)),
+        Err(_) => DateTime::from_timestamp(value.parse().ok()?, 0),
+    }
+}
+
+/// Whether `ip` is in `cidr`; a bare address is a single-host range
+fn ip_in_cidr(cidr: &str, ip: &str) -> bool {
+    let (network, prefix) = match cidr.split_once('/') {
+        Some((network, prefix)) => (network, Some(prefix)),
+        None => (cidr, None),
+    };
+    let (Ok(network), Ok(ip)) = (network.parse::<IpAddr>(), ip.parse::<IpAddr>()) else {
+        return false;
+    };
+    let (network, ip, width) = match (network, ip) {
+        (IpAddr::V4(network), IpAddr::V4(ip)) => {
+            (u32::from(network).into(), u32::from(ip).into(), 32)
+        }
+        (IpAddr::V6(network), IpAddr::V6(ip)) => (u128::from(network), u128::from(ip), 128),
+        _ => return false,
+    };
+    let bits = match prefix.map(str::parse::<u32>) {
+        None => width,
+        Some(Ok(bits)) if bits <= width => bits,
+        Some(_) => return false,
+    };
+    // Only the leading `bits` bits have to agree
+    (network ^ ip).checked_shr(width - bits).unwrap_or(0) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BUCKET: &str = "arn:aws:s3vectors:us-east-1:111122223333:bucket/docs";
+    const INDEX: &str = "arn:aws:s3vectors:us-east-1:111122223333:bucket/docs/index/embeddings";
+    const ROLE: &str = "arn:aws:iam::444455556666:role/search";
+
+    fn policy(statements: serde_json::Value) -> PolicyDocument {
+        PolicyDocument::from_json(&json!({"Version": "2012-10-17", "Statement": statements}))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_explicit_deny_beats_allow() {
+        let policy = policy(json!([
+            {
+                "Sid": "Read",
+                "Effect": "Allow",
+                "Principal": {"AWS": "444455556666"},
+                "Action": "s3vectors:*",
+                "Resource": format!("{BUCKET}/index/*")
+            },
+            {
+                "Sid": "NoWrites",
+                "Effect": "Deny",
+                "Principal": "*",
+                "Action": ["s3vectors:PutVectors", "s3vectors:DeleteVectors"],
+                "Resource": "*"
+            }
+        ]));
+
+        let query = policy.simulate(&SimulationRequest::new(
+            ROLE,
+            "s3vectors:QueryVectors",
+            INDEX,
+        ));
+        assert!(query.allowed());
+        assert_eq!(query.explanation(), "Allowed by statement 0 (Read)");
+        assert_eq!(
+            query.statements[1].reason,
+            "action s3vectors:QueryVectors not listed"
+        );
+
+        let put = policy.simulate(&SimulationRequest::new(ROLE, "s3vectors:putvectors", INDEX));
+        assert_eq!(put.decision, Decision::ExplicitDeny);
+        assert_eq!(put.decided_by, Some(1));
+
+        let other = policy.simulate(&SimulationRequest::new(
+            "arn:aws:iam::999999999999:role/x",
+            "s3vectors:QueryVectors",
+            INDEX,
+        ));
+        assert_eq!(other.decision, Decision::ImplicitDeny);
+        assert_eq!(other.decided_by, None);
+        assert!(other.statements[0].reason.starts_with("principal"));
+
+        // The bucket itself is not covered by `bucket/docs/index/*`
+        let bucket = policy.simulate(&SimulationRequest::new(
+            ROLE,
+            "s3vectors:GetVectorBucket",
+            BUCKET,
+        ));
+        assert_eq!(bucket.decision, Decision::ImplicitDeny);
+    }
+
+    #[test]
+    fn test_principal_matching() {
+        let role = Principal::aws(ROLE);
+        assert!(principal_matches(&role, ROLE));
+        assert!(principal_matches(
+            &role,
+            "arn:aws:sts::444455556666:assumed-role/search/session-1"
+        ));
+        assert!(!principal_matches(
+            &role,
+            "arn:aws:iam::444455556666:role/other"
+        ));
+
+        let account = Principal::aws("arn:aws:iam::444455556666:root");
+        assert!(principal_matches(
+            &account,
+            "arn:aws:iam::444455556666:user/alice"
+        ));
+        assert!(!principal_matches(
+            &account,
+            "arn:aws:iam::111122223333:user/alice"
+        ));
+
+        assert!(principal_matches(&Principal::Any, "anyone"));
+        assert!(principal_matches(
+            &Principal::service("bedrock.amazonaws.com"),
+            "bedrock.amazonaws.com"
+        ));
+    }
+
+    #[test]
+    fn test_negated_elements() {
+        // Only the search role may do anything but read
+        let policy = policy(json!([
+            {
+                "Effect": "Allow",
+                "Principal": "*",
+                "Action": "s3vectors:*",
+                "Resource": "*"
+            },
+            {
+                "Sid": "ReadOnlyForOthers",
+                "Effect": "Deny",
+                "NotPrincipal": {"AWS": ROLE},
+                "NotAction": ["s3vectors:Get*", "s3vectors:QueryVectors"],
+                "NotResource": format!("{BUCKET}/index/scratch")
+            }
+        ]));
+        let simulate = |principal: &str, action: &str, resource: &str| {
+            policy.simulate(&SimulationRequest::new(principal, action, resource))
+        };
+        let other = "arn:aws:iam::444455556666:role/other";
+
+        assert!(simulate(ROLE, "s3vectors:PutVectors", INDEX).allowed());
+        assert!(simulate(other, "s3vectors:QueryVectors", INDEX).allowed());
+        assert!(simulate(
+            other,
+            "s3vectors:PutVectors",
+            &format!("{BUCKET}/index/scratch")
+        )
+        .allowed());
+        let put = simulate(other, "s3vectors:PutVectors", INDEX);
+        assert_eq!(put.decision, Decision::ExplicitDeny);
+
+        let role = simulate(ROLE, "s3vectors:PutVectors", INDEX);
+        assert_eq!(
+            role.statements[1].reason,
+            format!("principal '{ROLE}' excluded")
+        );
+        let query = simulate(other, "s3vectors:QueryVectors", INDEX);
+        assert_eq!(
+            query.statements[1].reason,
+            "action s3vectors:QueryVectors excluded"
+        );
+
+        // Other principal types match by ID
+        let federated = Principal::Specific {
+            aws: vec![],
+            service: vec![],
+            federated: vec!["cognito-identity.amazonaws.com".to_string()],
+            canonical_user: vec![],
+        };
+        assert!(principal_matches(
+            &federated,
+            "cognito-identity.amazonaws.com"
+        ));
+        assert!(!principal_matches(&federated, ROLE));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let strings = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let holds = |operator: &str, expected: &[&str], actual: Option<&[&str]>| {
+            let actual = actual.map(strings);
+            condition_holds(operator, &strings(expected), actual.as_deref())
+        };
+
+        assert!(holds("StringEquals", &["o-1", "o-2"], Some(&["o-2"])));
+        assert!(!holds("StringEquals", &["o-1"], None));
+        assert!(holds("StringNotEquals", &["o-1"], None));
+        assert!(!holds("StringNotEquals", &["o-1"], Some(&["o-1"])));
+        assert!(holds("StringEqualsIfExists", &["o-1"], None));
+        assert!(holds(
+            "StringLike",
+            &["arn:aws:iam::*:role/ci-*"],
+            Some(&["arn:aws:iam::1:role/ci-x"])
+        ));
+        assert!(holds("Bool", &["true"], Some(&["True"])));
+        assert!(holds("NumericLessThan", &["10"], Some(&["3"])));
+        assert!(!holds("NumericLessThan", &["10"], Some(&["abc"])));
+        assert!(holds(
+            "DateGreaterThan",
+            &["2024-01-01T00:00:00Z"],
+            Some(&["1735689600"])
+        ));
+        assert!(holds("IpAddress", &["10.0.0.0/8"], Some(&["10.1.2.3"])));
+        assert!(!holds("IpAddress", &["10.0.0.0/8"], Some(&["192.168.0.1"])));
+        assert!(holds("IpAddress", &["0.0.0.0/0"], Some(&["192.168.0.1"])));
+        assert!(holds(
+            "NotIpAddress",
+            &["10.0.0.0/8"],
+            Some(&["192.168.0.1"])
+        ));
+        assert!(holds(
+            "IpAddress",
+            &["2001:db8::/32"],
+            Some(&["2001:db8::1"])
+        ));
+        assert!(holds("Null", &["true"], None));
+        assert!(!holds("Null", &["false"], None));
+        assert!(holds(
+            "ForAllValues:StringEquals",
+            &["a", "b"],
+            Some(&["a", "b"])
+        ));
+        assert!(!holds(
+            "ForAllValues:StringEquals",
+            &["a"],
+            Some(&["a", "c"])
+        ));
+        assert!(holds("ForAllValues:StringEquals", &["a"], None));
+        assert!(holds("ForAnyValue:StringEquals", &["a"], Some(&["c", "a"])));
+        assert!(!holds("ForAnyValue:StringEquals", &["a"], None));
+    }
+
+    #[test]
+    fn test_condition_uses_request_context() {
+        let policy = policy(json!({
+            "Sid": "OrgOnly",
+            "Effect": "Allow",
+            "Principal": "*",
+            "Action": "s3vectors:QueryVectors",
+            "Resource": "*",
+            "Condition": {
+                "StringEquals": {"aws:PrincipalOrgID": "o-abc"},
+                "Bool": {"aws:SecureTransport": true}
+            }
+        }));
+
+        let request = SimulationRequest::new(ROLE, "s3vectors:QueryVectors", INDEX);
+        let denied = policy.simulate(&request);
+        assert_eq!(denied.decision, Decision::ImplicitDeny);
+        assert_eq!(
+            denied.statements[0].reason,
+            "condition StringEquals aws:PrincipalOrgID not met"
+        );
+
+        let allowed = policy.simulate(
+            &request
+                .clone()
+                .with_context("aws:principalorgid", ["o-abc"]),
+        );
+        assert!(allowed.allowed());
+        let insecure = request
+            .with_context("aws:PrincipalOrgID", ["o-abc"])
+            .with_context("aws:SecureTransport", ["false"]);
+        assert!(!policy.simulate(&insecure).allowed());
+    }
+
+    #[test]
+    fn test_request_validation() {
+        assert!(
+            SimulationRequest::new(ROLE, "s3vectors:QueryVectors", INDEX)
+                .validate()
+                .is_ok()
+        );
+        assert!(SimulationRequest::new(ROLE, "s3vectors:Query*", INDEX)
+            .validate()
+            .is_err());
+        assert!(SimulationRequest::new(ROLE, "s3:GetObject", INDEX)
+            .validate()
+            .is_err());
+        assert!(
+            SimulationRequest::new(ROLE, "s3vectors:QueryVectors", "docs")
+                .validate()
+                .is_err()
+        );
+    }
+}
@@ -8,11 +8,11 @@ use tokio::sync::Semaphore;
 use tracing::{debug, info, warn};
 
 use crate::{
-    batch_put_vectors, create_bucket_and_index,
+    batch_put_vectors, create_bucket_and_index_with,
     document::{Document, DocumentChunk, DocumentProcessor},
     embeddings,
     types::*,
-    S3VectorsClient, Vector, VectorData,
+    CreateIndexOptions, Filter, S3VectorsClient, Vector, VectorData,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +22,18 @@ pub struct RagConfig {
     pub embedding_batch_size: usize,
     pub vector_upload_batch_size: usize,
     pub max_concurrent_embeddings: usize,
+    /// Metadata keys stored on each vector but excluded from query filters.
+    /// Defaults to `content`, the chunk preview, which is too large to filter on.
+    #[serde(default = "default_non_filterable_metadata_keys")]
+    pub non_filterable_metadata_keys: Vec<String>,
+    /// Delete and recreate an existing index whose settings differ from
+    /// these, instead of failing. Deletes every vector in it.
+    #[serde(default)]
+    pub recreate_index_on_mismatch: bool,
+}
+
+fn default_non_filterable_metadata_keys() -> Vec<String> {
+    vec!["content".to_string()]
 }
 
 impl Default for RagConfig {
@@ -32,6 +44,8 @@ impl Default for RagConfig {
             embedding_batch_size: 32,
             vector_upload_batch_size: 100,
             max_concurrent_embeddings: 4,
+            non_filterable_metadata_keys: default_non_filterable_metadata_keys(),
+            recreate_index_on_mismatch: false,
         }
     }
 }
@@ -68,12 +82,21 @@ impl RagPipeline {
             self.config.bucket_name, self.config.index_name
         );
 
-        create_bucket_and_index(
+        let keys = &self.config.non_filterable_metadata_keys;
+        let metadata_configuration = (!keys.is_empty()).then(|| MetadataConfiguration {
+            non_filterable_metadata_keys: Some(keys.clone()),
+        });
+
+        create_bucket_and_index_with(
             &self.client,
             &self.config.bucket_name,
             &self.config.index_name,
             embeddings::embedding_dimensions(),
             DistanceMetric::Cosine,
+            &CreateIndexOptions {
+                metadata_configuration,
+                recreate_on_mismatch: self.config.recreate_index_on_mismatch,
+            },
         )
         .await
         .context("Failed to create bucket and index")?;
@@ -131,9 +154,15 @@ impl RagPipeline {
                     )
                     .await
                     {
-                        Ok(_) => {
-                            total_uploaded += buffer.len();
-                            debug!("Uploaded batch of {} vectors", buffer.len());
+                        Ok(report) => {
+                            total_uploaded += report.succeeded.len();
+                            debug!("Uploaded batch of {} vectors", report.succeeded.len());
+                            if let Some(failure) = report.failed.first() {
+                                tracing::error!("Error uploading vectors: {}", failure.error);
+                                if first_error.is_none() {
+                                    first_error = Some(failure.error.to_string());
+                                }
+                            }
                         }
                         Err(e) => {
                             tracing::error!("Error uploading vectors: {}", e);
@@ -157,9 +186,15 @@ impl RagPipeline {
                 )
                 .await
                 {
-                    Ok(_) => {
-                        total_uploaded += buffer.len();
-                        debug!("Uploaded final batch of {} vectors", buffer.len());
+                    Ok(report) => {
+                        total_uploaded += report.succeeded.len();
+                        debug!("Uploaded final batch of {} vectors", report.succeeded.len());
+                        if let Some(failure) = report.failed.first() {
+                            tracing::error!("Error uploading final batch: {}", failure.error);
+                            if first_error.is_none() {
+                                first_error = Some(failure.error.to_string());
+                            }
+                        }
                     }
                     Err(e) => {
                         tracing::error!("Error uploading final batch: {}", e);
@@ -273,7 +308,7 @@ impl RagPipeline {
         &self,
         query: &str,
         top_k: u32,
-        filter: Option<serde_json::Value>,
+        filter: Option<Filter>,
     ) -> Result<Vec<RagSearchResult>> {
         info!("Searching for: {}", query);
 
@@ -359,8 +394,18 @@ impl RagPipeline {
 
 /// High-level RAG query function
 pub async fn rag_query(pipeline: &RagPipeline, query: &str, top_k: u32) -> Result<String> {
+    rag_query_with_filter(pipeline, query, top_k, None).await
+}
+
+/// [`rag_query`] restricted to documents whose metadata matches `filter`
+pub async fn rag_query_with_filter(
+    pipeline: &RagPipeline,
+    query: &str,
+    top_k: u32,
+    filter: Option<Filter>,
+) -> Result<String> {
     // Search for relevant documents
-    let results = pipeline.search(query, top_k, None).await?;
+    let results = pipeline.search(query, top_k, filter).await?;
 
     if results.is_empty() {
         return Ok("No relevant documents found for your query.".to_string());
@@ -382,5 +427,28 @@ mod tests {
         assert_eq!(config.bucket_name, "rag-vectors-default");
         assert_eq!(config.index_name, "documents-default");
         assert_eq!(config.embedding_batch_size, 32);
+        assert_eq!(config.non_filterable_metadata_keys, vec!["content"]);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_marks_content_non_filterable() {
+        let emulator = crate::emulator::Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+        let config = RagConfig {
+            bucket_name: "rag-bucket".to_string(),
+            index_name: "docs".to_string(),
+            ..Default::default()
+        };
+
+        RagPipeline::new(config, client.clone())
+            .initialize()
+            .await
+            .unwrap();
+
+        let index = client.describe_index("rag-bucket", "docs").await.unwrap();
+        let keys = index
+            .metadata_configuration
+            .and_then(|config| config.non_filterable_metadata_keys);
+        assert_eq!(keys, Some(vec!["content".to_string()]));
     }
 }
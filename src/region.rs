@@ -0,0 +1,426 @@
+//! Regions and partitions S3 Vectors is available in.
+//!
+//! The built-in catalogue covers the `aws`, `aws-cn` and `aws-us-gov`
+//! partitions. Regions launched after a release can be added without
+//! upgrading, either with a JSON file in the same shape as
+//! [`RegionCatalogue::builtin`] (`S3_VECTORS_REGIONS_FILE`, or
+//! `~/.s3-vectors/regions.json` if it exists) or as a comma-separated list in
+//! `S3_VECTORS_REGIONS`:
+//!
+//! ```json
+//! {"partitions": [{"id": "aws", "regions": ["ap-east-2"]}]}
+//! ```
+//!
+//! Regions from overrides are added to the partition with the same `id`, and
+//! any other field given replaces the built-in one. A new `id` adds a
+//! partition, which then needs every field. Overrides that cannot be loaded
+//! are logged and ignored, so endpoints, signing and region checks all see
+//! the same catalogue.
+//!
+//! ```
+//! use s3_vectors::RegionCatalogue;
+//!
+//! let catalogue = RegionCatalogue::builtin();
+//! assert_eq!(
+//!     catalogue.endpoint("cn-north-1"),
+//!     "https://s3vectors.cn-north-1.api.amazonwebservices.com.cn"
+//! );
+//! assert!(catalogue.validate("us-gov-west-1").is_ok());
+//! assert!(catalogue.validate("mars-east-1").is_err());
+//! ```
+
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::credentials::Env;
+
+// Partition fields follow the AWS SDKs' `partitions.json`; regions are the
+// ones S3 Vectors is listed in by the Amazon S3 User Guide at release time.
+const BUILTIN: &str = r#"{
+  "partitions": [
+    {
+      "id": "aws",
+      "regionRegex": "^(us|eu|ap|sa|ca|me|af|il|mx)-\\w+-\\d+$",
+      "dnsSuffix": "amazonaws.com",
+      "dualStackDnsSuffix": "api.aws",
+      "signingName": "s3vectors",
+      "regions": [
+        "us-east-1", "us-east-2", "us-west-1", "us-west-2", "ca-central-1",
+        "eu-central-1", "eu-west-1", "eu-west-2", "eu-west-3", "eu-north-1",
+        "eu-south-1", "ap-south-1", "ap-northeast-1", "ap-northeast-2",
+        "ap-southeast-1", "ap-southeast-2"
+      ]
+    },
+    {
+      "id": "aws-cn",
+      "regionRegex": "^cn-\\w+-\\d+$",
+      "dnsSuffix": "amazonaws.com.cn",
+      "dualStackDnsSuffix": "api.amazonwebservices.com.cn",
+      "signingName": "s3vectors",
+      "regions": ["cn-north-1", "cn-northwest-1"]
+    },
+    {
+      "id": "aws-us-gov",
+      "regionRegex": "^us-gov-\\w+-\\d+$",
+      "dnsSuffix": "amazonaws.com",
+      "dualStackDnsSuffix": "api.aws",
+      "signingName": "s3vectors",
+      "regions": ["us-gov-west-1", "us-gov-east-1"]
+    }
+  ]
+}"#;
+
+/// An AWS partition and the S3 Vectors regions in it
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Partition {
+    /// `aws`, `aws-cn` or `aws-us-gov`, as used in ARNs
+    pub id: String,
+    /// Pattern that every region name in the partition matches
+    pub region_regex: String,
+    /// Suffix of classic endpoints, e.g. STS
+    pub dns_suffix: String,
+    /// Suffix of dual-stack endpoints, which S3 Vectors uses
+    pub dual_stack_dns_suffix: String,
+    /// Service name requests are signed for
+    pub signing_name: String,
+    /// Regions S3 Vectors is available in
+    pub regions: Vec<String>,
+    #[serde(skip)]
+    matcher: Regex,
+}
+
+/// All known partitions
+#[derive(Clone, Debug, Serialize)]
+pub struct RegionCatalogue {
+    pub partitions: Vec<Partition>,
+}
+
+/// A partition as written in a catalogue file, where every field but `id`
+/// may be left out to keep the built-in value
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct PartitionEntry {
+    id: String,
+    region_regex: Option<String>,
+    dns_suffix: Option<String>,
+    dual_stack_dns_suffix: Option<String>,
+    signing_name: Option<String>,
+    #[serde(default)]
+    regions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogueFile {
+    partitions: Vec<PartitionEntry>,
+}
+
+static BUILTIN_CATALOGUE: LazyLock<RegionCatalogue> = LazyLock::new(|| {
+    let mut catalogue = RegionCatalogue {
+        partitions: Vec::new(),
+    };
+    catalogue
+        .merge_json(BUILTIN)
+        .expect("built-in region catalogue is valid");
+    catalogue
+});
+
+static CATALOGUE: LazyLock<RegionCatalogue> =
+    LazyLock::new(|| RegionCatalogue::from_env_or_builtin(&Env::default()));
+
+impl Partition {
+    /// Whether `region` is named like a region of this partition
+    pub fn matches(&self, region: &str) -> bool {
+        self.matcher.is_match(region)
+    }
+
+    /// Whether S3 Vectors is available in `region`
+    pub fn has_region(&self, region: &str) -> bool {
+        self.regions.iter().any(|r| r == region)
+    }
+}
+
+impl RegionCatalogue {
+    /// The catalogue shipped with this release
+    pub fn builtin() -> &'static RegionCatalogue {
+        &BUILTIN_CATALOGUE
+    }
+
+    /// The built-in catalogue with overrides from `S3_VECTORS_REGIONS_FILE`
+    /// (or `~/.s3-vectors/regions.json`) and `S3_VECTORS_REGIONS`, loaded once.
+    /// If the overrides cannot be loaded, a warning is logged and the
+    /// built-in catalogue is used.
+    pub fn global() -> &'static RegionCatalogue {
+        &CATALOGUE
+    }
+
+    fn from_env_or_builtin(env: &Env) -> Self {
+        Self::from_env(env).unwrap_or_else(|e| {
+            warn!("Ignoring region overrides: {e:#}");
+            Self::builtin().clone()
+        })
+    }
+
+    fn from_env(env: &Env) -> Result<Self> {
+        let mut catalogue = Self::builtin().clone();
+
+        let file = match env.get("S3_VECTORS_REGIONS_FILE") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => env
+                .home_dir()
+                .map(|home| home.join(".s3-vectors").join("regions.json"))
+                .filter(|path| path.exists()),
+        };
+        if let Some(path) = file {
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            catalogue
+                .merge_json(&json)
+                .with_context(|| format!("Invalid region catalogue {}", path.display()))?;
+        }
+
+        if let Some(regions) = env.get("S3_VECTORS_REGIONS") {
+            for region in regions.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+                catalogue
+                    .add_region(region)
+                    .context("Invalid region in S3_VECTORS_REGIONS")?;
+            }
+        }
+        Ok(catalogue)
+    }
+
+    /// Merge partitions from a catalogue file into this catalogue
+    pub fn merge_json(&mut self, json: &str) -> Result<()> {
+        let file: CatalogueFile = serde_json::from_str(json)?;
+        for entry in file.partitions {
+            let index = match self.partitions.iter().position(|p| p.id == entry.id) {
+                Some(index) => index,
+                None => {
+                    let missing =
+                        |field: &str| anyhow!("New partition '{}' needs a '{field}'", entry.id);
+                    let regex = entry
+                        .region_regex
+                        .clone()
+                        .ok_or_else(|| missing("regionRegex"))?;
+                    self.partitions.push(Partition {
+                        id: entry.id.clone(),
+                        matcher: compile(&regex)?,
+                        region_regex: regex,
+                        dns_suffix: entry
+                            .dns_suffix
+                            .clone()
+                            .ok_or_else(|| missing("dnsSuffix"))?,
+                        dual_stack_dns_suffix: entry
+                            .dual_stack_dns_suffix
+                            .clone()
+                            .ok_or_else(|| missing("dualStackDnsSuffix"))?,
+                        signing_name: entry.signing_name.clone().unwrap_or("s3vectors".into()),
+                        regions: Vec::new(),
+                    });
+                    self.partitions.len() - 1
+                }
+            };
+
+            let partition = &mut self.partitions[index];
+            if let Some(regex) = entry.region_regex {
+                partition.matcher = compile(&regex)?;
+                partition.region_regex = regex;
+            }
+            if let Some(suffix) = entry.dns_suffix {
+                partition.dns_suffix = suffix;
+            }
+            if let Some(suffix) = entry.dual_stack_dns_suffix {
+                partition.dual_stack_dns_suffix = suffix;
+            }
+            if let Some(name) = entry.signing_name {
+                partition.signing_name = name;
+            }
+            for region in entry.regions {
+                if !partition.matches(&region) {
+                    bail!(
+                        "Region '{region}' does not match partition '{}' ({})",
+                        partition.id,
+                        partition.region_regex
+                    );
+                }
+                if !partition.has_region(&region) {
+                    partition.regions.push(region);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Make S3 Vectors available in `region`, in the partition its name
+    /// belongs to
+    pub fn add_region(&mut self, region: &str) -> Result<()> {
+        let id = self
+            .partition_by_name(region)
+            .map(|p| p.id.clone())
+            .ok_or_else(|| anyhow!("'{region}' does not belong to any known partition"))?;
+        let partition = self
+            .partitions
+            .iter_mut()
+            .find(|p| p.id == id)
+            .expect("partition exists");
+        if !partition.has_region(region) {
+            partition.regions.push(region.to_string());
+        }
+        Ok(())
+    }
+
+    /// The partition with the given `id`, e.g. `aws-cn`
+    pub fn partition_by_id(&self, id: &str) -> Option<&Partition> {
+        self.partitions.iter().find(|p| p.id == id)
+    }
+
+    /// The partition `region` is listed in, or else the one whose naming
+    /// pattern it matches
+    pub fn partition(&self, region: &str) -> Option<&Partition> {
+        self.partitions
+            .iter()
+            .find(|p| p.has_region(region))
+            .or_else(|| self.partition_by_name(region))
+    }
+
+    fn partition_by_name(&self, region: &str) -> Option<&Partition> {
+        self.partitions.iter().find(|p| p.matches(region))
+    }
+
+    /// Check that S3 Vectors is available in `region`
+    pub fn validate(&self, region: &str) -> Result<()> {
+        let Some(partition) = self.partition(region) else {
+            bail!("'{region}' is not an AWS region");
+        };
+        if !partition.has_region(region) {
+            bail!(
+                "S3 Vectors is not available in '{region}'. Known {} regions: {}. If it was \
+                 launched recently, add it with S3_VECTORS_REGIONS or a regions file \
+                 (S3_VECTORS_REGIONS_FILE).",
+                partition.id,
+                partition.regions.join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Regional S3 Vectors endpoint, e.g. `https://s3vectors.us-east-1.api.aws`.
+    /// Unknown regions are assumed to be in the `aws` partition.
+    pub fn endpoint(&self, region: &str) -> String {
+        let suffix = self
+            .partition(region)
+            .or_else(|| self.partition_by_id("aws"))
+            .map_or("api.aws", |p| p.dual_stack_dns_suffix.as_str());
+        format!("https://s3vectors.{region}.{suffix}")
+    }
+
+    /// Service name to sign S3 Vectors requests in `region` for
+    pub fn signing_name(&self, region: &str) -> &str {
+        self.partition(region)
+            .map_or("s3vectors", |p| p.signing_name.as_str())
+    }
+
+    /// DNS suffix of classic endpoints in `region`, e.g. `amazonaws.com.cn`
+    pub fn dns_suffix(&self, region: &str) -> &str {
+        self.partition(region)
+            .map_or("amazonaws.com", |p| p.dns_suffix.as_str())
+    }
+}
+
+fn compile(regex: &str) -> Result<Regex> {
+    Regex::new(regex).with_context(|| format!("Invalid region pattern '{regex}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_partitions() {
+        let catalogue = RegionCatalogue::builtin();
+        assert_eq!(catalogue.partition("us-east-1").unwrap().id, "aws");
+        assert_eq!(catalogue.partition("cn-northwest-1").unwrap().id, "aws-cn");
+        assert_eq!(
+            catalogue.partition("us-gov-east-1").unwrap().id,
+            "aws-us-gov"
+        );
+        // Unlisted regions still resolve by name, but are not available
+        assert_eq!(
+            catalogue.partition("us-gov-north-9").unwrap().id,
+            "aws-us-gov"
+        );
+        assert!(catalogue.partition("mars-east-1").is_none());
+        assert!(catalogue.validate("eu-west-1").is_ok());
+        assert!(catalogue
+            .validate("ap-east-9")
+            .unwrap_err()
+            .to_string()
+            .contains("S3_VECTORS_REGIONS"));
+
+        assert_eq!(
+            catalogue.endpoint("us-west-2"),
+            "https://s3vectors.us-west-2.api.aws"
+        );
+        assert_eq!(catalogue.dns_suffix("cn-north-1"), "amazonaws.com.cn");
+        assert_eq!(catalogue.signing_name("us-gov-west-1"), "s3vectors");
+    }
+
+    #[test]
+    fn test_overrides_from_file_and_env() {
+        let home = tempfile::tempdir().unwrap();
+        let dir = home.path().join(".s3-vectors");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("regions.json"),
+            r#"{"partitions": [
+                {"id": "aws", "regions": ["ap-east-2"]},
+                {"id": "aws-iso", "regionRegex": "^us-iso-\\w+-\\d+$",
+                 "dnsSuffix": "c2s.ic.gov", "dualStackDnsSuffix": "c2s.ic.gov",
+                 "regions": ["us-iso-east-1"]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let home_dir = home.path().to_str().unwrap();
+        let env = Env::from_vars(&[
+            ("HOME", home_dir),
+            ("S3_VECTORS_REGIONS", "il-central-1, cn-north-9"),
+        ]);
+        let catalogue = RegionCatalogue::from_env(&env).unwrap();
+        for region in [
+            "us-east-1",
+            "ap-east-2",
+            "il-central-1",
+            "cn-north-9",
+            "us-iso-east-1",
+        ] {
+            assert!(catalogue.validate(region).is_ok(), "{region}");
+        }
+        assert_eq!(catalogue.partition("cn-north-9").unwrap().id, "aws-cn");
+        assert_eq!(
+            catalogue.endpoint("us-iso-east-1"),
+            "https://s3vectors.us-iso-east-1.c2s.ic.gov"
+        );
+
+        // A region outside every partition, or an incomplete new partition
+        let env = Env::from_vars(&[("HOME", home_dir), ("S3_VECTORS_REGIONS", "moon-1")]);
+        assert!(RegionCatalogue::from_env(&env).is_err());
+        // which leaves only the built-in regions
+        let catalogue = RegionCatalogue::from_env_or_builtin(&env);
+        assert!(catalogue.validate("us-east-1").is_ok());
+        assert!(catalogue.validate("ap-east-2").is_err());
+        let mut catalogue = RegionCatalogue::builtin().clone();
+        assert!(catalogue
+            .merge_json(r#"{"partitions": [{"id": "aws-eusc"}]}"#)
+            .is_err());
+        assert!(catalogue
+            .merge_json(r#"{"partitions": [{"id": "aws", "regions": ["cn-north-2"]}]}"#)
+            .is_err());
+    }
+}
@@ -0,0 +1,358 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::S3VectorsError;
+
+/// Retry behaviour for throttled, server-side and transport failures
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryConfig {
+    /// Number of retries after the initial attempt
+    pub max_retries: u32,
+    /// Base delay for the first retry; the cap doubles after each attempt
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between retries
+    pub max_backoff: Duration,
+    pub mode: RetryMode,
+    /// Give up once this much time has passed since the first attempt,
+    /// even if retries remain
+    pub deadline: Option<Duration>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(5000),
+            mode: RetryMode::Standard,
+            deadline: None,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// The strategy implementing this configuration
+    pub fn strategy(&self) -> Arc<dyn RetryStrategy> {
+        match self.mode {
+            RetryMode::Standard => Arc::new(StandardRetry::new(self.clone())),
+            RetryMode::Adaptive => Arc::new(AdaptiveRetry::new(self.clone())),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RetryMode {
+    /// Exponential backoff with full jitter
+    #[default]
+    Standard,
+    /// Standard retries plus client-side rate limiting that slows down all
+    /// requests from this client after throttling responses
+    Adaptive,
+}
+
+/// Decides whether and when a failed request is sent again.
+///
+/// One strategy instance is shared by every request of a client, so
+/// implementations can keep state across requests (e.g. rate limits).
+pub trait RetryStrategy: fmt::Debug + Send + Sync {
+    /// Delay to wait before sending an attempt (zero to send immediately)
+    fn before_attempt(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Delay before retrying after `error` on attempt `attempt` (0-based), or
+    /// `None` to give up. `retry_after` is the server's `Retry-After` hint.
+    /// The client never sleeps past [`RetryConfig::deadline`], whatever the
+    /// delay.
+    fn retry_delay(
+        &self,
+        attempt: u32,
+        error: &S3VectorsError,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration>;
+
+    /// Called when an attempt succeeds
+    fn on_success(&self) {}
+}
+
+/// Capped exponential backoff with full jitter, driven by
+/// [`S3VectorsError::is_retryable`]
+#[derive(Debug)]
+pub struct StandardRetry {
+    config: RetryConfig,
+}
+
+impl StandardRetry {
+    pub fn new(config: RetryConfig) -> Self {
+        Self { config }
+    }
+
+    /// Full jitter: uniformly random between zero and the exponential cap
+    fn backoff(&self, attempt: u32) -> Duration {
+        let cap = self
+            .config
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.config.max_backoff);
+        cap.mul_f64(fastrand::f64())
+    }
+}
+
+impl RetryStrategy for StandardRetry {
+    fn retry_delay(
+        &self,
+        attempt: u32,
+        error: &S3VectorsError,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if attempt >= self.config.max_retries || !error.is_retryable() {
+            return None;
+        }
+        // The server knows best when it will accept requests again, but a
+        // hint longer than `max_backoff` must not stall the caller
+        Some(match retry_after {
+            Some(hint) => hint.min(self.config.max_backoff),
+            None => self.backoff(attempt),
+        })
+    }
+}
+
+/// Standard retries plus a client-side token bucket.
+///
+/// The bucket is only enforced after the first throttling response. Its fill
+/// rate drops to 70% of the recently measured send rate on every throttle and
+/// recovers additively on success, a simplified form of the AWS SDKs'
+/// adaptive retry mode.
+#[derive(Debug)]
+pub struct AdaptiveRetry {
+    standard: StandardRetry,
+    limiter: Mutex<RateLimiter>,
+}
+
+#[derive(Debug)]
+struct RateLimiter {
+    enabled: bool,
+    /// Tokens (requests) added per second
+    fill_rate: f64,
+    tokens: f64,
+    last_refill: Instant,
+    /// Requests sent in the current one-second window and the smoothed rate
+    window_start: Instant,
+    window_count: u32,
+    measured_rate: f64,
+}
+
+const MIN_FILL_RATE: f64 = 0.5;
+const THROTTLE_BETA: f64 = 0.7;
+
+impl AdaptiveRetry {
+    pub fn new(config: RetryConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            standard: StandardRetry::new(config),
+            limiter: Mutex::new(RateLimiter {
+                enabled: false,
+                fill_rate: 0.0,
+                tokens: 0.0,
+                last_refill: now,
+                window_start: now,
+                window_count: 0,
+                measured_rate: 0.0,
+            }),
+        }
+    }
+
+    /// Current client-side request rate limit, if throttling has been seen
+    pub fn rate_limit(&self) -> Option<f64> {
+        let limiter = self.limiter.lock().unwrap();
+        limiter.enabled.then_some(limiter.fill_rate)
+    }
+}
+
+impl RateLimiter {
+    fn record_send(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.window_start).as_secs_f64();
+        if elapsed >= 1.0 {
+            let rate = self.window_count as f64 / elapsed;
+            self.measured_rate = if self.measured_rate == 0.0 {
+                rate
+            } else {
+                0.8 * self.measured_rate + 0.2 * rate
+            };
+            self.window_start = now;
+            self.window_count = 0;
+        }
+        self.window_count += 1;
+    }
+
+    fn current_rate(&self, now: Instant) -> f64 {
+        let elapsed = now
+            .duration_since(self.window_start)
+            .as_secs_f64()
+            .max(0.001);
+        self.measured_rate
+            .max(self.window_count as f64 / elapsed.max(1.0))
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        // Allow a burst of at most one second's worth of requests
+        self.tokens = (self.tokens + elapsed * self.fill_rate).min(self.fill_rate.max(1.0));
+        self.last_refill = now;
+    }
+}
+
+impl RetryStrategy for AdaptiveRetry {
+    fn before_attempt(&self) -> Duration {
+        let mut limiter = self.limiter.lock().unwrap();
+        let now = Instant::now();
+        limiter.record_send(now);
+        if !limiter.enabled {
+            return Duration::ZERO;
+        }
+
+        limiter.refill(now);
+        // Reserve a token now; a negative balance is paid off by waiting
+        limiter.tokens -= 1.0;
+        if limiter.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-limiter.tokens / limiter.fill_rate)
+        }
+    }
+
+    fn retry_delay(
+        &self,
+        attempt: u32,
+        error: &S3VectorsError,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if matches!(error, S3VectorsError::TooManyRequests(_)) {
+            let mut limiter = self.limiter.lock().unwrap();
+            let now = Instant::now();
+            let base = if limiter.enabled {
+                limiter.fill_rate.min(limiter.current_rate(now))
+            } else {
+                limiter.current_rate(now)
+            };
+            limiter.refill(now);
+            limiter.fill_rate = (base * THROTTLE_BETA).max(MIN_FILL_RATE);
+            limiter.tokens = limiter.tokens.min(limiter.fill_rate);
+            limiter.enabled = true;
+            tracing::debug!("Throttled; limiting requests to {:.2}/s", limiter.fill_rate);
+        }
+        self.standard.retry_delay(attempt, error, retry_after)
+    }
+
+    fn on_success(&self) {
+        let mut limiter = self.limiter.lock().unwrap();
+        if limiter.enabled {
+            let now = Instant::now();
+            limiter.refill(now);
+            let ceiling = (limiter.current_rate(now) * 2.0).max(MIN_FILL_RATE);
+            limiter.fill_rate = (limiter.fill_rate + 0.5).min(ceiling);
+        }
+    }
+}
+
+/// Parse a `Retry-After` header given in seconds or as an HTTP date
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Duration::try_from_secs_f64(seconds).ok();
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorMetadata;
+
+    fn error(status: u16, code: &str) -> S3VectorsError {
+        S3VectorsError::from_response(
+            status,
+            &reqwest::header::HeaderMap::new(),
+            &format!(r#"{{"__type": "{code}", "message": "x"}}"#),
+        )
+    }
+
+    #[test]
+    fn test_standard_retry_classification_and_jitter() {
+        let strategy = StandardRetry::new(RetryConfig {
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(300),
+            ..RetryConfig::default()
+        });
+        let throttled = error(429, "TooManyRequestsException");
+
+        for attempt in 0..2 {
+            let delay = strategy.retry_delay(attempt, &throttled, None).unwrap();
+            assert!(
+                delay
+                    <= Duration::from_millis(100 * 2u64.pow(attempt))
+                        .min(Duration::from_millis(300))
+            );
+        }
+        assert!(strategy.retry_delay(2, &throttled, None).is_none());
+        assert!(strategy
+            .retry_delay(0, &error(404, "NotFoundException"), None)
+            .is_none());
+        assert!(strategy
+            .retry_delay(0, &error(400, "ValidationException"), None)
+            .is_none());
+        assert_eq!(
+            strategy.retry_delay(
+                0,
+                &error(503, "ServiceUnavailableException"),
+                Some(Duration::from_secs(2))
+            ),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(
+            strategy.retry_delay(
+                0,
+                &error(503, "ServiceUnavailableException"),
+                Some(Duration::from_millis(200))
+            ),
+            Some(Duration::from_millis(200))
+        );
+    }
+
+    #[test]
+    fn test_adaptive_retry_limits_rate_after_throttling() {
+        let strategy = AdaptiveRetry::new(RetryConfig::default());
+        assert_eq!(strategy.before_attempt(), Duration::ZERO);
+        assert!(strategy.rate_limit().is_none());
+
+        strategy.retry_delay(
+            0,
+            &S3VectorsError::TooManyRequests(ErrorMetadata::default()),
+            None,
+        );
+        let limit = strategy.rate_limit().unwrap();
+        assert!(limit >= MIN_FILL_RATE);
+
+        // Sending faster than the limit has to wait
+        let waits: Vec<Duration> = (0..5).map(|_| strategy.before_attempt()).collect();
+        assert!(waits.iter().any(|w| !w.is_zero()));
+
+        strategy.on_success();
+        assert!(strategy.rate_limit().unwrap() >= limit);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("3"), Some(Duration::from_secs(3)));
+        assert_eq!(parse_retry_after("0.5"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_retry_after("soon"), None);
+        assert_eq!(parse_retry_after("1e20"), None);
+        assert_eq!(parse_retry_after("-1"), None);
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+}
@@ -0,0 +1,206 @@
+//! Minimal HTTP/1.1 stand-in server for exercising network code in tests.
+//!
+//! Every connection serves exactly one request and is then closed, which keeps
+//! the parser trivial and matches how the tests drive it. Responses can also
+//! inject faults: a delay before answering, or dropping the connection
+//! without any response.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::S3VectorsClient;
+
+#[derive(Clone, Debug)]
+pub(crate) struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+
+    pub fn body_str(&self) -> &str {
+        std::str::from_utf8(&self.body).unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub delay: Option<Duration>,
+    pub reset: bool,
+}
+
+impl MockResponse {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+            delay: None,
+            reset: false,
+        }
+    }
+
+    /// Close the connection after reading the request, without responding
+    pub fn reset() -> Self {
+        Self {
+            reset: true,
+            ..Self::new(0, "")
+        }
+    }
+
+    pub fn json(status: u16, body: serde_json::Value) -> Self {
+        Self::new(status, body.to_string()).header("Content-Type", "application/json")
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Wait this long before sending the response (or resetting)
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+type Handler = dyn Fn(&RecordedRequest) -> MockResponse + Send + Sync;
+
+pub(crate) struct MockServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    /// Start a server on an ephemeral local port that answers every request
+    /// with the response produced by `handler`
+    pub async fn start(
+        handler: impl Fn(&RecordedRequest) -> MockResponse + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    serve_connection(socket, &handler, &recorded).await;
+                });
+            }
+        });
+
+        Self { addr, requests }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// A client sending to this server, with backoffs short enough that
+    /// retries don't slow the tests down
+    pub fn client(&self) -> S3VectorsClient {
+        S3VectorsClient::builder("us-east-1")
+            .endpoint(&self.url())
+            .credentials("AKIDEXAMPLE".to_string(), "secret".to_string(), None)
+            .backoff(Duration::from_millis(1), Duration::from_millis(5))
+            .build()
+            .unwrap()
+    }
+}
+
+async fn serve_connection(
+    mut socket: TcpStream,
+    handler: &Arc<Handler>,
+    recorded: &Arc<Mutex<Vec<RecordedRequest>>>,
+) -> Option<()> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let read = socket.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = buffer[header_end..].to_vec();
+    while body.len() < content_length {
+        let read = socket.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+
+    let request = RecordedRequest {
+        method,
+        path,
+        headers,
+        body,
+    };
+    recorded.lock().unwrap().push(request.clone());
+
+    let response = handler(&request);
+    if let Some(delay) = response.delay {
+        tokio::time::sleep(delay).await;
+    }
+    if response.reset {
+        return None;
+    }
+
+    let mut raw = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        raw.push_str(&format!("{name}: {value}\r\n"));
+    }
+    raw.push_str("\r\n");
+    raw.push_str(&response.body);
+
+    socket.write_all(raw.as_bytes()).await.ok()?;
+    socket.shutdown().await.ok()
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::filter::Filter;
 
 // Enums
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, clap::ValueEnum)]
@@ -46,19 +49,45 @@ pub struct VectorBucket {
     pub encryption_configuration: Option<EncryptionConfiguration>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct EncryptionConfiguration {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub kms_key_arn: Option<String>,
+    /// `AES256` (SSE-S3) or `aws:kms` (SSE-KMS)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sse_type: Option<String>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+impl EncryptionConfiguration {
+    pub const SSE_S3: &'static str = "AES256";
+    pub const SSE_KMS: &'static str = "aws:kms";
+
+    /// Server-side encryption with S3 managed keys, the service default
+    pub fn sse_s3() -> Self {
+        Self {
+            kms_key_arn: None,
+            sse_type: Some(Self::SSE_S3.to_string()),
+        }
+    }
+
+    /// Server-side encryption with the given KMS key ARN
+    pub fn sse_kms(kms_key_arn: impl Into<String>) -> Self {
+        Self {
+            kms_key_arn: Some(kms_key_arn.into()),
+            sse_type: Some(Self::SSE_KMS.to_string()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateVectorBucketRequest {
     pub vector_bucket_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption_configuration: Option<EncryptionConfiguration>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -87,6 +116,7 @@ pub struct ListVectorBucketsRequest {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ListVectorBucketsResponse {
+    #[serde(rename = "vectorBuckets", alias = "buckets")]
     pub buckets: Vec<VectorBucket>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_token: Option<String>,
@@ -106,6 +136,8 @@ pub struct VectorIndex {
     pub status: Option<IndexStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vector_count: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_configuration: Option<MetadataConfiguration>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -196,20 +228,13 @@ pub struct GetIndexRequest {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetIndexResponse {
-    pub index: IndexInfo,
+    pub index: VectorIndex,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct IndexInfo {
-    pub dimension: u32,
-    pub index_name: String,
-    pub vector_bucket_name: String,
-    pub created_at: String,
-    pub status: IndexStatus,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub vector_count: Option<u64>,
-}
+#[deprecated(
+    note = "`GetIndexResponse::index` is a `VectorIndex`, the service's index description"
+)]
+pub type IndexInfo = VectorIndex;
 
 // Vector types
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -271,7 +296,7 @@ pub struct GetVectorsRequest {
     pub index_name: String,
     pub keys: Vec<String>,
     #[serde(default = "default_true")]
-    pub return_vector: bool,
+    pub return_data: bool,
     #[serde(default = "default_true")]
     pub return_metadata: bool,
 }
@@ -286,7 +311,7 @@ pub struct GetVectorsResponse {
 pub struct RetrievedVector {
     pub key: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub vector: Option<Vec<f32>>,
+    pub data: Option<VectorData>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<Value>,
 }
@@ -308,16 +333,34 @@ pub struct ListVectorsRequest {
     pub max_results: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_token: Option<String>,
+    /// Split the index into this many segments (1-16) for parallel listing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segment_count: Option<u32>,
+    /// Segment to list, from 0 to `segment_count - 1`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segment_index: Option<u32>,
+    pub return_data: bool,
+    pub return_metadata: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ListVectorsResponse {
-    pub keys: Vec<String>,
+    pub vectors: Vec<ListOutputVector>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_token: Option<String>,
 }
 
+/// A listed vector; `data` and `metadata` are only present when requested
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ListOutputVector {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<VectorData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryVectorsRequest {
@@ -326,7 +369,7 @@ pub struct QueryVectorsRequest {
     pub query_vector: QueryVector,
     pub top_k: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub filter: Option<Value>,
+    pub filter: Option<Filter>,
     #[serde(default = "default_true")]
     pub return_metadata: bool,
     #[serde(default = "default_true")]
@@ -352,14 +395,38 @@ pub struct MatchedVector {
     pub metadata: Option<Value>,
 }
 
-// Service error response
-#[derive(Clone, Debug, Deserialize)]
-pub struct ServiceError {
-    #[serde(rename = "__type")]
-    pub error_type: Option<String>,
-    pub message: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub request_id: Option<String>,
+#[allow(deprecated)]
+pub use service_error::ServiceError;
+
+// A module of its own, so that the derives may use the deprecated struct
+#[allow(deprecated)]
+mod service_error {
+    use serde::Deserialize;
+
+    /// Error body of a failed response
+    #[deprecated(
+        note = "failed requests are reported as `S3VectorsError` variants carrying an `ErrorMetadata`"
+    )]
+    #[derive(Clone, Debug, Deserialize)]
+    pub struct ServiceError {
+        #[serde(rename = "__type")]
+        pub error_type: Option<String>,
+        pub message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub request_id: Option<String>,
+    }
+}
+
+// Tagging types
+#[derive(Clone, Debug, Serialize)]
+pub struct TagResourceRequest {
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ListTagsForResourceResponse {
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
 }
 
 fn default_true() -> bool {
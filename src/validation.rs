@@ -1,4 +1,5 @@
 use anyhow::{bail, Result};
+use std::collections::BTreeMap;
 
 /// Validate S3 bucket name according to S3 naming rules
 pub fn validate_bucket_name(name: &str) -> Result<()> {
@@ -64,22 +65,113 @@ pub fn validate_top_k(top_k: u32) -> Result<()> {
     Ok(())
 }
 
-/// Validate AWS region is supported for S3 Vectors preview
-pub fn validate_region(region: &str) -> Result<()> {
-    // S3 Vectors is currently in preview and only available in specific regions
-    // Based on AWS documentation, these are the confirmed preview regions
-    const SUPPORTED_REGIONS: &[&str] = &["us-east-1", "us-west-2"];
+/// Validate ListVectors segmentation: both or neither of count (1-16) and
+/// index (below count)
+pub fn validate_segment(segment_count: Option<u32>, segment_index: Option<u32>) -> Result<()> {
+    match (segment_count, segment_index) {
+        (None, None) => Ok(()),
+        (Some(count), Some(index)) => {
+            if count == 0 || count > 16 {
+                bail!("Segment count must be between 1 and 16");
+            }
+            if index >= count {
+                bail!("Segment index must be less than segment count ({count})");
+            }
+            Ok(())
+        }
+        _ => bail!("Segment count and segment index must be specified together"),
+    }
+}
 
-    if !SUPPORTED_REGIONS.contains(&region) {
-        bail!(
-            "S3 Vectors preview is only available in: {}. Please use one of these regions.",
-            SUPPORTED_REGIONS.join(", ")
-        );
+/// Validate non-filterable metadata keys: 1-10 distinct keys of 1-63 characters
+pub fn validate_non_filterable_keys(keys: &[String]) -> Result<()> {
+    if keys.is_empty() || keys.len() > 10 {
+        bail!("Non-filterable metadata keys must list between 1 and 10 keys");
+    }
+
+    for (i, key) in keys.iter().enumerate() {
+        if key.is_empty() || key.len() > 63 {
+            bail!("Non-filterable metadata key '{key}' must be between 1 and 63 characters");
+        }
+        if keys[..i].contains(key) {
+            bail!("Non-filterable metadata key '{key}' is listed more than once");
+        }
     }
 
     Ok(())
 }
 
+/// Validate a KMS key ARN, e.g.
+/// `arn:aws:kms:us-east-1:111122223333:key/1234abcd-12ab-34cd-56ef-1234567890ab`
+pub fn validate_kms_key_arn(arn: &str) -> Result<()> {
+    let parts: Vec<&str> = arn.splitn(6, ':').collect();
+    let [prefix, partition, service, region, account, resource] = parts[..] else {
+        bail!("KMS key '{arn}' must be a full key ARN (arn:aws:kms:<region>:<account>:key/<id>)");
+    };
+
+    if prefix != "arn" || service != "kms" {
+        bail!("KMS key '{arn}' must be a full key ARN (arn:aws:kms:<region>:<account>:key/<id>)");
+    }
+    let Some(known) = crate::RegionCatalogue::global().partition_by_id(partition) else {
+        bail!("KMS key ARN '{arn}' has an unknown partition '{partition}'");
+    };
+    if region.is_empty() {
+        bail!("KMS key ARN '{arn}' has no region");
+    }
+    if !known.matches(region) {
+        bail!("KMS key ARN '{arn}': '{region}' is not a region of partition '{partition}'");
+    }
+    if account.len() != 12 || !account.chars().all(|c| c.is_ascii_digit()) {
+        bail!("KMS key ARN '{arn}' must contain a 12-digit account ID");
+    }
+    match resource.strip_prefix("key/") {
+        Some(id) if !id.is_empty() => Ok(()),
+        _ => bail!("KMS key ARN '{arn}' must name a key (key/<id>), not an alias"),
+    }
+}
+
+/// Validate an S3 Vectors bucket or index ARN, e.g.
+/// `arn:aws:s3vectors:us-east-1:111122223333:bucket/docs/index/embeddings`
+pub fn validate_resource_arn(arn: &str) -> Result<()> {
+    arn.parse::<crate::ResourceArn>()?;
+    Ok(())
+}
+
+/// Validate resource tags: at most 50, keys of 1-128 and values of up to 256
+/// characters from letters, digits, spaces and `_ . : / = + - @`, and no keys
+/// in the reserved `aws:` namespace
+pub fn validate_tags(tags: &BTreeMap<String, String>) -> Result<()> {
+    if tags.len() > 50 {
+        bail!("At most 50 tags are allowed, got {}", tags.len());
+    }
+
+    let allowed = |c: char| c.is_alphanumeric() || c.is_whitespace() || "_.:/=+-@".contains(c);
+    for (key, value) in tags {
+        let key_len = key.chars().count();
+        if key_len == 0 || key_len > 128 {
+            bail!("Tag key '{key}' must be between 1 and 128 characters");
+        }
+        if value.chars().count() > 256 {
+            bail!("Value of tag '{key}' must be at most 256 characters");
+        }
+        if key.to_ascii_lowercase().starts_with("aws:") {
+            bail!("Tag key '{key}' uses the reserved 'aws:' prefix");
+        }
+        if !key.chars().all(allowed) || !value.chars().all(allowed) {
+            bail!(
+                "Tag '{key}' may only contain letters, numbers, spaces and the characters _ . : / = + - @"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Validate that S3 Vectors is available in `region`, according to the
+/// [`RegionCatalogue`](crate::RegionCatalogue) and its overrides
+pub fn validate_region(region: &str) -> Result<()> {
+    crate::RegionCatalogue::global().validate(region)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,6 +199,69 @@ mod tests {
         assert!(validate_index_name("index name").is_err()); // contains space
     }
 
+    #[test]
+    fn test_non_filterable_keys_validation() {
+        let keys = |ks: &[&str]| ks.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+        assert!(validate_non_filterable_keys(&keys(&["content"])).is_ok());
+        assert!(validate_non_filterable_keys(&keys(&["content", "source_path"])).is_ok());
+
+        assert!(validate_non_filterable_keys(&[]).is_err()); // empty
+        assert!(validate_non_filterable_keys(&vec!["k".to_string(); 11]).is_err()); // too many
+        assert!(validate_non_filterable_keys(&keys(&[""])).is_err()); // empty key
+        assert!(validate_non_filterable_keys(&["a".repeat(64)]).is_err()); // too long
+        assert!(validate_non_filterable_keys(&keys(&["a", "b", "a"])).is_err());
+    }
+
+    #[test]
+    fn test_kms_key_arn_validation() {
+        let key = "arn:aws:kms:us-east-1:111122223333:key/1234abcd-12ab-34cd-56ef-1234567890ab";
+        assert!(validate_kms_key_arn(key).is_ok());
+        assert!(validate_kms_key_arn("arn:aws-cn:kms:cn-north-1:111122223333:key/abc").is_ok());
+
+        assert!(validate_kms_key_arn("1234abcd-12ab-34cd-56ef-1234567890ab").is_err()); // bare key ID
+        assert!(validate_kms_key_arn("arn:aws:kms:us-east-1:111122223333:alias/mine").is_err());
+        assert!(validate_kms_key_arn("arn:aws:s3:us-east-1:111122223333:key/abc").is_err());
+        assert!(validate_kms_key_arn("arn:aws:kms:us-east-1:1111:key/abc").is_err()); // short account
+        assert!(validate_kms_key_arn("arn:aws:kms:us-east-1:111122223333:key/").is_err());
+        assert!(validate_kms_key_arn("arn:aws-xx:kms:us-east-1:111122223333:key/abc").is_err());
+        assert!(validate_kms_key_arn("arn:aws-cn:kms:us-east-1:111122223333:key/abc").is_err());
+    }
+
+    #[test]
+    fn test_resource_arn_validation() {
+        let bucket = "arn:aws:s3vectors:us-east-1:111122223333:bucket/docs";
+        assert!(validate_resource_arn(bucket).is_ok());
+        assert!(validate_resource_arn(&format!("{bucket}/index/embeddings")).is_ok());
+
+        assert!(validate_resource_arn("docs").is_err());
+        assert!(validate_resource_arn("arn:aws:s3:::docs").is_err());
+        assert!(validate_resource_arn("arn:aws:s3vectors:us-east-1:111122223333:docs").is_err());
+        assert!(validate_resource_arn(&format!("{bucket}/index/")).is_err());
+    }
+
+    #[test]
+    fn test_tags_validation() {
+        let tags = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        assert!(validate_tags(&tags(&[("env", "prod"), ("team", "ml search")])).is_ok());
+        assert!(validate_tags(&tags(&[("cost-center", "")])).is_ok());
+
+        assert!(validate_tags(&tags(&[("", "x")])).is_err()); // empty key
+        assert!(validate_tags(&tags(&[(&"k".repeat(129), "x")])).is_err()); // key too long
+        assert!(validate_tags(&tags(&[("k", &"v".repeat(257))])).is_err()); // value too long
+        assert!(validate_tags(&tags(&[("aws:createdBy", "me")])).is_err()); // reserved prefix
+        assert!(validate_tags(&tags(&[("env", "prod;dev")])).is_err()); // invalid character
+
+        let many = (0..51)
+            .map(|i| (format!("k{i}"), "v".to_string()))
+            .collect();
+        assert!(validate_tags(&many).is_err()); // too many
+    }
+
     #[test]
     fn test_dimension_validation() {
         assert!(validate_dimensions(128).is_ok());
@@ -115,4 +270,16 @@ mod tests {
         assert!(validate_dimensions(0).is_err());
         assert!(validate_dimensions(4097).is_err());
     }
+
+    #[test]
+    fn test_segment_validation() {
+        assert!(validate_segment(None, None).is_ok());
+        assert!(validate_segment(Some(16), Some(15)).is_ok());
+
+        assert!(validate_segment(Some(0), Some(0)).is_err());
+        assert!(validate_segment(Some(17), Some(0)).is_err());
+        assert!(validate_segment(Some(4), Some(4)).is_err());
+        assert!(validate_segment(Some(4), None).is_err());
+        assert!(validate_segment(None, Some(0)).is_err());
+    }
 }
@@ -0,0 +1,488 @@
+//! Waiting for buckets, indexes and vectors to reach a state.
+//!
+//! The service creates and deletes buckets and indexes asynchronously, and
+//! put vectors take a moment to show up in queries. [`Waiter`] polls until
+//! that has happened, backing off between checks, up to a deadline.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use tokio::time::sleep;
+use tracing::debug;
+
+use crate::error::S3VectorsError;
+use crate::types::*;
+use crate::S3VectorsClient;
+
+/// Most results a query can return, used to look for a vector among its
+/// nearest neighbours
+const MAX_TOP_K: u32 = 30;
+
+/// Most keys [`Waiter::vectors_visible`] looks for with queries
+const QUERY_SAMPLE: usize = 3;
+
+/// Polls the service until a bucket, index or vectors reach a state.
+///
+/// The first check runs immediately. Later checks wait `interval`, multiplied
+/// by `backoff` after each check up to `max_interval`, and the waiter gives up
+/// with [`WaitError::Timeout`] once `deadline` has passed since the first check.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Waiter {
+    /// Delay before the second check
+    pub interval: Duration,
+    /// Upper bound for the delay between checks
+    pub max_interval: Duration,
+    /// Factor applied to the delay after each check; values below 1 (or NaN)
+    /// count as 1
+    pub backoff: f64,
+    /// Total time to keep polling
+    pub deadline: Duration,
+}
+
+impl Default for Waiter {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(10),
+            backoff: 1.5,
+            deadline: Duration::from_secs(300),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WaitError {
+    #[error("Timed out after {elapsed:?} waiting for {target}")]
+    Timeout { target: String, elapsed: Duration },
+
+    /// The resource reported a `Failed` status
+    #[error("Gave up waiting for {target}: it reported status Failed")]
+    Failed { target: String },
+
+    #[error(transparent)]
+    Service(#[from] S3VectorsError),
+}
+
+impl Waiter {
+    /// A waiter giving up after `deadline`, with the default intervals
+    pub fn with_deadline(deadline: Duration) -> Self {
+        Self {
+            deadline,
+            ..Default::default()
+        }
+    }
+
+    /// Wait until the bucket exists and is active. Buckets without a
+    /// reported status count as active once they can be described.
+    pub async fn bucket_active(
+        &self,
+        client: &S3VectorsClient,
+        bucket_name: &str,
+    ) -> Result<VectorBucket, WaitError> {
+        let target = || format!("bucket {bucket_name} to become active");
+        let mut attempts = self.attempts();
+        loop {
+            match client.describe_vector_bucket(bucket_name).await {
+                Ok(bucket) => match bucket.status {
+                    None | Some(BucketStatus::Active) => return Ok(bucket),
+                    Some(BucketStatus::Failed) => {
+                        return Err(WaitError::Failed { target: target() })
+                    }
+                    Some(status) => debug!("Bucket {} is {:?}", bucket_name, status),
+                },
+                // Not visible yet right after creation
+                Err(S3VectorsError::NotFound(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+            attempts.pause(target).await?;
+        }
+    }
+
+    /// Wait until the bucket can no longer be described
+    pub async fn bucket_deleted(
+        &self,
+        client: &S3VectorsClient,
+        bucket_name: &str,
+    ) -> Result<(), WaitError> {
+        let target = || format!("bucket {bucket_name} to be deleted");
+        let mut attempts = self.attempts();
+        loop {
+            match client.describe_vector_bucket(bucket_name).await {
+                Ok(_) => {}
+                Err(S3VectorsError::NotFound(_)) => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+            attempts.pause(target).await?;
+        }
+    }
+
+    /// Wait until the index exists and is active. Indexes without a
+    /// reported status count as active once they can be described.
+    pub async fn index_active(
+        &self,
+        client: &S3VectorsClient,
+        bucket_name: &str,
+        index_name: &str,
+    ) -> Result<VectorIndex, WaitError> {
+        let target = || format!("index {bucket_name}/{index_name} to become active");
+        let mut attempts = self.attempts();
+        loop {
+            match client.describe_index(bucket_name, index_name).await {
+                Ok(index) => match index.status {
+                    None | Some(IndexStatus::Active) => return Ok(index),
+                    Some(IndexStatus::Failed) => {
+                        return Err(WaitError::Failed { target: target() })
+                    }
+                    Some(status) => debug!("Index {} is {:?}", index_name, status),
+                },
+                Err(S3VectorsError::NotFound(_)) => {}
+                Err(e) => return Err(e.into()),
+            }
+            attempts.pause(target).await?;
+        }
+    }
+
+    /// Wait until the index can no longer be described
+    pub async fn index_deleted(
+        &self,
+        client: &S3VectorsClient,
+        bucket_name: &str,
+        index_name: &str,
+    ) -> Result<(), WaitError> {
+        let target = || format!("index {bucket_name}/{index_name} to be deleted");
+        let mut attempts = self.attempts();
+        loop {
+            match client.describe_index(bucket_name, index_name).await {
+                Ok(_) => {}
+                Err(S3VectorsError::NotFound(_)) => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+            attempts.pause(target).await?;
+        }
+    }
+
+    /// Wait until every key can be fetched, and a sample of them is returned
+    /// by a query for its own vector, i.e. until freshly put vectors show up
+    /// in searches.
+    ///
+    /// Keys are fetched in batched `GetVectors` calls; only up to
+    /// `QUERY_SAMPLE` spread-out keys are queried, since the index catches up
+    /// with all of them at about the same time.
+    pub async fn vectors_visible(
+        &self,
+        client: &S3VectorsClient,
+        bucket_name: &str,
+        index_name: &str,
+        keys: &[String],
+    ) -> Result<(), WaitError> {
+        let target = || {
+            format!(
+                "{} vector(s) in {bucket_name}/{index_name} to become visible",
+                keys.len()
+            )
+        };
+        let mut unfetched: Vec<String> = keys.to_vec();
+        unfetched.sort();
+        unfetched.dedup();
+        let step = unfetched.len().div_ceil(QUERY_SAMPLE).max(1);
+        let mut unqueried: Vec<String> = unfetched.iter().step_by(step).cloned().collect();
+        let mut data: HashMap<String, Vec<f32>> = HashMap::new();
+        let mut attempts = self.attempts();
+
+        loop {
+            if !unfetched.is_empty() {
+                let report = client
+                    .get_vectors_chunked(GetVectorsRequest {
+                        vector_bucket_name: bucket_name.to_string(),
+                        index_name: index_name.to_string(),
+                        keys: unfetched.clone(),
+                        return_data: true,
+                        return_metadata: false,
+                    })
+                    .await?;
+                for vector in report.vectors {
+                    if let Some(vector_data) = vector.data {
+                        data.insert(vector.key, vector_data.float32);
+                    }
+                }
+                unfetched.retain(|key| !data.contains_key(key));
+            }
+
+            if unfetched.is_empty() {
+                let mut still_unqueried = Vec::new();
+                for key in unqueried {
+                    let response = client
+                        .query_vectors(QueryVectorsRequest {
+                            vector_bucket_name: bucket_name.to_string(),
+                            index_name: index_name.to_string(),
+                            query_vector: QueryVector {
+                                float32: data[&key].clone(),
+                            },
+                            top_k: MAX_TOP_K,
+                            filter: None,
+                            return_metadata: false,
+                            return_distance: true,
+                        })
+                        .await?;
+                    if !finds(&response, &key) {
+                        still_unqueried.push(key);
+                    }
+                }
+                unqueried = still_unqueried;
+                if unqueried.is_empty() {
+                    return Ok(());
+                }
+            }
+
+            debug!(
+                "{} vector(s) not fetchable and {} not found by queries yet",
+                unfetched.len(),
+                unqueried.len()
+            );
+            attempts.pause(target).await?;
+        }
+    }
+
+    fn attempts(&self) -> Attempts<'_> {
+        Attempts {
+            waiter: self,
+            start: Instant::now(),
+            delay: self.interval,
+            // `max` also turns NaN into 1
+            backoff: self.backoff.max(1.0),
+        }
+    }
+}
+
+/// Whether a query for `key`'s own vector found it. A full page of vectors
+/// identical to it also counts, as the key can tie with all of them.
+fn finds(response: &QueryVectorsResponse, key: &str) -> bool {
+    let identical = |v: &MatchedVector| v.distance.is_some_and(|d| d.abs() <= 1e-6);
+    response.vectors.iter().any(|v| v.key == key)
+        || (response.vectors.len() == MAX_TOP_K as usize && response.vectors.iter().all(identical))
+}
+
+/// Sleeps between checks and enforces the deadline
+struct Attempts<'a> {
+    waiter: &'a Waiter,
+    start: Instant,
+    delay: Duration,
+    backoff: f64,
+}
+
+impl Attempts<'_> {
+    async fn pause(&mut self, target: impl FnOnce() -> String) -> Result<(), WaitError> {
+        let elapsed = self.start.elapsed();
+        if elapsed >= self.waiter.deadline {
+            return Err(WaitError::Timeout {
+                target: target(),
+                elapsed,
+            });
+        }
+
+        // Never sleep past the deadline, so the last check runs just before it
+        sleep(self.delay.min(self.waiter.deadline - elapsed)).await;
+        self.delay = self.next_delay();
+        Ok(())
+    }
+
+    /// The delay after the current one, capped at `max_interval` also when
+    /// the product does not fit in a `Duration`
+    fn next_delay(&self) -> Duration {
+        Duration::try_from_secs_f64(self.delay.as_secs_f64() * self.backoff)
+            .map_or(self.waiter.max_interval, |delay| {
+                delay.min(self.waiter.max_interval)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn fast() -> Waiter {
+        Waiter {
+            interval: Duration::from_millis(1),
+            max_interval: Duration::from_millis(5),
+            backoff: 2.0,
+            deadline: Duration::from_secs(5),
+        }
+    }
+
+    fn index_body(status: &str) -> serde_json::Value {
+        serde_json::json!({
+            "index": {
+                "indexName": "docs",
+                "indexArn": "arn:aws:s3vectors:us-east-1:123456789012:bucket/b/index/docs",
+                "dimension": 3,
+                "dataType": "float32",
+                "distanceMetric": "cosine",
+                "creationTime": 1700000000.0,
+                "status": status,
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_index_active_polls_status() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let server = MockServer::start(move |_| {
+            let status = match counter.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => "Creating",
+                _ => "Active",
+            };
+            MockResponse::json(200, index_body(status))
+        })
+        .await;
+
+        let index = fast()
+            .index_active(&server.client(), "bucket", "docs")
+            .await
+            .unwrap();
+        assert_eq!(index.status, Some(IndexStatus::Active));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let server = MockServer::start(|_| MockResponse::json(200, index_body("Failed"))).await;
+        let err = fast()
+            .index_active(&server.client(), "bucket", "docs")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WaitError::Failed { .. }), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_waiter_times_out_at_deadline() {
+        let server = MockServer::start(|_| MockResponse::json(200, index_body("Creating"))).await;
+        let waiter = Waiter {
+            deadline: Duration::from_millis(50),
+            ..fast()
+        };
+
+        let start = Instant::now();
+        let err = waiter
+            .index_active(&server.client(), "bucket", "docs")
+            .await
+            .unwrap_err();
+        match err {
+            WaitError::Timeout { target, elapsed } => {
+                assert_eq!(target, "index bucket/docs to become active");
+                assert!(elapsed >= Duration::from_millis(50));
+            }
+            other => panic!("expected timeout, got {other:?}"),
+        }
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_deleted_and_visible_against_emulator() {
+        let emulator = crate::emulator::Emulator::new().spawn().await.unwrap();
+        let client = emulator.client().unwrap();
+        let waiter = fast();
+
+        client.create_vector_bucket("waits").await.unwrap();
+        waiter.bucket_active(&client, "waits").await.unwrap();
+        client
+            .create_index(CreateIndexRequest {
+                vector_bucket_name: "waits".to_string(),
+                index_name: "docs".to_string(),
+                data_type: DataType::Float32,
+                dimension: 2,
+                distance_metric: DistanceMetric::Euclidean,
+                metadata_configuration: None,
+            })
+            .await
+            .unwrap();
+        waiter.index_active(&client, "waits", "docs").await.unwrap();
+
+        client
+            .put_vectors(PutVectorsRequest {
+                vector_bucket_name: "waits".to_string(),
+                index_name: "docs".to_string(),
+                vectors: (0..3)
+                    .map(|i| Vector {
+                        key: format!("v{i}"),
+                        data: VectorData {
+                            float32: vec![i as f32, 1.0],
+                        },
+                        metadata: None,
+                    })
+                    .collect(),
+            })
+            .await
+            .unwrap();
+        let keys = ["v0".to_string(), "v2".to_string()];
+        waiter
+            .vectors_visible(&client, "waits", "docs", &keys)
+            .await
+            .unwrap();
+
+        // A key that never arrives times out
+        let short = Waiter {
+            deadline: Duration::from_millis(20),
+            ..fast()
+        };
+        let err = short
+            .vectors_visible(&client, "waits", "docs", &["missing".to_string()])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, WaitError::Timeout { .. }), "{err:?}");
+
+        client.delete_index("waits", "docs").await.unwrap();
+        waiter
+            .index_deleted(&client, "waits", "docs")
+            .await
+            .unwrap();
+        client.delete_vector_bucket("waits").await.unwrap();
+        waiter.bucket_deleted(&client, "waits").await.unwrap();
+    }
+
+    #[test]
+    fn test_backoff_is_clamped() {
+        let delays = |backoff: f64| {
+            let waiter = Waiter {
+                interval: Duration::from_secs(1),
+                max_interval: Duration::from_secs(10),
+                backoff,
+                deadline: Duration::MAX,
+            };
+            let mut attempts = waiter.attempts();
+            (0..3)
+                .map(|_| {
+                    attempts.delay = attempts.next_delay();
+                    attempts.delay.as_secs()
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(delays(2.0), [2, 4, 8]);
+        assert_eq!(delays(f64::INFINITY), [10, 10, 10]);
+        assert_eq!(delays(f64::MAX), [10, 10, 10]);
+        assert_eq!(delays(1e300), [10, 10, 10]);
+        assert_eq!(delays(f64::NAN), [1, 1, 1]);
+        assert_eq!(delays(0.5), [1, 1, 1]);
+    }
+
+    #[test]
+    fn test_query_finds_key_among_identical_vectors() {
+        let matched = |key: &str, distance: f32| MatchedVector {
+            key: key.to_string(),
+            distance: Some(distance),
+            metadata: None,
+        };
+        let response = |vectors| QueryVectorsResponse { vectors };
+        assert!(finds(
+            &response(vec![matched("a", 0.0), matched("b", 0.5)]),
+            "b"
+        ));
+        assert!(!finds(&response(vec![matched("a", 0.0)]), "b"));
+
+        // Ranked behind more identical vectors than one query returns
+        let ties = (0..MAX_TOP_K).map(|i| matched(&format!("dup{i}"), 0.0));
+        assert!(finds(&response(ties.collect()), "b"));
+        let near = (0..MAX_TOP_K).map(|i| matched(&format!("near{i}"), 0.1));
+        assert!(!finds(&response(near.collect()), "b"));
+    }
+}
@@ -0,0 +1,16 @@
+use std::process::{Command, Stdio};
+
+#[test]
+fn test_init_runs_without_building_a_client() {
+    // A region outside the catalogue would fail client creation
+    let output = Command::new(env!("CARGO_BIN_EXE_s3-vectors"))
+        .args(["--region", "af-south-1", "init"])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("Welcome to S3 Vectors Setup"), "{stdout}");
+    assert!(!stderr.contains("not available"), "{stderr}");
+}